@@ -95,4 +95,7 @@ pub enum Errno {
     TooBig = 12,
     MapFailed = 13,
     InvSyscall = 14,
+    TimedOut = 15,
+    Canceled = 16,
+    BadState = 17,
 }
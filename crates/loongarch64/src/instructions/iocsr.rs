@@ -0,0 +1,45 @@
+use core::arch::asm;
+
+/// Reads a 32-bit IOCSR.
+///
+/// # Safety
+/// Reading some IOCSRs has side effects on the interrupt controllers.
+pub unsafe fn read_w(address: u64) -> u32 {
+    let value: u32;
+    unsafe {
+        asm!("iocsrrd.w {}, {}", out(reg) value, in(reg) address);
+    }
+    value
+}
+
+/// Writes a 32-bit IOCSR.
+///
+/// # Safety
+/// The caller must ensure the write keeps the hardware in a consistent state.
+pub unsafe fn write_w(address: u64, value: u32) {
+    unsafe {
+        asm!("iocsrwr.w {}, {}", in(reg) value, in(reg) address);
+    }
+}
+
+/// Reads a 64-bit IOCSR.
+///
+/// # Safety
+/// Reading some IOCSRs has side effects on the interrupt controllers.
+pub unsafe fn read_d(address: u64) -> u64 {
+    let value: u64;
+    unsafe {
+        asm!("iocsrrd.d {}, {}", out(reg) value, in(reg) address);
+    }
+    value
+}
+
+/// Writes a 64-bit IOCSR.
+///
+/// # Safety
+/// The caller must ensure the write keeps the hardware in a consistent state.
+pub unsafe fn write_d(address: u64, value: u64) {
+    unsafe {
+        asm!("iocsrwr.d {}, {}", in(reg) value, in(reg) address);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod interrupt;
+pub mod iocsr;
+pub mod time;
 pub mod tlb;
@@ -0,0 +1,29 @@
+use core::arch::asm;
+
+use bit_field::BitField;
+
+/// Reads the constant-frequency stable counter.
+pub fn read_stable_counter() -> u64 {
+    let value: u64;
+    unsafe {
+        asm!("rdtime.d {}, $zero", out(reg) value);
+    }
+    value
+}
+
+/// Returns the stable counter frequency in Hz, computed from CPUCFG words 4 and 5.
+pub fn stable_counter_frequency() -> u64 {
+    let base = cpucfg(4);
+    let scale = cpucfg(5);
+    let multiplier = scale.get_bits(0..16);
+    let divisor = scale.get_bits(16..32);
+    base * multiplier / divisor
+}
+
+fn cpucfg(word: u64) -> u64 {
+    let value: u64;
+    unsafe {
+        asm!("cpucfg {}, {}", out(reg) value, in(reg) word);
+    }
+    value
+}
@@ -13,6 +13,13 @@ pub fn flush(addr: VirtAddr) {
     }
 }
 
+/// Invalidates every non-global entry tagged with `asid`.
+pub fn flush_asid(asid: u64) {
+    unsafe {
+        asm!("invtlb 0x04, {}, $zero", in(reg) asid);
+    }
+}
+
 pub fn flush_all() {
     unsafe {
         asm!("invtlb 0, $zero, $zero");
@@ -54,4 +54,8 @@ impl ExceptionStatus {
     pub fn read_ecode(&self) -> u64 {
         self.read().get_bits(16..=21)
     }
+
+    pub fn read_esubcode(&self) -> u64 {
+        self.read().get_bits(22..=30)
+    }
 }
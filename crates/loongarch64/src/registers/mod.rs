@@ -3,12 +3,14 @@ pub use int::*;
 pub use ipi::*;
 pub use mode::*;
 pub use paging::*;
+pub use watch::*;
 
 mod dmw;
 mod int;
 mod ipi;
 mod mode;
 mod paging;
+mod watch;
 
 #[macro_export]
 macro_rules! define_csr {
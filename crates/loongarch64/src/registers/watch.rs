@@ -0,0 +1,175 @@
+use bit_field::BitField;
+
+use crate::{PrivilegeLevel, define_csr};
+
+define_csr!(MemoryWatchConfig, 0x300);
+define_csr!(MemoryWatchStatus, 0x301);
+define_csr!(FetchWatchConfig, 0x380);
+define_csr!(FetchWatchStatus, 0x381);
+
+impl MemoryWatchConfig {
+    /// Number of memory watchpoints implemented.
+    pub fn read_count(&self) -> usize {
+        self.read().get_bits(0..=5) as usize
+    }
+}
+
+impl FetchWatchConfig {
+    /// Number of fetch watchpoints implemented.
+    pub fn read_count(&self) -> usize {
+        self.read().get_bits(0..=5) as usize
+    }
+}
+
+impl MemoryWatchStatus {
+    /// Bit mask of the watchpoints that triggered.
+    pub fn read_hits(&self) -> u64 {
+        self.read().get_bits(0..=13)
+    }
+
+    pub fn clear_hits(&self) {
+        self.write(self.read_hits());
+    }
+}
+
+impl FetchWatchStatus {
+    /// Bit mask of the watchpoints that triggered.
+    pub fn read_hits(&self) -> u64 {
+        self.read().get_bits(0..=13)
+    }
+
+    pub fn clear_hits(&self) {
+        self.write(self.read_hits());
+    }
+
+    /// Ignores the next fetch watchpoint hit, so that the instruction at
+    /// the return address runs once before the watchpoint fires.
+    pub fn set_skip(&self) {
+        self.write(1 << 16);
+    }
+}
+
+/// Highest number of watchpoints of one kind addressable through [`WatchSlot`].
+pub const WATCH_SLOT_COUNT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Fetch,
+    Memory,
+}
+
+/// The configuration registers of one watchpoint.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchSlot {
+    kind: WatchKind,
+    index: usize,
+}
+
+impl WatchSlot {
+    pub fn new(kind: WatchKind, index: usize) -> Self {
+        assert!(index < WATCH_SLOT_COUNT);
+        Self { kind, index }
+    }
+
+    /// Sets the address compared against.
+    pub fn set_addr(&self, addr: u64) {
+        self.write(0, addr);
+    }
+
+    /// Sets the address bits ignored by the comparison.
+    pub fn set_mask(&self, mask: u64) {
+        self.write(1, mask);
+    }
+
+    /// Sets the control register, 0 disables the watchpoint.
+    pub fn set_ctrl(&self, ctrl: WatchCtrlBuilder) {
+        self.write(2, ctrl.value);
+    }
+
+    fn write(&self, offset: u32, value: u64) {
+        macro_rules! write_slot {
+            ($base: literal) => {
+                match (self.index, offset) {
+                    (0, 0) => write_indexed::<{ $base }>(value),
+                    (0, 1) => write_indexed::<{ $base + 1 }>(value),
+                    (0, _) => write_indexed::<{ $base + 2 }>(value),
+                    (1, 0) => write_indexed::<{ $base + 0x8 }>(value),
+                    (1, 1) => write_indexed::<{ $base + 0x9 }>(value),
+                    (1, _) => write_indexed::<{ $base + 0xa }>(value),
+                    (2, 0) => write_indexed::<{ $base + 0x10 }>(value),
+                    (2, 1) => write_indexed::<{ $base + 0x11 }>(value),
+                    (2, _) => write_indexed::<{ $base + 0x12 }>(value),
+                    (3, 0) => write_indexed::<{ $base + 0x18 }>(value),
+                    (3, 1) => write_indexed::<{ $base + 0x19 }>(value),
+                    (3, _) => write_indexed::<{ $base + 0x1a }>(value),
+                    (4, 0) => write_indexed::<{ $base + 0x20 }>(value),
+                    (4, 1) => write_indexed::<{ $base + 0x21 }>(value),
+                    (4, _) => write_indexed::<{ $base + 0x22 }>(value),
+                    (5, 0) => write_indexed::<{ $base + 0x28 }>(value),
+                    (5, 1) => write_indexed::<{ $base + 0x29 }>(value),
+                    (5, _) => write_indexed::<{ $base + 0x2a }>(value),
+                    (6, 0) => write_indexed::<{ $base + 0x30 }>(value),
+                    (6, 1) => write_indexed::<{ $base + 0x31 }>(value),
+                    (6, _) => write_indexed::<{ $base + 0x32 }>(value),
+                    (7, 0) => write_indexed::<{ $base + 0x38 }>(value),
+                    (7, 1) => write_indexed::<{ $base + 0x39 }>(value),
+                    (7, _) => write_indexed::<{ $base + 0x3a }>(value),
+                    _ => unreachable!(),
+                }
+            };
+        }
+
+        match self.kind {
+            WatchKind::Memory => write_slot!(0x310),
+            WatchKind::Fetch => write_slot!(0x390),
+        }
+    }
+}
+
+fn write_indexed<const CSR: u32>(value: u64) {
+    unsafe {
+        core::arch::asm!("csrwr {}, {}", in(reg) value, const CSR);
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct WatchCtrlBuilder {
+    value: u64,
+}
+
+impl WatchCtrlBuilder {
+    pub fn new() -> Self {
+        Self { value: 0 }
+    }
+
+    /// Enables the watchpoint for code running at `privilege`.
+    pub fn enable_privilege(&mut self, privilege: PrivilegeLevel) -> Self {
+        self.value.set_bit(1 + privilege as usize, true);
+        *self
+    }
+
+    /// Memory watchpoints only: fires on loads.
+    pub fn watch_load(&mut self) -> Self {
+        self.value.set_bit(8, true);
+        *self
+    }
+
+    /// Memory watchpoints only: fires on stores.
+    pub fn watch_store(&mut self) -> Self {
+        self.value.set_bit(9, true);
+        *self
+    }
+
+    /// Memory watchpoints only: the access size watched, 1, 2, 4 or 8 bytes.
+    pub fn set_len(&mut self, len: usize) -> Self {
+        let len = match len {
+            8 => 0,
+            4 => 1,
+            2 => 2,
+            1 => 3,
+            _ => panic!("Invalid watchpoint length {}", len),
+        };
+        self.value.set_bits(10..=11, len);
+        *self
+    }
+}
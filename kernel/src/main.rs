@@ -16,15 +16,17 @@ use limine::{
     request::{FramebufferRequest, ModuleRequest, StackSizeRequest},
 };
 use object::{
+    dev::Resource,
     ipc::{Channel, MessagePacket},
     mem::{PAGE_SIZE, Vmo, align_up_by_page_size},
-    object::{Handle, Rights},
-    task::Process,
+    object::{Handle, KernelObject, Rights},
+    task::{Job, Process},
 };
 use protocol::{
-    BOOT_DATA_CNT, BOOT_FB_HANDLE_IDX, BOOT_HANDLE_CNT, BOOT_PCIE_HANDLE_IDX, BOOT_TERM_HANDLE_IDX,
-    FB_HEIGHT_IDX, FB_WIDTH_IDX, FIRST_HANDLE, PCIE_INFO_LEN_IDX, PROC_HANDLE_IDX,
-    PROC_START_HANDLE_CNT, ProcessStartInfo, TERM_SIZE_IDX, VMAR_HANDLE_IDX,
+    BOOT_DATA_CNT, BOOT_FB_HANDLE_IDX, BOOT_HANDLE_CNT, BOOT_JOB_HANDLE_IDX, BOOT_PCIE_HANDLE_IDX,
+    BOOT_RESOURCE_HANDLE_IDX, BOOT_TERM_HANDLE_IDX, FB_HEIGHT_IDX, FB_WIDTH_IDX, FIRST_HANDLE,
+    PCIE_INFO_LEN_IDX, PROC_HANDLE_IDX, PROC_START_HANDLE_CNT, ProcessStartInfo, TERM_SIZE_IDX,
+    VMAR_HANDLE_IDX,
 };
 use syscall::syscall_handler;
 
@@ -59,7 +61,9 @@ pub extern "C" fn kmain() -> ! {
     object::init();
     log::info!("kernel initialized");
 
-    let process = Process::new();
+    Job::root().set_name("root".into());
+    let process = Process::new(&Job::root()).unwrap();
+    process.set_name("user_boot".into());
     let vmar = process.root_vmar();
 
     let files = USER_BOOT_REQUEST.get_response().unwrap().modules();
@@ -88,6 +92,7 @@ pub extern "C" fn kmain() -> ! {
         let aligned_memsz = align_up_by_page_size(memsz + page_offset);
 
         let vmo = Vmo::allocate_ram(aligned_memsz / PAGE_SIZE).unwrap();
+        vmo.set_name(alloc::format!("user_boot-segment@{:#x}", aligned_vaddr));
         let file_data = &user_boot_data[segment.file_range()];
         vmo.write_bytes(page_offset, file_data).unwrap();
         if file_data.len() < memsz {
@@ -131,6 +136,7 @@ pub extern "C" fn kmain() -> ! {
         .allocate_child(align_up_by_page_size(terminal_data.len()))
         .unwrap();
     let terminal_vmo = Vmo::allocate_ram(terminal_region.page_count()).unwrap();
+    terminal_vmo.set_name("terminal-image".into());
     terminal_region
         .map(0, &terminal_vmo, PageProperty::user_data(), false)
         .unwrap();
@@ -139,7 +145,7 @@ pub extern "C" fn kmain() -> ! {
     log::debug!("pushing handles");
 
     let (kernel_endpoint, user_endpoint) = Channel::new();
-    let channel = process.add_handle(Handle::new(user_endpoint, Rights::READ));
+    let channel = process.add_handle(Handle::new(user_endpoint, Rights::READ | Rights::WAIT));
     assert_eq!(channel.as_raw(), FIRST_HANDLE);
 
     let process_handle = Handle::new(process.clone(), Rights::PROCESS);
@@ -153,16 +159,19 @@ pub extern "C" fn kmain() -> ! {
 
     log::info!("Staring user boot with info: {:#x?}", proc_info);
 
-    let thread = process.new_thread();
-    process.start(
-        thread.clone(),
-        entry_point,
-        stack_ptr,
-        |ctx| {
-            ctx.set_first_arg(proc_info_ptr);
-        },
-        syscall_handler,
-    );
+    let thread = process.new_thread().unwrap();
+    thread.set_name("user_boot".into());
+    process
+        .start(
+            thread.clone(),
+            entry_point,
+            stack_ptr,
+            |ctx| {
+                ctx.set_first_arg(proc_info_ptr);
+            },
+            syscall_handler,
+        )
+        .unwrap();
 
     let mut handles =
         alloc::vec![Handle::new(process.clone(), Rights::empty()); PROC_START_HANDLE_CNT];
@@ -187,10 +196,16 @@ pub extern "C" fn kmain() -> ! {
         * frame_buffer.height() as usize
         * (frame_buffer.bpp() as usize / 8);
     let fb_vmo = Vmo::acquire_iomem(virt_to_phys(frame_buffer.addr() as usize), fb_len).unwrap();
+    fb_vmo.set_name("framebuffer".into());
+    // Uncached, but with writes merged so that drawing stays fast.
+    fb_vmo
+        .set_cache_policy(CachePolicy::WeaklyOrderedUnCached)
+        .unwrap();
 
     let pcie_info = PcieInfo::get();
     log::debug!("PCIe Info: {:#x?}", pcie_info);
     let pcie_info_vmo = Vmo::acquire_iomem(pcie_info.paddr, pcie_info.length).unwrap();
+    pcie_info_vmo.set_name("pcie-info".into());
 
     let mut data = alloc::vec![0usize; BOOT_DATA_CNT];
     data[TERM_SIZE_IDX] = terminal_data.len();
@@ -206,6 +221,8 @@ pub extern "C" fn kmain() -> ! {
     handles[BOOT_TERM_HANDLE_IDX] = Handle::new(terminal_region, Rights::VMAR);
     handles[BOOT_FB_HANDLE_IDX] = Handle::new(fb_vmo, Rights::VMO);
     handles[BOOT_PCIE_HANDLE_IDX] = Handle::new(pcie_info_vmo, Rights::VMO);
+    handles[BOOT_JOB_HANDLE_IDX] = Handle::new(Job::root(), Rights::JOB);
+    handles[BOOT_RESOURCE_HANDLE_IDX] = Handle::new(Resource::root(), Rights::RESOURCE);
 
     kernel_endpoint
         .write(MessagePacket { data, handles })
@@ -1,7 +1,10 @@
 use alloc::sync::Arc;
 use errors::Result;
 use kernel_hal::mem::PageProperty;
-use object::mem::{Vmar, Vmo};
+use object::{
+    mem::{Vmar, Vmo},
+    object::KernelObject,
+};
 use pod::Pod;
 
 static USER_STACK_SIZE: usize = 16 * 1024 * 1024;
@@ -9,6 +12,7 @@ static USER_STACK_SIZE: usize = 16 * 1024 * 1024;
 pub fn new_user_stack(vmar: Arc<Vmar>) -> Result<Arc<Vmar>> {
     let stack = vmar.allocate_child(USER_STACK_SIZE)?;
     let vmo = Vmo::allocate_ram(stack.page_count())?;
+    vmo.set_name("user-stack".into());
     stack.map(0, &vmo, PageProperty::user_data(), false)?;
 
     Ok(stack)
@@ -0,0 +1,96 @@
+use bit_field::BitField;
+use loongarch64::{
+    instructions::iocsr,
+    registers::{ExceptionConfig, ExceptionStatus},
+};
+
+/// The number of PCH-PIC inputs, each routed to the EXTIOI vector of the same number.
+pub const IRQ_COUNT: usize = 64;
+
+/// The PCH-PIC on the QEMU virt machine.
+const PCH_PIC_BASE: usize = 0x1000_0000;
+const PCH_PIC_INT_MASK: usize = 0x20;
+const PCH_PIC_HTMSI_EN: usize = 0x40;
+const PCH_PIC_EDGE: usize = 0x60;
+const PCH_PIC_ROUTE_ENTRY: usize = 0x100;
+const PCH_PIC_HTMSI_VECTOR: usize = 0x200;
+const PCH_PIC_POLARITY: usize = 0x3e0;
+
+const IOCSR_MISC_FUNC: u64 = 0x420;
+const IOCSR_MISC_FUNC_EXT_IOI_EN: usize = 48;
+
+const EXTIOI_IPMAP: u64 = 0x14c0;
+const EXTIOI_ENABLE: u64 = 0x1600;
+const EXTIOI_ISR: u64 = 0x1800;
+const EXTIOI_COREMAP: u64 = 0x1c00;
+
+/// EXTIOI vectors are delivered on HWI0.
+const HWI0: usize = 2;
+
+fn pch_pic_write(offset: usize, value: u64) {
+    // SAFETY: The PCH-PIC registers are mapped like the UART, and only touched here.
+    unsafe { ((PCH_PIC_BASE + offset) as *mut u64).write_volatile(value) }
+}
+
+fn pch_pic_read(offset: usize) -> u64 {
+    unsafe { ((PCH_PIC_BASE + offset) as *const u64).read_volatile() }
+}
+
+pub(crate) fn init() {
+    unsafe {
+        let mut misc = iocsr::read_d(IOCSR_MISC_FUNC);
+        misc.set_bit(IOCSR_MISC_FUNC_EXT_IOI_EN, true);
+        iocsr::write_d(IOCSR_MISC_FUNC, misc);
+
+        // Vectors 0..64 form the first two groups, both delivered to IP0 of core 0.
+        iocsr::write_w(EXTIOI_IPMAP, 0x0101);
+        for vector in (0..IRQ_COUNT).step_by(4) {
+            iocsr::write_w(EXTIOI_COREMAP + vector as u64, 0x0101_0101);
+        }
+        iocsr::write_d(EXTIOI_ENABLE, u64::MAX);
+    }
+
+    // Every line starts masked, level-triggered and active-high.
+    pch_pic_write(PCH_PIC_INT_MASK, u64::MAX);
+    pch_pic_write(PCH_PIC_EDGE, 0);
+    pch_pic_write(PCH_PIC_POLARITY, 0);
+    for irq in 0..IRQ_COUNT {
+        unsafe {
+            ((PCH_PIC_BASE + PCH_PIC_ROUTE_ENTRY + irq) as *mut u8).write_volatile(1);
+            ((PCH_PIC_BASE + PCH_PIC_HTMSI_VECTOR + irq) as *mut u8).write_volatile(irq as u8);
+        }
+    }
+    pch_pic_write(PCH_PIC_HTMSI_EN, u64::MAX);
+
+    let mut ecfg = ExceptionConfig.read();
+    ecfg.set_bit(HWI0, true);
+    ExceptionConfig.write(ecfg);
+}
+
+pub(crate) fn mask(vector: usize) {
+    let mut mask = pch_pic_read(PCH_PIC_INT_MASK);
+    mask.set_bit(vector, true);
+    pch_pic_write(PCH_PIC_INT_MASK, mask);
+}
+
+pub(crate) fn unmask(vector: usize) {
+    let mut mask = pch_pic_read(PCH_PIC_INT_MASK);
+    mask.set_bit(vector, false);
+    pch_pic_write(PCH_PIC_INT_MASK, mask);
+}
+
+/// Returns whether an external interrupt is pending on this core.
+pub(crate) fn is_pending() -> bool {
+    ExceptionStatus.read().get_bit(HWI0)
+}
+
+/// Takes the lowest pending vector, clearing it in the EXTIOI.
+pub(crate) fn claim() -> Option<usize> {
+    let pending = unsafe { iocsr::read_d(EXTIOI_ISR) };
+    if pending == 0 {
+        return None;
+    }
+    let vector = pending.trailing_zeros() as usize;
+    unsafe { iocsr::write_d(EXTIOI_ISR, 1 << vector) };
+    Some(vector)
+}
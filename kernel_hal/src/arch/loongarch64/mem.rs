@@ -2,6 +2,7 @@ use alloc::{sync::Arc, vec::Vec};
 use errors::{Errno, Result};
 use loongarch64::{
     PhysAddr as Paddr, PrivilegeLevel, VirtAddr as Vaddr,
+    instructions::tlb,
     registers::{Asid, PgdHigh, PgdLow},
     structures::paging::{
         CachePolicy, FrameAllocator, FrameDeallocator, Mapper, OffsetPageTable, Page, PageProperty,
@@ -37,6 +38,7 @@ pub fn current_page_table() -> LoongArch64PageTable {
             OffsetPageTable::new(&mut *lower_half, &mut *higher_half, physical_memory_offset)
         },
         asid: 0,
+        user: false,
     }
 }
 
@@ -166,6 +168,8 @@ static FREE_ASIDS: Lazy<Mutex<Vec<u64>>> = Lazy::new(|| {
 pub struct LoongArch64PageTable {
     inner: OffsetPageTable<'static>,
     asid: u64,
+    /// Made by `deep_copy`, owning its user half and its root frame.
+    user: bool,
 }
 
 impl GeneralPageTable for LoongArch64PageTable {
@@ -379,6 +383,7 @@ impl GeneralPageTable for LoongArch64PageTable {
         Arc::new(RwLock::new(Self {
             inner: page_table,
             asid: FREE_ASIDS.lock().pop().unwrap(),
+            user: true,
         }))
     }
 
@@ -391,11 +396,59 @@ impl GeneralPageTable for LoongArch64PageTable {
 
         Asid.write_asid(self.asid);
     }
+
+    fn release(&mut self) {
+        // The kernel page table, or one released already.
+        if !self.user || self.asid == 0 {
+            return;
+        }
+
+        let frame_allocator = &mut FRAME_ALLOCATOR.lock();
+        let mut stack: Vec<(*mut PageTable, u8)> =
+            alloc::vec![(self.inner.lower_half_page_table_mut() as *mut _, 4)];
+        while let Some((table, level)) = stack.pop() {
+            for entry in unsafe { &mut *table }
+                .iter_mut()
+                .filter(|entry| !entry.is_unused())
+            {
+                if level > 1 && !entry.flags().contains(PageTableFlags::HUGE_PAGE) {
+                    let child = phys_to_virt(entry.addr().as_u64() as PhysAddr) as *mut PageTable;
+                    stack.push((child, level - 1));
+                }
+            }
+            // The root frame stays until the page table is dropped.
+            if level < 4 {
+                let frame = PhysFrame::<Size4KiB>::containing_address(Paddr::new(virt_to_phys(
+                    table as VirtAddr,
+                )
+                    as u64));
+                unsafe {
+                    frame_allocator.deallocate_frame(frame);
+                }
+            }
+        }
+        self.inner.lower_half_page_table_mut().zero();
+
+        tlb::flush_asid(self.asid);
+        FREE_ASIDS.lock().push(self.asid);
+        self.asid = 0;
+    }
 }
 
 impl Drop for LoongArch64PageTable {
     fn drop(&mut self) {
-        FREE_ASIDS.lock().push(self.asid);
+        if !self.user {
+            return;
+        }
+        self.release();
+        let root = virt_to_phys(self.inner.lower_half_page_table() as *const _ as VirtAddr);
+        unsafe {
+            FRAME_ALLOCATOR
+                .lock()
+                .deallocate_frame(PhysFrame::<Size4KiB>::containing_address(Paddr::new(
+                    root as u64,
+                )));
+        }
     }
 }
 
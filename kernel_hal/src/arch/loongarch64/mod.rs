@@ -1,8 +1,10 @@
 use loongarch64::registers::init_pwc;
 
+pub mod interrupt;
 pub mod mem;
 pub mod serial;
 pub mod task;
+pub mod timer;
 pub mod trap;
 
 pub(crate) fn init() {
@@ -12,6 +14,7 @@ pub(crate) fn init() {
 
 pub(crate) fn init_after_heap() {
     serial::init();
+    interrupt::init();
 }
 
 pub fn idle_ins() {
@@ -1,11 +1,40 @@
-use loongarch64::registers::{BadVirtAddr, ExceptionStatus, TimerConfigBuilder, TimerValue};
+use bit_field::BitField;
+use errors::{Errno, Result};
+use loongarch64::{
+    PrivilegeLevel,
+    registers::{
+        BadVirtAddr, ExceptionStatus, FetchWatchConfig, FetchWatchStatus, MemoryWatchConfig,
+        MemoryWatchStatus, TimerValue, WATCH_SLOT_COUNT, WatchCtrlBuilder, WatchKind, WatchSlot,
+    },
+};
 
 use crate::{
-    arch::trap::{CpuExceptionInfo, TrapFrame, handle_timer, run_user},
+    arch::{
+        interrupt,
+        timer::set_next_event,
+        trap::{CpuExceptionInfo, TrapFrame, handle_timer, run_user},
+    },
+    interrupt::handle_irqs,
     mem::VirtAddr,
-    task::ReturnReason,
+    task::{DebugRegs, ExceptionKind, HW_BREAKPOINT_COUNT, ReturnReason, WatchFlags},
+    timer::next_deadline,
 };
 
+/// Fetch watchpoint used for single-stepping, after the hardware breakpoints.
+const SINGLE_STEP_SLOT: usize = HW_BREAKPOINT_COUNT;
+/// Watchpoint Enable bit of `prmd`, restored into `crmd` by `ertn`.
+const PRMD_PWE: usize = 3;
+
+/// Number of fetch watchpoints the hardware implements.
+fn fetch_slot_count() -> usize {
+    FetchWatchConfig.read_count().min(WATCH_SLOT_COUNT)
+}
+
+/// Number of memory watchpoints the hardware implements.
+fn memory_slot_count() -> usize {
+    MemoryWatchConfig.read_count().min(WATCH_SLOT_COUNT)
+}
+
 #[repr(C)]
 pub struct UserContext {
     /// General registers
@@ -16,6 +45,9 @@ pub struct UserContext {
     era: usize,
     /// Extended Unit Enable
     euen: usize,
+    /// Hardware breakpoints and watchpoints, armed on each return to user mode
+    debug: DebugRegs,
+    single_step: bool,
 }
 
 impl Default for UserContext {
@@ -25,6 +57,8 @@ impl Default for UserContext {
             prmd: 0b0111, // User mode, enable interrupt
             era: 0,
             euen: 0,
+            debug: DebugRegs::default(),
+            single_step: false,
         }
     }
 }
@@ -69,14 +103,13 @@ pub struct GeneralRegs {
 impl UserContext {
     pub fn enter_user_space(&mut self) -> ReturnReason {
         loop {
-            TimerConfigBuilder::new()
-                .initial_value(100000000 >> 2)
-                .set_enabled(true)
-                .set_periodic(false)
-                .done();
+            // Back in the kernel after a quantum, or sooner for a deadline.
+            set_next_event(next_deadline());
+            let armed = self.arm_debug();
             unsafe {
                 run_user(self);
             }
+            let fetch_hits = if armed { take_watch_hits() } else { 0 };
             let ecode = ExceptionStatus.read_ecode();
             match ecode {
                 0 => {
@@ -84,6 +117,10 @@ impl UserContext {
                         handle_timer(&self.as_trap_frame());
                         break ReturnReason::KernelEvent;
                     }
+                    if interrupt::is_pending() {
+                        handle_irqs();
+                        break ReturnReason::KernelEvent;
+                    }
                 }
                 0xb => {
                     self.era += 4;
@@ -91,14 +128,98 @@ impl UserContext {
                 }
                 _ => {
                     let badv = BadVirtAddr.read() as VirtAddr;
+                    let kind = match ecode {
+                        0x1..=0x8 => ExceptionKind::PageFault,
+                        0xc => ExceptionKind::SoftwareBreakpoint,
+                        // Fetch watchpoint
+                        0x13 if ExceptionStatus.read_esubcode() == 0 => {
+                            if self.single_step && fetch_hits.get_bit(SINGLE_STEP_SLOT) {
+                                ExceptionKind::SingleStep
+                            } else {
+                                ExceptionKind::HardwareBreakpoint
+                            }
+                        }
+                        0x13 => ExceptionKind::Watchpoint,
+                        _ => ExceptionKind::General,
+                    };
                     break ReturnReason::Exception(CpuExceptionInfo {
                         code: ecode as usize,
                         badv,
+                        kind,
                     });
                 }
             }
         }
     }
+
+    /// Programs the watchpoints for the coming return to user mode,
+    /// returning whether any is in use.
+    ///
+    /// They only fire while `crmd.WE` is set, which `ertn` restores from `prmd`,
+    /// so the slots left over by other threads stay inert.
+    fn arm_debug(&mut self) -> bool {
+        let armed = self.single_step || !self.debug.is_empty();
+        self.prmd.set_bit(PRMD_PWE, armed);
+        if !armed {
+            return false;
+        }
+
+        // Slots the hardware lacks are never used, see `set_debug_regs`.
+        let fetch_slots = fetch_slot_count();
+        let user_ctrl = WatchCtrlBuilder::new().enable_privilege(PrivilegeLevel::Privilege3);
+        for (index, &addr) in self.debug.breakpoints.iter().enumerate().take(fetch_slots) {
+            let slot = WatchSlot::new(WatchKind::Fetch, index);
+            if addr == 0 {
+                slot.set_ctrl(WatchCtrlBuilder::new());
+                continue;
+            }
+            slot.set_addr(addr as u64);
+            slot.set_mask(0);
+            slot.set_ctrl(user_ctrl);
+        }
+
+        if SINGLE_STEP_SLOT < fetch_slots {
+            let slot = WatchSlot::new(WatchKind::Fetch, SINGLE_STEP_SLOT);
+            if self.single_step {
+                // Every address matches, the skip lets the current instruction run first.
+                slot.set_addr(self.era as u64);
+                slot.set_mask(u64::MAX);
+                slot.set_ctrl(user_ctrl);
+                FetchWatchStatus.set_skip();
+            } else {
+                slot.set_ctrl(WatchCtrlBuilder::new());
+            }
+        }
+
+        let memory_slots = memory_slot_count();
+        for (index, watchpoint) in self.debug.watchpoints.iter().enumerate().take(memory_slots) {
+            let slot = WatchSlot::new(WatchKind::Memory, index);
+            if watchpoint.flags.is_empty() {
+                slot.set_ctrl(WatchCtrlBuilder::new());
+                continue;
+            }
+            let mut ctrl = user_ctrl;
+            ctrl.set_len(watchpoint.len);
+            if watchpoint.flags.contains(WatchFlags::READ) {
+                ctrl.watch_load();
+            }
+            if watchpoint.flags.contains(WatchFlags::WRITE) {
+                ctrl.watch_store();
+            }
+            slot.set_addr(watchpoint.addr as u64);
+            slot.set_mask(0);
+            slot.set_ctrl(ctrl);
+        }
+        true
+    }
+}
+
+/// Returns the fetch watchpoints that fired, clearing every status.
+fn take_watch_hits() -> u64 {
+    let hits = FetchWatchStatus.read_hits();
+    FetchWatchStatus.clear_hits();
+    MemoryWatchStatus.clear_hits();
+    hits
 }
 
 impl UserContext {
@@ -166,6 +287,59 @@ impl UserContext {
         self.general.a1 = arg;
     }
 
+    /// Get general registers, from `r0` to `r31`
+    pub fn get_regs(&self) -> [usize; 32] {
+        unsafe { core::mem::transmute(self.general) }
+    }
+
+    /// Set general registers, from `r0` to `r31`. `r0` stays zero.
+    pub fn set_regs(&mut self, regs: &[usize; 32]) {
+        self.general = unsafe { core::mem::transmute::<[usize; 32], GeneralRegs>(*regs) };
+        self.general.zero = 0;
+    }
+
+    /// Get pre-exception mode information (`prmd`)
+    pub fn get_mode(&self) -> usize {
+        self.prmd
+    }
+
+    pub fn debug_regs(&self) -> DebugRegs {
+        self.debug
+    }
+
+    /// Set hardware breakpoints and watchpoints, checked against user space
+    /// and against the slots the hardware implements
+    pub fn set_debug_regs(&mut self, regs: DebugRegs) -> Result<()> {
+        regs.validate()?;
+        let breakpoints = regs.breakpoints.iter().rposition(|&addr| addr != 0);
+        let watchpoints = regs
+            .watchpoints
+            .iter()
+            .rposition(|watchpoint| !watchpoint.flags.is_empty());
+        if breakpoints.is_some_and(|index| index >= fetch_slot_count())
+            || watchpoints.is_some_and(|index| index >= memory_slot_count())
+        {
+            return Err(Errno::NotSupported.with_message("Not enough hardware watchpoints."));
+        }
+        self.debug = regs;
+        Ok(())
+    }
+
+    pub fn single_step(&self) -> bool {
+        self.single_step
+    }
+
+    /// Trap after every instruction, as a [`ExceptionKind::SingleStep`] exception
+    pub fn set_single_step(&mut self, enabled: bool) -> Result<()> {
+        if enabled && SINGLE_STEP_SLOT >= fetch_slot_count() {
+            return Err(
+                Errno::NotSupported.with_message("No fetch watchpoint for single-stepping.")
+            );
+        }
+        self.single_step = enabled;
+        Ok(())
+    }
+
     pub fn as_trap_frame(&self) -> TrapFrame {
         TrapFrame {
             general: self.general,
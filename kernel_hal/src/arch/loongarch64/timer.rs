@@ -0,0 +1,32 @@
+use core::time::Duration;
+
+use loongarch64::{
+    instructions::time::{read_stable_counter, stable_counter_frequency},
+    registers::{TimerConfigBuilder, TimerIntClear},
+};
+
+/// How long a thread may stay in user space before the kernel takes the CPU back, in timer ticks.
+const QUANTUM_TICKS: u64 = 100000000;
+
+pub fn monotonic() -> Duration {
+    let ticks = read_stable_counter() as u128;
+    let frequency = stable_counter_frequency() as u128;
+    Duration::from_nanos((ticks * 1_000_000_000 / frequency) as u64)
+}
+
+/// Arms the one-shot timer for `deadline`, but no further than a quantum away.
+/// A timer interrupt still pending from the previous arming is dropped.
+pub(crate) fn set_next_event(deadline: Option<Duration>) {
+    let ticks = deadline.map_or(QUANTUM_TICKS, |deadline| {
+        let nanos = deadline.saturating_sub(monotonic()).as_nanos();
+        let ticks = nanos * stable_counter_frequency() as u128 / 1_000_000_000;
+        ticks.min(QUANTUM_TICKS as u128) as u64
+    });
+    TimerIntClear.write(1);
+    // The counter is in units of 4 ticks.
+    TimerConfigBuilder::new()
+        .initial_value((ticks >> 2).max(1))
+        .set_enabled(true)
+        .set_periodic(false)
+        .done();
+}
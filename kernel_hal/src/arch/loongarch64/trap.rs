@@ -9,8 +9,9 @@ use loongarch64::{
 
 use crate::{
     arch::task::{GeneralRegs, UserContext},
+    interrupt::handle_irqs,
     mem::{MMUFlags, USER_ASPACE_BASE, USER_ASPACE_SIZE},
-    task::{PageFaultInfo, USER_PAGE_FAULT_HANDLER},
+    task::{ExceptionKind, PageFaultInfo, USER_PAGE_FAULT_HANDLER},
     timer::call_timer_callback_functions,
 };
 
@@ -40,6 +41,8 @@ extern "C" fn trap_handler(f: &mut TrapFrame) {
     if ecode == 0 {
         if estat.get_bit(11) {
             handle_timer(f);
+        } else if super::interrupt::is_pending() {
+            handle_irqs();
         } else {
             log::warn!("Unknown interrupt!");
         }
@@ -56,6 +59,7 @@ extern "C" fn trap_handler(f: &mut TrapFrame) {
             && handler(&CpuExceptionInfo {
                 code: ecode as usize,
                 badv: badv.as_u64() as crate::mem::VirtAddr,
+                kind: ExceptionKind::PageFault,
             })
             .is_ok()
         {
@@ -87,6 +91,7 @@ pub fn disable_int() {
 pub struct CpuExceptionInfo {
     pub code: usize,
     pub badv: crate::mem::VirtAddr,
+    pub kind: ExceptionKind,
 }
 
 impl CpuExceptionInfo {
@@ -0,0 +1,37 @@
+use std::sync::Mutex;
+
+pub const IRQ_COUNT: usize = 64;
+
+/// Simulated controller state: masked lines and lines raised but not yet claimed.
+static LINES: Mutex<(u64, u64)> = Mutex::new((u64::MAX, 0));
+
+pub(crate) fn init() {}
+
+pub(crate) fn mask(vector: usize) {
+    LINES.lock().unwrap().0 |= 1 << vector;
+}
+
+pub(crate) fn unmask(vector: usize) {
+    LINES.lock().unwrap().0 &= !(1 << vector);
+}
+
+pub(crate) fn is_pending() -> bool {
+    let (masked, pending) = *LINES.lock().unwrap();
+    pending & !masked != 0
+}
+
+pub(crate) fn claim() -> Option<usize> {
+    let mut lines = LINES.lock().unwrap();
+    let deliverable = lines.1 & !lines.0;
+    if deliverable == 0 {
+        return None;
+    }
+    let vector = deliverable.trailing_zeros() as usize;
+    lines.1 &= !(1 << vector);
+    Some(vector)
+}
+
+/// Raises `vector` as a device would. It is delivered once unmasked.
+pub(crate) fn raise(vector: usize) {
+    LINES.lock().unwrap().1 |= 1 << vector;
+}
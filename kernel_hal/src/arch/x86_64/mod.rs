@@ -1,6 +1,10 @@
+pub mod interrupt;
 pub mod task;
+pub mod timer;
 pub mod trap;
 
 pub fn init() {}
 
-pub(crate) fn init_after_heap() {}
+pub(crate) fn init_after_heap() {
+    interrupt::init();
+}
@@ -1,7 +1,9 @@
 use core::arch::naked_asm;
 
+use errors::Result;
+
 use super::trap::CpuExceptionInfo;
-use crate::task::ReturnReason;
+use crate::task::{DebugRegs, ExceptionKind, ReturnReason};
 
 /// User space context
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
@@ -10,6 +12,9 @@ pub struct UserContext {
     pub general: GeneralRegs,
     pub trap_num: usize,
     pub error_code: usize,
+    /// Kept for the debug syscalls, never armed.
+    pub debug: DebugRegs,
+    pub single_step: bool,
 }
 
 /// General registers
@@ -66,6 +71,11 @@ impl UserContext {
         ]
     }
 
+    /// Get instruction pointer
+    pub fn get_ip(&self) -> usize {
+        self.general.rip
+    }
+
     /// Set instruction pointer
     pub fn set_ip(&mut self, ip: usize) {
         self.general.rip = ip;
@@ -98,6 +108,45 @@ impl UserContext {
         self.general.rsi = arg;
     }
 
+    /// Get general registers, from `rax` to `gsbase`, padded with zeros
+    pub fn get_regs(&self) -> [usize; 32] {
+        let general: [usize; 20] = unsafe { core::mem::transmute(self.general) };
+        let mut regs = [0; 32];
+        regs[..general.len()].copy_from_slice(&general);
+        regs
+    }
+
+    /// Set general registers, from `rax` to `gsbase`
+    pub fn set_regs(&mut self, regs: &[usize; 32]) {
+        let mut general = [0; 20];
+        general.copy_from_slice(&regs[..20]);
+        self.general = unsafe { core::mem::transmute::<[usize; 20], GeneralRegs>(general) };
+    }
+
+    /// Get `rflags`
+    pub fn get_mode(&self) -> usize {
+        self.general.rflags
+    }
+
+    pub fn debug_regs(&self) -> DebugRegs {
+        self.debug
+    }
+
+    pub fn set_debug_regs(&mut self, regs: DebugRegs) -> Result<()> {
+        regs.validate()?;
+        self.debug = regs;
+        Ok(())
+    }
+
+    pub fn single_step(&self) -> bool {
+        self.single_step
+    }
+
+    pub fn set_single_step(&mut self, enabled: bool) -> Result<()> {
+        self.single_step = enabled;
+        Ok(())
+    }
+
     pub fn as_trap_frame(&mut self) -> super::trap::TrapFrame {
         super::trap::TrapFrame {}
     }
@@ -121,7 +170,11 @@ impl UserContext {
         #[cfg(feature = "libos")]
         {
             self.run_fncall();
-            ReturnReason::Exception(CpuExceptionInfo)
+            ReturnReason::Exception(CpuExceptionInfo {
+                code: self.trap_num,
+                badv: 0,
+                kind: ExceptionKind::General,
+            })
         }
         #[cfg(not(feature = "libos"))]
         unimplemented!()
@@ -367,6 +420,7 @@ mod tests {
             },
             trap_num: 0,
             error_code: 0,
+            ..Default::default()
         };
         cx.run_fncall();
         // check restored registers
@@ -0,0 +1,10 @@
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+pub fn monotonic() -> Duration {
+    START.elapsed()
+}
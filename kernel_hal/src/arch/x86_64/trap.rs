@@ -1,11 +1,18 @@
-use crate::task::PageFaultInfo;
+use crate::{
+    mem::VirtAddr,
+    task::{ExceptionKind, PageFaultInfo},
+};
 
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub struct TrapFrame {}
 
 #[derive(Debug)]
-pub struct CpuExceptionInfo;
+pub struct CpuExceptionInfo {
+    pub code: usize,
+    pub badv: VirtAddr,
+    pub kind: ExceptionKind,
+}
 
 impl CpuExceptionInfo {
     pub fn is_syscall(&self) -> bool {
@@ -0,0 +1,98 @@
+use alloc::{collections::btree_map::BTreeMap, sync::Arc};
+use errors::{Errno, Error};
+use spin::Mutex;
+
+use crate::arch::interrupt;
+
+pub use crate::arch::interrupt::IRQ_COUNT;
+
+type IrqHandler = Arc<dyn Fn() + Send + Sync>;
+
+static IRQ_HANDLERS: Mutex<BTreeMap<usize, IrqHandler>> = Mutex::new(BTreeMap::new());
+
+/// Calls `handler` whenever `vector` fires. The line is masked before the
+/// handler runs, and stays masked until it's unmasked with `unmask_irq`.
+pub fn register_irq_handler<F>(vector: usize, handler: F) -> Result<(), Error>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    if vector >= IRQ_COUNT {
+        return Err(Errno::InvArg.with_message("Interrupt vector out of range."));
+    }
+    let mut handlers = IRQ_HANDLERS.lock();
+    if handlers.contains_key(&vector) {
+        return Err(Errno::BadState.with_message("Interrupt vector already bound."));
+    }
+    handlers.insert(vector, Arc::new(handler));
+    Ok(())
+}
+
+/// Masks `vector` and removes its handler.
+pub fn unregister_irq_handler(vector: usize) {
+    interrupt::mask(vector);
+    IRQ_HANDLERS.lock().remove(&vector);
+}
+
+pub fn mask_irq(vector: usize) {
+    interrupt::mask(vector);
+}
+
+pub fn unmask_irq(vector: usize) {
+    interrupt::unmask(vector);
+}
+
+/// Dispatches every pending external interrupt to its handler.
+pub fn handle_irqs() {
+    if !interrupt::is_pending() {
+        return;
+    }
+    while let Some(vector) = interrupt::claim() {
+        interrupt::mask(vector);
+        let handler = IRQ_HANDLERS.lock().get(&vector).cloned();
+        match handler {
+            Some(handler) => handler(),
+            None => log::warn!("Unhandled interrupt {}!", vector),
+        }
+    }
+}
+
+/// Raises `vector` as if a device asserted it.
+#[cfg(feature = "libos")]
+pub fn trigger_irq(vector: usize) {
+    interrupt::raise(vector);
+    handle_irqs();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn deliver_when_unmasked() {
+        const VECTOR: usize = 7;
+        static FIRED: AtomicUsize = AtomicUsize::new(0);
+
+        register_irq_handler(VECTOR, || {
+            FIRED.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        assert!(register_irq_handler(VECTOR, || {}).is_err());
+
+        // Masked lines are held back until unmasked.
+        trigger_irq(VECTOR);
+        assert_eq!(FIRED.load(Ordering::SeqCst), 0);
+        unmask_irq(VECTOR);
+        handle_irqs();
+        assert_eq!(FIRED.load(Ordering::SeqCst), 1);
+
+        // Delivery masks the line again.
+        trigger_irq(VECTOR);
+        assert_eq!(FIRED.load(Ordering::SeqCst), 1);
+
+        unregister_irq_handler(VECTOR);
+        assert!(register_irq_handler(VECTOR, || {}).is_ok());
+        unregister_irq_handler(VECTOR);
+    }
+}
@@ -1,13 +1,17 @@
-use core::ops::Range;
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use alloc::sync::Arc;
 use errors::{Errno, Error};
 use pod::Pod;
+use spin::RwLock;
 
 use crate::{
     mem::{
-        PageProperty, PhysicalMemory, VirtAddr, VmSpace, align_down_by_page_size, phys_to_virt,
-        virt_to_phys,
+        CachePolicy, PageProperty, PhysicalMemory, VirtAddr, VmSpace, align_down_by_page_size,
+        phys_to_virt, virt_to_phys,
     },
     platform::mem::PAGE_SIZE,
 };
@@ -17,6 +21,9 @@ pub struct IoMem {
     start_address: VirtAddr,
     size: usize,
     vm_space: Arc<VmSpace>,
+    cache_policy: RwLock<CachePolicy>,
+    /// Whether `try_map` put the range in the kernel page table.
+    mapped: AtomicBool,
 }
 
 impl IoMem {
@@ -31,6 +38,8 @@ impl IoMem {
             start_address,
             size,
             vm_space: unsafe { VmSpace::kernel() },
+            cache_policy: RwLock::new(CachePolicy::StronglyOrderedUnCached),
+            mapped: AtomicBool::new(false),
         }))
     }
 }
@@ -43,6 +52,25 @@ impl IoMem {
     pub fn size(&self) -> usize {
         self.size
     }
+
+    /// Device memory is strongly ordered and uncached unless told otherwise.
+    pub fn cache_policy(&self) -> CachePolicy {
+        *self.cache_policy.read()
+    }
+
+    /// Changes how the range is cached, including where `try_map` already mapped it.
+    pub fn set_cache_policy(&self, policy: CachePolicy) -> Result<(), Error> {
+        *self.cache_policy.write() = policy;
+
+        if self.mapped.load(Ordering::SeqCst) {
+            let aligned_addr = align_down_by_page_size(self.start_address());
+            let len = self.start_address() + self.size() - aligned_addr;
+            self.vm_space
+                .cursor(aligned_addr)?
+                .protect(len, |property| property.cache_policy = policy)?;
+        }
+        Ok(())
+    }
 }
 
 impl IoMem {
@@ -55,9 +83,13 @@ impl IoMem {
                     virt_to_phys(aligned_addr),
                     self.size().div_ceil(PAGE_SIZE),
                 ),
-                PageProperty::kernel_data(),
+                PageProperty {
+                    cache_policy: self.cache_policy(),
+                    ..PageProperty::kernel_data()
+                },
             )
             .unwrap();
+        self.mapped.store(true, Ordering::SeqCst);
         Ok(())
     }
 
@@ -3,6 +3,7 @@
 
 extern crate alloc;
 
+pub mod interrupt;
 pub mod io;
 pub mod mem;
 pub mod task;
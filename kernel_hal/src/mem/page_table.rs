@@ -154,6 +154,10 @@ pub trait GeneralPageTable: Sync + Send {
     fn deep_copy(&self) -> Arc<RwLock<dyn GeneralPageTable>>;
     fn activate(&self);
 
+    /// Frees the user half of a page table that will never be activated again,
+    /// along with its address space ID. The mapped frames themselves are not touched.
+    fn release(&mut self) {}
+
     /// Note that start_vaddr and size must be aligned by page size
     fn map_cont(
         &mut self,
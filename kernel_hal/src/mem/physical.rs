@@ -1,3 +1,5 @@
+use core::mem::ManuallyDrop;
+
 use errors::{Errno, Result};
 
 use crate::{
@@ -36,10 +38,16 @@ impl PhysicalMemoryAllocOptions {
     }
 }
 
+/// A run of physical frames.
+///
+/// Frames from [`PhysicalMemoryAllocOptions::allocate`] are owned and go back
+/// to the frame allocator on drop, while views made by
+/// [`PhysicalMemory::from_start_address`] leave the frames alone.
 #[derive(Debug)]
 pub struct PhysicalMemory {
     count: usize,
     start_address: PhysAddr,
+    owned: bool,
 }
 
 impl PhysicalMemory {
@@ -52,6 +60,7 @@ impl PhysicalMemory {
         Ok(Self {
             count,
             start_address,
+            owned: true,
         })
     }
 }
@@ -61,6 +70,7 @@ impl PhysicalMemory {
         Self {
             count,
             start_address,
+            owned: false,
         }
     }
 
@@ -70,10 +80,30 @@ impl PhysicalMemory {
         Self::from_start_address(start_address, count)
     }
 
-    pub fn deallocate(&self) {
-        for id in 0..self.count() {
-            let start_address = self.get_start_address_of_frame(id).unwrap();
-            FRAME_ALLOCATOR.lock().deallocate_frames(start_address, 1);
+    /// Splits the run into single frames, each owning its frame if `self` did.
+    pub fn into_frames(self) -> impl Iterator<Item = Self> {
+        let this = ManuallyDrop::new(self);
+        let (start_address, owned) = (this.start_address, this.owned);
+
+        (0..this.count).map(move |id| Self {
+            count: 1,
+            start_address: start_address + id * PageSize::Size4K as usize,
+            owned,
+        })
+    }
+
+    /// Gives up ownership of the frames, which are then never freed.
+    pub fn leak(self) -> PhysAddr {
+        ManuallyDrop::new(self).start_address
+    }
+}
+
+impl Drop for PhysicalMemory {
+    fn drop(&mut self) {
+        if self.owned {
+            FRAME_ALLOCATOR
+                .lock()
+                .deallocate_frames(self.start_address, self.count);
         }
     }
 }
@@ -77,6 +77,11 @@ impl VmSpace {
     pub fn activate(&self) {
         self.page_table.read().activate();
     }
+
+    /// Frees the page table of a user space that will never be activated again.
+    pub fn release(&self) {
+        self.page_table.write().release();
+    }
 }
 
 /// An interface to map, unmap and change flags of virtual memory regions safely.
@@ -183,6 +188,22 @@ impl Cursor {
         Ok(())
     }
 
+    /// Unmaps whatever is mapped in the current region, skipping the holes.
+    /// This moves the cursor to the end of the region.
+    pub fn clear(&mut self, len: usize) {
+        let page_size = PageSize::Size4K;
+        let end = self.virtual_address + page_size.align_up(len);
+
+        let mut page_table = self.page_table.write();
+        while self.virtual_address < end {
+            self.virtual_address += match page_table.unmap(self.virtual_address) {
+                Ok(size) => size as usize,
+                Err(_) => page_size as usize,
+            };
+        }
+        self.virtual_address = end;
+    }
+
     /// Changes the flags of the current virtual memory region.
     /// This moves the cursor to the end of the region.
     pub fn protect(&mut self, len: usize, updater: impl Fn(&mut PageProperty)) -> Result<()> {
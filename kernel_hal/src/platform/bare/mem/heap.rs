@@ -71,6 +71,7 @@ impl DefaultAllocator {
                 )
                 .unwrap();
         }
+        pm.leak();
 
         unsafe {
             self.0
@@ -11,24 +11,39 @@ use alloc::{
 use spin::Mutex;
 
 use crate::{
-    arch::task::{TaskContext, context_switch, first_context_switch, kernel_task_entry_wrapper},
+    arch::{
+        idle_ins,
+        task::{TaskContext, context_switch, first_context_switch, kernel_task_entry_wrapper},
+        timer::set_next_event,
+        trap::{disable_int, enable_int},
+    },
+    interrupt::handle_irqs,
     platform::task::sched::SCHEDULER,
     task::ThreadState,
+    timer::{next_deadline, timer_tick},
 };
 
 mod sched;
 
 #[unsafe(no_mangle)]
 pub(crate) extern "C" fn kernel_task_entry() -> ! {
-    let mut updater = {
-        let current_thread = SCHEDULER.lock().current().unwrap();
-        current_thread.func.get().unwrap()
-    };
-
-    loop {
-        updater();
-        schedule();
+    let updater = SCHEDULER.lock().current().unwrap().func.get();
+
+    // A killed thread comes back here once more, so that its stack is unwound
+    // and whatever its updater holds is dropped before it leaves for good.
+    if let Some(mut updater) = updater {
+        while !current_dead() {
+            updater();
+            if !current_dead() {
+                schedule();
+            }
+        }
     }
+    exit_current()
+}
+
+fn current_dead() -> bool {
+    SCHEDULER.lock().current().unwrap().state().dead()
 }
 
 #[derive(Debug)]
@@ -66,9 +81,33 @@ impl HwThread {
 impl HwThread {
     /// Set state.
     /// It adds and removes the thread from the scheduler atomatically.
+    /// A thread that dies while not running is scheduled once more to unwind its stack.
     pub fn set_state(self: &Arc<Self>, state: ThreadState) {
-        let origin = self.state();
-        self.inner.lock().state = state;
+        let origin = core::mem::replace(&mut self.inner.lock().state, state);
+        self.reschedule(origin, state);
+    }
+
+    /// Sets `new` only if the thread is in `current`, checked and set under one lock.
+    /// Returns whether the state was set.
+    pub fn compare_set_state(self: &Arc<Self>, current: ThreadState, new: ThreadState) -> bool {
+        {
+            let mut inner = self.inner.lock();
+            if inner.state != current {
+                return false;
+            }
+            inner.state = new;
+        }
+        self.reschedule(current, new);
+        true
+    }
+
+    fn reschedule(self: &Arc<Self>, origin: ThreadState, state: ThreadState) {
+        if state.dead() {
+            if origin.blocked() {
+                SCHEDULER.lock().add(self);
+            }
+            return;
+        }
         if origin.ready() && !state.ready() {
             SCHEDULER.lock().remove(self);
         }
@@ -90,27 +129,60 @@ impl HwThread {
         SCHEDULER.lock().current().unwrap().thread.clone()
     }
 
-    pub fn exit(&self) {
+    /// Does nothing by itself: a dead thread leaves once its updater returns.
+    pub fn exit(&self) {}
+
+    /// Gives up the CPU.
+    /// A blocked thread will not come back until its state is set to ready.
+    pub fn yield_now(&self) {
         schedule();
     }
 }
 
 pub fn launch_multitask() {
-    let next = SCHEDULER.lock().get_next().unwrap();
-    // Access directly to avoid unnecessary checks.
-    next.inner.lock().state = ThreadState::Running;
-    let next_ctx = next.ctx.get();
+    let next_ctx = pick_next();
 
     unsafe {
         first_context_switch(next_ctx);
     }
 }
 
+/// Picks the next thread and marks it running, unless it is only back to unwind.
+fn pick_next() -> *mut TaskContext {
+    let next = SCHEDULER.lock().get_next().unwrap();
+    // Access directly to avoid unnecessary checks.
+    let mut inner = next.inner.lock();
+    if !inner.state.dead() {
+        inner.state = ThreadState::Running;
+    }
+    next.ctx.get()
+}
+
+/// Fires the deadlines that have passed and, if no thread is ready, waits for
+/// an interrupt with interrupts enabled. The kernel otherwise runs with them
+/// masked, so this is where timers and devices wake blocked threads.
+pub fn wait_for_interrupt() {
+    timer_tick();
+    handle_irqs();
+    if !SCHEDULER.lock().no_next() {
+        return;
+    }
+    set_next_event(next_deadline());
+    enable_int();
+    idle_ins();
+    disable_int();
+}
+
 #[inline(always)]
 pub(super) fn schedule() {
-    if SCHEDULER.lock().no_next() {
-        // Fall through.
-        return;
+    while SCHEDULER.lock().no_next() {
+        let current = SCHEDULER.lock().current().unwrap();
+        if current.state().running() {
+            // Fall through.
+            return;
+        }
+        // Nothing else to run: the current thread waits for something to make it ready.
+        wait_for_interrupt();
     }
 
     let current = SCHEDULER.lock().take_current().unwrap();
@@ -121,18 +193,31 @@ pub(super) fn schedule() {
         SCHEDULER.lock().add(&current);
     }
 
-    let next_ctx = {
-        let next = SCHEDULER.lock().get_next().unwrap();
-        // Access directly to avoid unnecessary checks.
-        next.inner.lock().state = ThreadState::Running;
-        next.ctx.get()
-    };
+    let next_ctx = pick_next();
 
     unsafe {
         context_switch(next_ctx, current_ctx);
     }
 }
 
+/// Leaves the current dead thread for good.
+/// Nothing on its stack is unwound, so no reference is held across the switch.
+fn exit_current() -> ! {
+    static EXITED_CTX: SyncUnsafeCell<TaskContext> = SyncUnsafeCell::new(TaskContext::new());
+
+    drop(SCHEDULER.lock().take_current());
+    // Every other thread may be blocked: idle on the way out until one is woken.
+    while SCHEDULER.lock().no_next() {
+        wait_for_interrupt();
+    }
+    let next_ctx = pick_next();
+
+    unsafe {
+        context_switch(next_ctx, EXITED_CTX.get());
+    }
+    unreachable!()
+}
+
 type Entry = Box<dyn FnMut() + Send + 'static>;
 
 struct FuncWrapper(Cell<Option<Entry>>);
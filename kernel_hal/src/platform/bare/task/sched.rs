@@ -1,7 +1,4 @@
-use alloc::{
-    collections::vec_deque::VecDeque,
-    sync::{Arc, Weak},
-};
+use alloc::{collections::vec_deque::VecDeque, sync::Arc};
 use spin::Mutex;
 
 use crate::task::HwThread;
@@ -11,7 +8,8 @@ pub static SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::new());
 #[derive(Debug)]
 pub struct Scheduler {
     pub(super) threads: VecDeque<Arc<HwThread>>,
-    current: Option<Weak<HwThread>>,
+    /// Held strongly, so that a thread outlives its last reference until it switches away.
+    current: Option<Arc<HwThread>>,
 }
 
 impl Scheduler {
@@ -30,16 +28,16 @@ impl Scheduler {
 
     pub fn get_next(&mut self) -> Option<Arc<HwThread>> {
         let next = self.threads.pop_front()?;
-        self.current = Some(Arc::downgrade(&next));
+        self.current = Some(next.clone());
         Some(next)
     }
 
     pub fn current(&self) -> Option<Arc<HwThread>> {
-        self.current.clone().and_then(|t| t.upgrade())
+        self.current.clone()
     }
 
     pub fn take_current(&mut self) -> Option<Arc<HwThread>> {
-        self.current.take().and_then(|t| t.upgrade())
+        self.current.take()
     }
 
     pub fn remove(&mut self, thread: &Arc<HwThread>) {
@@ -15,6 +15,14 @@ static TOKIO_RT: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().unwrap());
 
 pub fn launch_multitask() {}
 
+/// Fires the deadlines that have passed and dispatches pending interrupts.
+/// Timers have a thread of their own here, so there's nothing to wait for.
+pub fn wait_for_interrupt() {
+    crate::timer::timer_tick();
+    crate::interrupt::handle_irqs();
+    std::thread::yield_now();
+}
+
 #[derive(Default)]
 pub struct HwThread {
     state: Mutex<ThreadState>,
@@ -46,11 +54,24 @@ impl HwThread {
         *self.state.lock().unwrap() = state;
     }
 
+    pub fn compare_set_state(&self, current: ThreadState, new: ThreadState) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if *state != current {
+            return false;
+        }
+        *state = new;
+        true
+    }
+
     pub fn current_thread() -> Weak<dyn Any + Send + Sync> {
         Weak::<()>::new()
     }
 
     pub fn exit(&self) {}
+
+    pub fn yield_now(&self) {
+        std::thread::yield_now();
+    }
 }
 
 struct ThreadFuture {
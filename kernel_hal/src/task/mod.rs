@@ -1,5 +1,5 @@
 pub use crate::arch::task::UserContext;
-pub use crate::platform::task::{HwThread, launch_multitask};
+pub use crate::platform::task::{HwThread, launch_multitask, wait_for_interrupt};
 pub use exception::*;
 pub use user::*;
 
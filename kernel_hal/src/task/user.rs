@@ -1,7 +1,113 @@
-use crate::arch::trap::CpuExceptionInfo;
+use bitflags::bitflags;
+use errors::{Errno, Result};
+
+use crate::{
+    arch::trap::CpuExceptionInfo,
+    mem::{USER_ASPACE_BASE, USER_ASPACE_SIZE, VirtAddr},
+};
 
 pub enum ReturnReason {
     Syscall,
     Exception(CpuExceptionInfo),
     KernelEvent,
 }
+
+/// What raised a user exception, as far as the architecture tells.
+#[repr(usize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionKind {
+    #[default]
+    General = 0,
+    PageFault = 1,
+    /// A breakpoint instruction was executed.
+    SoftwareBreakpoint = 2,
+    /// A hardware breakpoint set in [`DebugRegs`] was reached.
+    HardwareBreakpoint = 3,
+    /// One instruction ran with single-stepping enabled.
+    SingleStep = 4,
+    /// A watchpoint set in [`DebugRegs`] was accessed.
+    Watchpoint = 5,
+}
+
+pub const HW_BREAKPOINT_COUNT: usize = 4;
+pub const WATCHPOINT_COUNT: usize = 4;
+
+bitflags! {
+    /// Accesses a watchpoint fires on.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct WatchFlags: usize {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+    }
+}
+
+/// A data watchpoint, unused while `flags` is empty.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub addr: VirtAddr,
+    /// 1, 2, 4 or 8, with `addr` aligned to it.
+    pub len: usize,
+    pub flags: WatchFlags,
+}
+
+/// Hardware breakpoints and watchpoints of a user thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DebugRegs {
+    /// Instruction addresses, unused while 0.
+    pub breakpoints: [VirtAddr; HW_BREAKPOINT_COUNT],
+    pub watchpoints: [Watchpoint; WATCHPOINT_COUNT],
+}
+
+impl DebugRegs {
+    /// Checks that every breakpoint and watchpoint in use lies in user space
+    /// and that watchpoints are naturally aligned.
+    pub fn validate(&self) -> Result<()> {
+        let user_space = USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE;
+        for &addr in self.breakpoints.iter().filter(|&&addr| addr != 0) {
+            if !user_space.contains(&addr) || addr % 4 != 0 {
+                return Err(Errno::InvArg.with_message("Invalid breakpoint address."));
+            }
+        }
+        for watchpoint in self.watchpoints.iter().filter(|w| !w.flags.is_empty()) {
+            if !matches!(watchpoint.len, 1 | 2 | 4 | 8)
+                || !watchpoint.addr.is_multiple_of(watchpoint.len)
+                || !user_space.contains(&watchpoint.addr)
+            {
+                return Err(Errno::InvArg.with_message("Invalid watchpoint."));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_debug_regs() {
+        let mut regs = DebugRegs::default();
+        assert!(regs.validate().is_ok());
+        assert!(regs.is_empty());
+
+        regs.breakpoints[0] = USER_ASPACE_BASE + 0x100;
+        regs.watchpoints[1] = Watchpoint {
+            addr: USER_ASPACE_BASE + 0x1008,
+            len: 8,
+            flags: WatchFlags::WRITE,
+        };
+        assert!(regs.validate().is_ok());
+
+        regs.watchpoints[1].addr += 4;
+        assert!(regs.validate().is_err());
+        regs.watchpoints[1].flags = WatchFlags::empty();
+        assert!(regs.validate().is_ok());
+
+        regs.breakpoints[2] = USER_ASPACE_BASE + USER_ASPACE_SIZE;
+        assert!(regs.validate().is_err());
+    }
+}
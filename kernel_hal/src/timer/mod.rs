@@ -1,11 +1,24 @@
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
 use alloc::{boxed::Box, vec::Vec};
 use spin::Mutex;
 
 use crate::arch::trap::TrapFrame;
 
+pub use crate::arch::timer::monotonic;
+
 type TimerFn = Box<dyn Fn() + Sync + Send>;
+type DeadlineFn = Box<dyn FnOnce(Duration) + Send>;
+
+/// Identifies a deadline set by [`timer_set`], for cancelling it.
+pub type TimerKey = u64;
 
 static TIMER_CALLBACKS: Mutex<Vec<TimerFn>> = Mutex::new(Vec::new());
+static DEADLINES: Mutex<Vec<(TimerKey, Duration, DeadlineFn)>> = Mutex::new(Vec::new());
+static NEXT_TIMER_KEY: AtomicU64 = AtomicU64::new(0);
 
 pub fn register_callback_on_cpu<F>(func: F)
 where
@@ -15,10 +28,141 @@ where
     callbacks.push(Box::new(func));
 }
 
+/// Calls `func` with the current time once the monotonic clock reaches `deadline`,
+/// unless cancelled with [`timer_cancel`] first.
+pub fn timer_set<F>(deadline: Duration, func: F) -> TimerKey
+where
+    F: FnOnce(Duration) + Send + 'static,
+{
+    let key = NEXT_TIMER_KEY.fetch_add(1, Ordering::Relaxed);
+    DEADLINES.lock().push((key, deadline, Box::new(func)));
+
+    #[cfg(feature = "libos")]
+    timer_thread().unpark();
+    #[cfg(not(feature = "libos"))]
+    crate::arch::timer::set_next_event(next_deadline());
+
+    key
+}
+
+/// Returns the earliest pending deadline, which the hardware timer has to fire at.
+pub(crate) fn next_deadline() -> Option<Duration> {
+    DEADLINES
+        .lock()
+        .iter()
+        .map(|(_, deadline, _)| *deadline)
+        .min()
+}
+
+/// Drops a pending deadline. Returns `false` if it has already fired.
+pub fn timer_cancel(key: TimerKey) -> bool {
+    let mut deadlines = DEADLINES.lock();
+    let count = deadlines.len();
+    deadlines.retain(|(other, _, _)| *other != key);
+    deadlines.len() != count
+}
+
+/// Fires every timer whose deadline has passed.
+pub fn timer_tick() {
+    let now = monotonic();
+    let expired = DEADLINES
+        .lock()
+        .extract_if(.., |(_, deadline, _)| *deadline <= now)
+        .collect::<Vec<_>>();
+    for (_, _, func) in expired {
+        func(now);
+    }
+}
+
+/// The thread standing in for the timer interrupt, which sleeps until the
+/// earliest deadline and is unparked whenever one is set.
+#[cfg(feature = "libos")]
+fn timer_thread() -> &'static std::thread::Thread {
+    static THREAD: spin::Lazy<std::thread::Thread> = spin::Lazy::new(|| {
+        std::thread::spawn(|| {
+            loop {
+                match next_deadline() {
+                    Some(deadline) => {
+                        std::thread::park_timeout(deadline.saturating_sub(monotonic()))
+                    }
+                    None => std::thread::park(),
+                }
+                timer_tick();
+            }
+        })
+        .thread()
+        .clone()
+    });
+    &THREAD
+}
+
 #[allow(unused)]
 pub(crate) fn call_timer_callback_functions(_: &TrapFrame) {
     let callbacks = TIMER_CALLBACKS.lock();
     for callback in callbacks.iter() {
         (callback)();
     }
+    drop(callbacks);
+    timer_tick();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, atomic::AtomicBool};
+
+    use super::*;
+
+    #[test]
+    fn monotonic_increases() {
+        let before = monotonic();
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(monotonic() >= before + Duration::from_millis(10));
+    }
+
+    #[test]
+    fn timer_fires() {
+        let fired = Arc::new(AtomicBool::new(false));
+        let flag = fired.clone();
+        timer_set(monotonic() + Duration::from_millis(20), move |_| {
+            flag.store(true, Ordering::SeqCst);
+        });
+        assert!(!fired.load(Ordering::SeqCst));
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(fired.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cancel() {
+        let fired = Arc::new(AtomicBool::new(false));
+        let flag = fired.clone();
+        let key = timer_set(monotonic() + Duration::from_millis(20), move |_| {
+            flag.store(true, Ordering::SeqCst);
+        });
+        assert!(timer_cancel(key));
+        assert!(!DEADLINES.lock().iter().any(|(other, _, _)| *other == key));
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!fired.load(Ordering::SeqCst));
+        assert!(!timer_cancel(key));
+    }
+
+    #[test]
+    fn fires_while_idle() {
+        let fired = Arc::new(AtomicBool::new(false));
+        let flag = fired.clone();
+        let deadline = monotonic() + Duration::from_millis(20);
+        timer_set(deadline, move |_| {
+            flag.store(true, Ordering::SeqCst);
+        });
+        assert!(next_deadline().is_some_and(|next| next <= deadline));
+
+        // No thread ever enters user space: waiting alone must get the deadline fired.
+        let give_up = monotonic() + Duration::from_secs(1);
+        while !fired.load(Ordering::SeqCst) && monotonic() < give_up {
+            crate::task::wait_for_interrupt();
+        }
+        assert!(fired.load(Ordering::SeqCst));
+        assert!(monotonic() >= deadline);
+    }
 }
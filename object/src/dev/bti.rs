@@ -0,0 +1,179 @@
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use kernel_hal::mem::PhysAddr;
+use spin::Mutex;
+
+use crate::{
+    Errno, Result, impl_kobj,
+    mem::{PAGE_SIZE, Vmo},
+    new_kobj,
+    object::KObjectBase,
+};
+
+/// A bus transaction initiator: a device that can access memory on its own.
+///
+/// There's no IOMMU, so device addresses are physical addresses.
+pub struct Bti {
+    /// Every pinned range, including ones whose handle was closed without unpinning.
+    /// Those stay quarantined here, since the device may still be using them.
+    pmts: Mutex<Vec<Arc<Pmt>>>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Bti);
+
+impl Bti {
+    pub fn new() -> Arc<Self> {
+        new_kobj!({
+            pmts: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Pins `offset..offset + len` of `vmo` for the device.
+    /// The returned token carries one device address per page.
+    pub fn pin(self: &Arc<Self>, vmo: &Arc<Vmo>, offset: usize, len: usize) -> Result<Arc<Pmt>> {
+        let addrs = vmo.pin(offset, len)?;
+        let pmt = Pmt::new(Arc::downgrade(self), vmo.clone(), offset, addrs);
+        self.pmts.lock().push(pmt.clone());
+        Ok(pmt)
+    }
+
+    /// Returns the number of pinned ranges, quarantined ones included.
+    pub fn pinned_count(&self) -> usize {
+        self.pmts.lock().len()
+    }
+
+    /// Unpins every range whose token has been dropped without unpinning.
+    pub fn release_quarantine(&self) {
+        let quarantined: Vec<_> = {
+            let mut pmts = self.pmts.lock();
+            let (quarantined, live) = core::mem::take(&mut *pmts)
+                .into_iter()
+                .partition(|pmt| Arc::strong_count(pmt) == 1);
+            *pmts = live;
+            quarantined
+        };
+        for pmt in quarantined {
+            pmt.release();
+        }
+    }
+}
+
+impl Drop for Bti {
+    fn drop(&mut self) {
+        for pmt in self.pmts.get_mut().drain(..) {
+            pmt.release();
+        }
+    }
+}
+
+/// A pinned memory token, which keeps a range of a VMO in place for DMA.
+pub struct Pmt {
+    bti: Weak<Bti>,
+    vmo: Arc<Vmo>,
+    offset: usize,
+    addrs: Vec<PhysAddr>,
+    pinned: Mutex<bool>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Pmt);
+
+impl Pmt {
+    fn new(bti: Weak<Bti>, vmo: Arc<Vmo>, offset: usize, addrs: Vec<PhysAddr>) -> Arc<Self> {
+        new_kobj!({
+            bti,
+            vmo,
+            offset,
+            addrs,
+            pinned: Mutex::new(true),
+        })
+    }
+
+    /// The device address of each pinned page.
+    pub fn addrs(&self) -> &[PhysAddr] {
+        &self.addrs
+    }
+
+    pub fn unpin(self: &Arc<Self>) -> Result<()> {
+        if !self.release() {
+            return Err(Errno::BadState.with_message("Memory is not pinned."));
+        }
+        if let Some(bti) = self.bti.upgrade() {
+            bti.pmts.lock().retain(|pmt| !Arc::ptr_eq(pmt, self));
+        }
+        Ok(())
+    }
+
+    fn release(&self) -> bool {
+        let mut pinned = self.pinned.lock();
+        if *pinned {
+            *pinned = false;
+            self.vmo.unpin(self.offset, self.addrs.len() * PAGE_SIZE);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_unpin() {
+        let bti = Bti::new();
+        let vmo = Vmo::allocate_ram(4).unwrap();
+
+        let pmt = bti.pin(&vmo, PAGE_SIZE, 2 * PAGE_SIZE).unwrap();
+        assert_eq!(pmt.addrs().len(), 2);
+        assert_eq!(pmt.addrs()[0], vmo.physical_address(PAGE_SIZE).unwrap());
+        assert!(vmo.is_pinned());
+
+        pmt.unpin().unwrap();
+        assert!(!vmo.is_pinned());
+        assert_eq!(bti.pinned_count(), 0);
+        assert!(pmt.unpin().is_err());
+    }
+
+    #[test]
+    fn pin_invalid_range() {
+        let bti = Bti::new();
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        assert!(bti.pin(&vmo, 0, 2 * PAGE_SIZE).is_err());
+        assert!(bti.pin(&vmo, 1, PAGE_SIZE).is_err());
+        assert!(!vmo.is_pinned());
+    }
+
+    #[test]
+    fn quarantine() {
+        let bti = Bti::new();
+        let vmo = Vmo::allocate_ram(1).unwrap();
+
+        drop(bti.pin(&vmo, 0, PAGE_SIZE).unwrap());
+        assert!(vmo.is_pinned());
+        assert_eq!(bti.pinned_count(), 1);
+
+        bti.release_quarantine();
+        assert!(!vmo.is_pinned());
+        assert_eq!(bti.pinned_count(), 0);
+    }
+
+    #[test]
+    fn quarantine_spares_open_tokens() {
+        let bti = Bti::new();
+        let vmo = Vmo::allocate_ram(2).unwrap();
+
+        let pmt = bti.pin(&vmo, 0, PAGE_SIZE).unwrap();
+        drop(bti.pin(&vmo, PAGE_SIZE, PAGE_SIZE).unwrap());
+        bti.release_quarantine();
+        assert_eq!(bti.pinned_count(), 1);
+        assert!(vmo.is_pinned());
+
+        pmt.unpin().unwrap();
+        assert!(!vmo.is_pinned());
+    }
+}
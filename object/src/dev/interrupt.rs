@@ -0,0 +1,217 @@
+use core::time::Duration;
+
+use alloc::sync::{Arc, Weak};
+use kernel_hal::{interrupt, timer::monotonic};
+use spin::Mutex;
+
+use crate::{
+    Errno, Result, impl_kobj,
+    ipc::{PacketPayload, Port, PortPacket},
+    new_kobj,
+    object::{KObjectBase, Signal, Upcast},
+    task::Thread,
+};
+
+/// A hardware interrupt line bound to a user-space driver.
+///
+/// The line is masked whenever it fires, and unmasked again by `ack`.
+pub struct Interrupt {
+    vector: usize,
+    inner: Mutex<InterruptInner>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Interrupt);
+
+#[derive(Default)]
+struct InterruptInner {
+    bound: bool,
+    /// When the line last fired, if nobody has waited for it yet.
+    timestamp: Option<Duration>,
+    port: Option<(Weak<Port>, u64)>,
+}
+
+impl Interrupt {
+    pub fn new(vector: usize) -> Result<Arc<Self>> {
+        let interrupt: Arc<Self> = new_kobj!({
+            vector,
+            inner: Mutex::new(InterruptInner::default()),
+        });
+
+        let weak = Arc::downgrade(&interrupt);
+        interrupt::register_irq_handler(vector, move || {
+            if let Some(interrupt) = weak.upgrade() {
+                interrupt.trigger();
+            }
+        })?;
+        interrupt.inner.lock().bound = true;
+        interrupt::unmask_irq(vector);
+
+        Ok(interrupt)
+    }
+
+    pub fn vector(&self) -> usize {
+        self.vector
+    }
+}
+
+impl Interrupt {
+    fn trigger(&self) {
+        let now = monotonic();
+        let mut inner = self.inner.lock();
+        if let Some((port, key)) = inner.bound_port() {
+            // Queued unlocked, as the port may run callbacks of its own.
+            drop(inner);
+            port.queue(PortPacket {
+                key,
+                payload: PacketPayload::Interrupt { timestamp: now },
+            });
+            return;
+        }
+        inner.timestamp = Some(now);
+        drop(inner);
+        self.base.signal_set(Signal::SIGNALED);
+    }
+
+    /// Blocks `thread` until the line fires, and returns when it did.
+    pub fn wait(
+        self: &Arc<Self>,
+        thread: &Arc<Thread>,
+        deadline: Option<Duration>,
+    ) -> Result<Duration> {
+        loop {
+            {
+                let mut inner = self.inner.lock();
+                if inner.bound_port().is_some() {
+                    return Err(Errno::BadState.with_message("Interrupt is bound to a port."));
+                }
+                if let Some(timestamp) = inner.timestamp.take() {
+                    self.base.signal_clear(Signal::SIGNALED);
+                    return Ok(timestamp);
+                }
+            }
+            thread.wait_signal(&self.clone().upcast(), Signal::SIGNALED, deadline)?;
+        }
+    }
+
+    /// Unmasks the line, once the driver has serviced the device.
+    pub fn ack(&self) {
+        interrupt::unmask_irq(self.vector);
+    }
+
+    /// Delivers further interrupts to `port` as packets with `key`.
+    pub fn bind_port(&self, port: &Arc<Port>, key: u64) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.bound_port().is_some() {
+            return Err(Errno::BadState.with_message("Interrupt is already bound to a port."));
+        }
+        inner.port = Some((Arc::downgrade(port), key));
+        let timestamp = inner.timestamp.take();
+        drop(inner);
+
+        if let Some(timestamp) = timestamp {
+            self.base.signal_clear(Signal::SIGNALED);
+            port.queue(PortPacket {
+                key,
+                payload: PacketPayload::Interrupt { timestamp },
+            });
+        }
+        Ok(())
+    }
+}
+
+impl InterruptInner {
+    /// Returns the bound port, forgetting it once dropped, so that the line
+    /// falls back to signalling the interrupt object rather than being lost.
+    fn bound_port(&mut self) -> Option<(Arc<Port>, u64)> {
+        let (port, key) = self.port.as_ref()?;
+        match port.upgrade() {
+            Some(port) => Some((port, *key)),
+            None => {
+                self.port = None;
+                None
+            }
+        }
+    }
+}
+
+impl Drop for Interrupt {
+    fn drop(&mut self) {
+        if self.inner.lock().bound {
+            interrupt::unregister_irq_handler(self.vector);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kernel_hal::interrupt::trigger_irq;
+
+    use crate::object::KernelObject;
+
+    use super::*;
+
+    #[test]
+    fn wait_ack() {
+        let interrupt = Interrupt::new(10).unwrap();
+        let thread = Thread::new(Weak::new());
+        assert!(Interrupt::new(10).is_err());
+
+        trigger_irq(10);
+        assert!(interrupt.signal().contains(Signal::SIGNALED));
+        interrupt.wait(&thread, None).unwrap();
+        assert!(!interrupt.signal().contains(Signal::SIGNALED));
+
+        // Masked until acked.
+        trigger_irq(10);
+        assert!(!interrupt.signal().contains(Signal::SIGNALED));
+        interrupt.ack();
+        kernel_hal::interrupt::handle_irqs();
+        assert!(interrupt.signal().contains(Signal::SIGNALED));
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let interrupt = Interrupt::new(11).unwrap();
+        let thread = Thread::new(Weak::new());
+
+        let deadline = monotonic() + Duration::from_millis(50);
+        let err = interrupt.wait(&thread, Some(deadline)).unwrap_err();
+        assert_eq!(err.errno(), Errno::TimedOut);
+    }
+
+    #[test]
+    fn bind_port() {
+        let interrupt = Interrupt::new(12).unwrap();
+        let port = Port::new();
+        interrupt.bind_port(&port, 5).unwrap();
+
+        trigger_irq(12);
+        let packet = port.pop().unwrap();
+        assert_eq!(packet.key, 5);
+        assert!(matches!(packet.payload, PacketPayload::Interrupt { .. }));
+
+        let thread = Thread::new(Weak::new());
+        assert!(interrupt.wait(&thread, None).is_err());
+    }
+
+    #[test]
+    fn dropped_port_unbinds() {
+        let interrupt = Interrupt::new(14).unwrap();
+        let port = Port::new();
+        interrupt.bind_port(&port, 5).unwrap();
+        drop(port);
+
+        trigger_irq(14);
+        assert!(interrupt.signal().contains(Signal::SIGNALED));
+        let thread = Thread::new(Weak::new());
+        interrupt.wait(&thread, None).unwrap();
+        interrupt.ack();
+    }
+
+    #[test]
+    fn drop_unbinds() {
+        drop(Interrupt::new(13).unwrap());
+        assert!(Interrupt::new(13).is_ok());
+    }
+}
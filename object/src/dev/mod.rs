@@ -0,0 +1,7 @@
+pub use bti::*;
+pub use interrupt::*;
+pub use resource::*;
+
+mod bti;
+mod interrupt;
+mod resource;
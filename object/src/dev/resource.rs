@@ -0,0 +1,122 @@
+use core::ops::Range;
+
+use alloc::sync::Arc;
+use spin::Lazy;
+
+use crate::{Errno, Result, impl_kobj, new_kobj, object::KObjectBase};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// Grants access to all hardware, and lets its holder create other resources.
+    Root,
+    /// A range of physical addresses, either MMIO or RAM.
+    Memory,
+    /// A range of interrupt vectors.
+    Irq,
+}
+
+/// A capability to access a range of some kind of hardware.
+pub struct Resource {
+    kind: ResourceKind,
+    range: Range<usize>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Resource);
+
+impl Resource {
+    /// The resource covering all hardware, which is handed to `user_boot`.
+    pub fn root() -> Arc<Self> {
+        static ROOT: Lazy<Arc<Resource>> = Lazy::new(Resource::new_root_impl);
+        ROOT.clone()
+    }
+
+    fn new_root_impl() -> Arc<Self> {
+        new_kobj!({
+            kind: ResourceKind::Root,
+            range: 0..usize::MAX,
+        })
+    }
+
+    /// Creates a resource for a sub-range this resource covers.
+    pub fn new_child(&self, kind: ResourceKind, base: usize, size: usize) -> Result<Arc<Self>> {
+        if kind == ResourceKind::Root {
+            return Err(Errno::InvArg.with_message("Can't create another root resource."));
+        }
+        let range = self.validate(kind, base, size)?;
+        Ok(new_kobj!({ kind, range }))
+    }
+
+    pub fn kind(&self) -> ResourceKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Fails unless this resource grants access to `size` units of `kind` at `base`.
+    pub fn validate(&self, kind: ResourceKind, base: usize, size: usize) -> Result<Range<usize>> {
+        let end = base
+            .checked_add(size)
+            .ok_or(Errno::InvArg.with_message("Resource range overflows."))?;
+        if self.kind == ResourceKind::Root {
+            return Ok(base..end);
+        }
+        if self.kind != kind {
+            return Err(Errno::WrongType.with_message("Resource is of another kind."));
+        }
+        if base < self.range.start || end > self.range.end {
+            return Err(Errno::AccessDenied.with_message("Resource doesn't cover the range."));
+        }
+        Ok(base..end)
+    }
+
+    /// Fails unless this is the root resource.
+    pub fn validate_root(&self) -> Result<()> {
+        if self.kind == ResourceKind::Root {
+            Ok(())
+        } else {
+            Err(Errno::AccessDenied.with_message("Root resource required."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_covers_all() {
+        let root = Resource::root();
+        assert!(root.validate(ResourceKind::Memory, 0x1000, 0x1000).is_ok());
+        assert!(root.validate(ResourceKind::Irq, 3, 1).is_ok());
+        assert!(root.validate_root().is_ok());
+    }
+
+    #[test]
+    fn child_range() {
+        let mmio = Resource::root()
+            .new_child(ResourceKind::Memory, 0x1000, 0x2000)
+            .unwrap();
+
+        assert!(mmio.validate(ResourceKind::Memory, 0x1800, 0x800).is_ok());
+        assert_eq!(
+            mmio.validate(ResourceKind::Memory, 0x2000, 0x2000)
+                .unwrap_err()
+                .errno(),
+            Errno::AccessDenied
+        );
+        assert_eq!(
+            mmio.validate(ResourceKind::Irq, 0x1000, 1)
+                .unwrap_err()
+                .errno(),
+            Errno::WrongType
+        );
+        assert!(mmio.validate_root().is_err());
+
+        assert!(mmio.new_child(ResourceKind::Memory, 0x1000, 0x1000).is_ok());
+        assert!(mmio.new_child(ResourceKind::Memory, 0, 0x1000).is_err());
+        assert!(mmio.new_child(ResourceKind::Root, 0x1000, 1).is_err());
+    }
+}
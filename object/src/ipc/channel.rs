@@ -7,7 +7,7 @@ use spin::Mutex;
 
 use crate::{
     Errno, Result, impl_kobj, new_kobj,
-    object::{Handle, KObjectBase, Upcast},
+    object::{Handle, KObjectBase, Signal, Upcast},
 };
 
 pub struct Channel {
@@ -45,6 +45,8 @@ impl Channel {
         unsafe {
             Arc::get_mut_unchecked(&mut channel0).peer = Arc::downgrade(&channel1);
         }
+        channel0.base.signal_set(Signal::WRITABLE);
+        channel1.base.signal_set(Signal::WRITABLE);
 
         (channel0, channel1)
     }
@@ -60,6 +62,9 @@ impl Channel {
     pub fn read(&self) -> Result<MessagePacket> {
         let mut recv_queue = self.recv_queue.lock();
         if let Some(msg) = recv_queue.pop_front() {
+            if recv_queue.is_empty() {
+                self.base.signal_clear(Signal::READABLE);
+            }
             Ok(msg)
         } else if self.peer_closed() {
             Err(Errno::PeerClosed.no_message())
@@ -77,6 +82,16 @@ impl Channel {
     fn push(&self, msg: MessagePacket) {
         let mut recv_queue = self.recv_queue.lock();
         recv_queue.push_back(msg);
+        self.base.signal_set(Signal::READABLE);
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base
+                .signal_change(Signal::WRITABLE, Signal::PEER_CLOSED);
+        }
     }
 }
 
@@ -116,4 +131,22 @@ mod tests {
         let recv_msg = channel1.read().unwrap();
         assert_eq!(recv_msg.data, Vec::from("Hello 1"));
     }
+
+    #[test]
+    fn signals() {
+        let (channel0, channel1) = Channel::new();
+        assert_eq!(channel0.signal(), Signal::WRITABLE);
+
+        channel1.write(MessagePacket::default()).unwrap();
+        assert_eq!(channel0.signal(), Signal::WRITABLE | Signal::READABLE);
+
+        channel0.read().unwrap();
+        assert_eq!(channel0.signal(), Signal::WRITABLE);
+
+        channel1.write(MessagePacket::default()).unwrap();
+        drop(channel1);
+        assert_eq!(channel0.signal(), Signal::READABLE | Signal::PEER_CLOSED);
+        assert!(channel0.read().is_ok());
+        assert_eq!(channel0.signal(), Signal::PEER_CLOSED);
+    }
 }
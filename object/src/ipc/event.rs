@@ -0,0 +1,108 @@
+use alloc::sync::{Arc, Weak};
+
+use crate::{
+    Errno, Result, impl_kobj, new_kobj,
+    object::{KObjectBase, Signal, Upcast},
+};
+
+pub struct Event {
+    base: KObjectBase,
+}
+
+impl_kobj!(Event
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL | Signal::SIGNALED
+    }
+);
+
+impl Event {
+    pub fn new() -> Arc<Self> {
+        new_kobj!({})
+    }
+}
+
+pub struct EventPair {
+    peer: Weak<Self>,
+    base: KObjectBase,
+}
+
+impl_kobj!(EventPair
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL | Signal::SIGNALED
+    }
+
+    fn peer(&self) -> Result<crate::object::KObject> {
+        self.peer
+            .upgrade()
+            .map(|p| p.upcast())
+            .ok_or(Errno::PeerClosed.no_message())
+    }
+);
+
+impl EventPair {
+    pub fn new() -> (Arc<Self>, Arc<Self>) {
+        let mut event0 = new_kobj!({
+            peer: Weak::default(),
+        });
+        let event1 = new_kobj!({
+            peer: Arc::downgrade(&event0),
+        });
+
+        unsafe {
+            Arc::get_mut_unchecked(&mut event0).peer = Arc::downgrade(&event1);
+        }
+
+        (event0, event1)
+    }
+
+    pub fn peer_closed(&self) -> bool {
+        self.peer.upgrade().is_none()
+    }
+}
+
+impl Drop for EventPair {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base.signal_set(Signal::PEER_CLOSED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::KernelObject;
+
+    use super::*;
+
+    #[test]
+    fn event() {
+        let event = Event::new();
+        event
+            .user_signal(Signal::empty(), Signal::SIGNALED)
+            .unwrap();
+        assert_eq!(event.signal(), Signal::SIGNALED);
+        assert!(
+            event
+                .user_signal(Signal::empty(), Signal::PEER_CLOSED)
+                .is_err()
+        );
+        assert!(event.peer().is_err());
+    }
+
+    #[test]
+    fn event_pair() {
+        let (event0, event1) = EventPair::new();
+
+        event0
+            .peer()
+            .unwrap()
+            .user_signal(Signal::empty(), Signal::USER_3)
+            .unwrap();
+        assert_eq!(event1.signal(), Signal::USER_3);
+        assert_eq!(event0.signal(), Signal::empty());
+
+        drop(event1);
+        assert!(event0.peer_closed());
+        assert!(event0.signal().contains(Signal::PEER_CLOSED));
+    }
+}
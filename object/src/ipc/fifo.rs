@@ -0,0 +1,188 @@
+use alloc::{
+    collections::vec_deque::VecDeque,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use spin::Mutex;
+
+use crate::{
+    Errno, Result, impl_kobj, new_kobj,
+    object::{KObjectBase, Signal, Upcast},
+};
+
+/// The largest buffer, in bytes, a fifo end may hold.
+pub const FIFO_MAX_SIZE: usize = 4096;
+
+/// A queue of fixed-size elements, which are moved in batches.
+pub struct Fifo {
+    peer: Weak<Self>,
+    elem_size: usize,
+    elem_count: usize,
+    recv_queue: Mutex<VecDeque<u8>>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Fifo
+    fn peer(&self) -> Result<crate::object::KObject> {
+        self.peer
+            .upgrade()
+            .map(|p| p.upcast())
+            .ok_or(Errno::PeerClosed.no_message())
+    }
+);
+
+impl Fifo {
+    pub fn new(elem_count: usize, elem_size: usize) -> Result<(Arc<Self>, Arc<Self>)> {
+        if elem_count == 0 || elem_size == 0 {
+            return Err(Errno::InvArg.with_message("Fifo elements can't be empty."));
+        }
+        if elem_count.saturating_mul(elem_size) > FIFO_MAX_SIZE {
+            return Err(Errno::TooBig.with_message("Fifo is too large."));
+        }
+
+        let capacity = elem_count * elem_size;
+        let mut fifo0 = new_kobj!({
+            peer: Weak::default(),
+            elem_size,
+            elem_count,
+            recv_queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        });
+        let fifo1 = new_kobj!({
+            peer: Arc::downgrade(&fifo0),
+            elem_size,
+            elem_count,
+            recv_queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        });
+
+        unsafe {
+            Arc::get_mut_unchecked(&mut fifo0).peer = Arc::downgrade(&fifo1);
+        }
+        fifo0.base.signal_set(Signal::WRITABLE);
+        fifo1.base.signal_set(Signal::WRITABLE);
+
+        Ok((fifo0, fifo1))
+    }
+
+    pub fn elem_size(&self) -> usize {
+        self.elem_size
+    }
+
+    pub fn elem_count(&self) -> usize {
+        self.elem_count
+    }
+
+    pub fn peer_closed(&self) -> bool {
+        self.peer.upgrade().is_none()
+    }
+}
+
+impl Fifo {
+    /// Writes as many whole elements from `data` as the peer has room for.
+    /// Returns the number of elements written.
+    pub fn write(&self, data: &[u8]) -> Result<usize> {
+        if data.is_empty() || !data.len().is_multiple_of(self.elem_size) {
+            return Err(Errno::InvArg.with_message("Data is not made of whole elements."));
+        }
+        let peer = self.peer.upgrade().ok_or(Errno::PeerClosed.no_message())?;
+
+        let mut recv_queue = peer.recv_queue.lock();
+        let space = self.elem_count - recv_queue.len() / self.elem_size;
+        let count = space.min(data.len() / self.elem_size);
+        if count == 0 {
+            return Err(Errno::ShouldWait.no_message());
+        }
+
+        recv_queue.extend(&data[..count * self.elem_size]);
+        peer.base.signal_set(Signal::READABLE);
+        if count == space {
+            self.base.signal_clear(Signal::WRITABLE);
+        }
+        Ok(count)
+    }
+
+    /// Reads up to `count` elements.
+    pub fn read(&self, count: usize) -> Result<Vec<u8>> {
+        if count == 0 {
+            return Err(Errno::InvArg.with_message("Can't read zero elements."));
+        }
+
+        let mut recv_queue = self.recv_queue.lock();
+        if recv_queue.is_empty() {
+            return if self.peer_closed() {
+                Err(Errno::PeerClosed.no_message())
+            } else {
+                Err(Errno::ShouldWait.no_message())
+            };
+        }
+
+        let was_full = recv_queue.len() == self.elem_count * self.elem_size;
+        let len = recv_queue.len().min(count * self.elem_size);
+        let data = recv_queue.drain(..len).collect();
+
+        if recv_queue.is_empty() {
+            self.base.signal_clear(Signal::READABLE);
+        }
+        if was_full && let Some(peer) = self.peer.upgrade() {
+            peer.base.signal_set(Signal::WRITABLE);
+        }
+        Ok(data)
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base
+                .signal_change(Signal::WRITABLE, Signal::PEER_CLOSED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::KernelObject;
+
+    use super::*;
+
+    #[test]
+    fn new_fifo() {
+        assert!(Fifo::new(4, 8).is_ok());
+        assert!(Fifo::new(0, 8).is_err());
+        assert!(Fifo::new(1024, 8).is_err());
+    }
+
+    #[test]
+    fn read_write() {
+        let (fifo0, fifo1) = Fifo::new(4, 2).unwrap();
+
+        assert_eq!(fifo0.write(&[1, 2, 3, 4]).unwrap(), 2);
+        assert!(fifo1.signal().contains(Signal::READABLE));
+        assert!(fifo0.write(&[1, 2, 3]).is_err());
+
+        assert_eq!(fifo1.read(1).unwrap(), [1, 2]);
+        assert_eq!(fifo1.read(4).unwrap(), [3, 4]);
+        assert!(!fifo1.signal().contains(Signal::READABLE));
+        assert_eq!(fifo1.read(1).unwrap_err().errno(), Errno::ShouldWait);
+    }
+
+    #[test]
+    fn full() {
+        let (fifo0, fifo1) = Fifo::new(2, 1).unwrap();
+
+        assert_eq!(fifo0.write(&[1, 2, 3]).unwrap(), 2);
+        assert!(!fifo0.signal().contains(Signal::WRITABLE));
+        assert_eq!(fifo0.write(&[4]).unwrap_err().errno(), Errno::ShouldWait);
+
+        fifo1.read(1).unwrap();
+        assert!(fifo0.signal().contains(Signal::WRITABLE));
+        assert_eq!(fifo0.write(&[4]).unwrap(), 1);
+    }
+
+    #[test]
+    fn peer_closed() {
+        let (fifo0, fifo1) = Fifo::new(2, 1).unwrap();
+        drop(fifo1);
+        assert!(fifo0.signal().contains(Signal::PEER_CLOSED));
+        assert_eq!(fifo0.write(&[1]).unwrap_err().errno(), Errno::PeerClosed);
+    }
+}
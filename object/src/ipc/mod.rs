@@ -1,3 +1,11 @@
 pub use channel::*;
+pub use event::*;
+pub use fifo::*;
+pub use port::*;
+pub use socket::*;
 
 mod channel;
+mod event;
+mod fifo;
+mod port;
+mod socket;
@@ -0,0 +1,271 @@
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use alloc::{
+    boxed::Box,
+    collections::vec_deque::VecDeque,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use spin::Mutex;
+
+use crate::{
+    Errno, Result, impl_kobj, new_kobj,
+    object::{KObject, KObjectBase, KernelObject, Signal, SignalCallbackId},
+};
+
+pub const PORT_PACKET_PAYLOAD_LEN: usize = 32;
+
+pub struct Port {
+    queue: Mutex<VecDeque<PortPacket>>,
+    /// `wait_async` registrations that haven't fired yet, so that they can be cancelled.
+    waits: Mutex<Vec<AsyncWait>>,
+    next_wait_id: AtomicU64,
+    base: KObjectBase,
+}
+
+struct AsyncWait {
+    id: u64,
+    key: u64,
+    object: Weak<dyn KernelObject>,
+    /// Filled once the callback is registered on the object.
+    callback: Option<SignalCallbackId>,
+}
+
+impl_kobj!(Port);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortPacket {
+    pub key: u64,
+    pub payload: PacketPayload,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketPayload {
+    /// Queued by `wait_async` when the watched object asserts a signal.
+    Signal { trigger: Signal, observed: Signal },
+    /// Queued directly by user space.
+    User([u8; PORT_PACKET_PAYLOAD_LEN]),
+    /// Queued by an `Interrupt` bound to the port when its line fires.
+    Interrupt { timestamp: Duration },
+}
+
+impl Port {
+    pub fn new() -> Arc<Self> {
+        new_kobj!({
+            queue: Mutex::new(VecDeque::new()),
+            waits: Mutex::new(Vec::new()),
+            next_wait_id: AtomicU64::new(0),
+        })
+    }
+}
+
+impl Port {
+    pub fn queue(&self, packet: PortPacket) {
+        self.queue.lock().push_back(packet);
+        // Set unlocked, as it may run callbacks that queue into this port.
+        // A packet popped in between leaves a stale `READABLE`, which the next
+        // `pop` finding the queue empty clears.
+        self.base.signal_set(Signal::READABLE);
+    }
+
+    pub fn pop(&self) -> Option<PortPacket> {
+        let mut queue = self.queue.lock();
+        let packet = queue.pop_front();
+        if queue.is_empty() {
+            self.base.signal_clear(Signal::READABLE);
+        }
+        packet
+    }
+
+    /// Puts back a popped packet at the front, as if it had never been popped.
+    pub fn requeue(&self, packet: PortPacket) {
+        self.queue.lock().push_front(packet);
+        self.base.signal_set(Signal::READABLE);
+    }
+
+    /// Queues a signal packet with `key` once `object` asserts any of `signals`.
+    /// The registration fires only once, unless cancelled first with [`Port::cancel`].
+    pub fn wait_async(self: &Arc<Self>, object: &KObject, key: u64, signals: Signal) -> Result<()> {
+        if Arc::as_ptr(object) as *const () == Arc::as_ptr(self) as *const () {
+            return Err(Errno::InvArg.with_message("A port can't wait on itself."));
+        }
+
+        let id = self.next_wait_id.fetch_add(1, Ordering::SeqCst);
+        self.waits.lock().push(AsyncWait {
+            id,
+            key,
+            object: Arc::downgrade(object),
+            callback: None,
+        });
+
+        let port = Arc::downgrade(self);
+        let callback = object.add_signal_callback(
+            signals,
+            Box::new(move |current| {
+                if let Some(port) = port.upgrade() {
+                    port.waits.lock().retain(|wait| wait.id != id);
+                    port.queue(PortPacket {
+                        key,
+                        payload: PacketPayload::Signal {
+                            trigger: signals,
+                            observed: current,
+                        },
+                    });
+                }
+            }),
+        );
+        // Unless it has fired already.
+        if let Some(wait) = self.waits.lock().iter_mut().find(|wait| wait.id == id) {
+            wait.callback = Some(callback);
+        }
+        Ok(())
+    }
+
+    /// Cancels the registrations of `wait_async` on `object` with `key` that haven't fired yet.
+    /// Packets they have already queued stay in the port.
+    pub fn cancel(&self, object: &KObject, key: u64) -> Result<()> {
+        let cancelled = self
+            .waits
+            .lock()
+            .extract_if(.., |wait| {
+                wait.key == key
+                    && wait.object.as_ptr() as *const () == Arc::as_ptr(object) as *const ()
+            })
+            .collect::<Vec<_>>();
+        if cancelled.is_empty() {
+            return Err(Errno::NotFound.with_message("No pending wait with this key."));
+        }
+        for callback in cancelled.into_iter().filter_map(|wait| wait.callback) {
+            object.remove_signal_callback(callback);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ipc::{Channel, MessagePacket},
+        object::{KernelObject, Upcast},
+    };
+
+    use super::*;
+
+    #[test]
+    fn queue_pop() {
+        let port = Port::new();
+        assert_eq!(port.pop(), None);
+
+        let packet = PortPacket {
+            key: 1,
+            payload: PacketPayload::User([7; PORT_PACKET_PAYLOAD_LEN]),
+        };
+        port.queue(packet);
+        assert!(port.signal().contains(Signal::READABLE));
+
+        assert_eq!(port.pop(), Some(packet));
+        assert!(!port.signal().contains(Signal::READABLE));
+    }
+
+    #[test]
+    fn wait_async() {
+        let port = Port::new();
+        let (channel0, channel1) = Channel::new();
+
+        port.wait_async(&channel0.upcast(), 42, Signal::READABLE)
+            .unwrap();
+        assert_eq!(port.pop(), None);
+
+        channel1.write(MessagePacket::default()).unwrap();
+        assert_eq!(
+            port.pop(),
+            Some(PortPacket {
+                key: 42,
+                payload: PacketPayload::Signal {
+                    trigger: Signal::READABLE,
+                    observed: Signal::READABLE | Signal::WRITABLE,
+                },
+            })
+        );
+
+        // One-shot: a second message doesn't queue anything.
+        channel1.write(MessagePacket::default()).unwrap();
+        assert_eq!(port.pop(), None);
+    }
+
+    #[test]
+    fn wait_async_already_asserted() {
+        let port = Port::new();
+        let (channel0, _channel1) = Channel::new();
+
+        port.wait_async(&channel0.upcast(), 0, Signal::WRITABLE)
+            .unwrap();
+        assert!(port.pop().is_some());
+    }
+
+    #[test]
+    fn wait_async_self() {
+        let port = Port::new();
+        assert!(
+            port.wait_async(&port.upcast(), 0, Signal::READABLE)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn wait_async_on_each_other() {
+        let port0 = Port::new();
+        let port1 = Port::new();
+        port0
+            .wait_async(&port1.clone().upcast(), 0, Signal::READABLE)
+            .unwrap();
+        port1
+            .wait_async(&port0.clone().upcast(), 1, Signal::READABLE)
+            .unwrap();
+
+        port0.queue(PortPacket {
+            key: 2,
+            payload: PacketPayload::User([0; PORT_PACKET_PAYLOAD_LEN]),
+        });
+        assert_eq!(port0.pop().map(|packet| packet.key), Some(2));
+        assert_eq!(port0.pop().map(|packet| packet.key), Some(0));
+        assert_eq!(port1.pop().map(|packet| packet.key), Some(1));
+    }
+
+    #[test]
+    fn cancel() {
+        let port = Port::new();
+        let (channel0, channel1) = Channel::new();
+        let object = channel0.upcast();
+
+        port.wait_async(&object, 42, Signal::READABLE).unwrap();
+        assert!(port.cancel(&object, 7).is_err());
+        port.cancel(&object, 42).unwrap();
+        assert!(port.cancel(&object, 42).is_err());
+
+        channel1.write(MessagePacket::default()).unwrap();
+        assert_eq!(port.pop(), None);
+
+        // A fired registration is gone.
+        port.wait_async(&object, 42, Signal::READABLE).unwrap();
+        assert!(port.pop().is_some());
+        assert!(port.cancel(&object, 42).is_err());
+    }
+
+    #[test]
+    fn requeue() {
+        let port = Port::new();
+        for key in 0..2 {
+            port.queue(PortPacket {
+                key,
+                payload: PacketPayload::User([0; PORT_PACKET_PAYLOAD_LEN]),
+            });
+        }
+        let packet = port.pop().unwrap();
+        port.requeue(packet);
+        assert_eq!(port.pop(), Some(packet));
+    }
+}
@@ -0,0 +1,295 @@
+use alloc::{
+    collections::vec_deque::VecDeque,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use spin::Mutex;
+
+use crate::{
+    Errno, Result, impl_kobj, new_kobj,
+    object::{KObjectBase, Signal, Upcast},
+};
+
+/// The number of bytes a socket end buffers before writers have to wait.
+pub const SOCKET_CAPACITY: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketType {
+    /// A byte stream, which may be read and written partially.
+    Stream,
+    /// A queue of datagrams, each read and written as a whole.
+    Datagram,
+}
+
+pub struct Socket {
+    peer: Weak<Self>,
+    socket_type: SocketType,
+    inner: Mutex<SocketInner>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Socket
+    fn peer(&self) -> Result<crate::object::KObject> {
+        self.peer
+            .upgrade()
+            .map(|p| p.upcast())
+            .ok_or(Errno::PeerClosed.no_message())
+    }
+);
+
+#[derive(Default)]
+struct SocketInner {
+    data: VecDeque<u8>,
+    datagrams: VecDeque<usize>,
+    /// The writer found no room and waits for `WRITABLE`.
+    writer_blocked: bool,
+    read_disabled: bool,
+    write_disabled: bool,
+}
+
+impl SocketInner {
+    fn space(&self) -> usize {
+        SOCKET_CAPACITY - self.data.len()
+    }
+}
+
+impl Socket {
+    pub fn new(socket_type: SocketType) -> (Arc<Self>, Arc<Self>) {
+        let mut socket0 = new_kobj!({
+            peer: Weak::default(),
+            socket_type,
+            inner: Mutex::new(SocketInner::default()),
+        });
+        let socket1 = new_kobj!({
+            peer: Arc::downgrade(&socket0),
+            socket_type,
+            inner: Mutex::new(SocketInner::default()),
+        });
+
+        unsafe {
+            Arc::get_mut_unchecked(&mut socket0).peer = Arc::downgrade(&socket1);
+        }
+        socket0.base.signal_set(Signal::WRITABLE);
+        socket1.base.signal_set(Signal::WRITABLE);
+
+        (socket0, socket1)
+    }
+
+    pub fn socket_type(&self) -> SocketType {
+        self.socket_type
+    }
+
+    pub fn peer_closed(&self) -> bool {
+        self.peer.upgrade().is_none()
+    }
+}
+
+impl Socket {
+    /// Writes as much of `data` as fits into the peer's buffer.
+    /// Datagrams are only written as a whole.
+    pub fn write(&self, data: &[u8]) -> Result<usize> {
+        if self.inner.lock().write_disabled {
+            return Err(Errno::BadState.with_message("Writing is disabled."));
+        }
+        let peer = self.peer.upgrade().ok_or(Errno::PeerClosed.no_message())?;
+        peer.push(self, data)
+    }
+
+    /// Reads up to `len` bytes, or the next datagram truncated to `len` bytes.
+    pub fn read(&self, len: usize) -> Result<Vec<u8>> {
+        let mut inner = self.inner.lock();
+        if inner.data.is_empty() {
+            return if inner.read_disabled || self.peer_closed() {
+                Err(Errno::PeerClosed.no_message())
+            } else {
+                Err(Errno::ShouldWait.no_message())
+            };
+        }
+
+        let data = match self.socket_type {
+            SocketType::Stream => {
+                let count = len.min(inner.data.len());
+                inner.data.drain(..count).collect()
+            }
+            SocketType::Datagram => {
+                let size = inner.datagrams.pop_front().unwrap();
+                let mut datagram: Vec<u8> = inner.data.drain(..size).collect();
+                datagram.truncate(len);
+                datagram
+            }
+        };
+
+        if inner.data.is_empty() {
+            self.base.signal_clear(Signal::READABLE);
+        }
+        if inner.writer_blocked
+            && !inner.read_disabled
+            && let Some(peer) = self.peer.upgrade()
+        {
+            inner.writer_blocked = false;
+            peer.base.signal_set(Signal::WRITABLE);
+        }
+        Ok(data)
+    }
+
+    /// Disables reading and/or writing on this end.
+    /// Disabling writes lets the peer drain what's left and then read EOF.
+    pub fn shutdown(&self, read: bool, write: bool) -> Result<()> {
+        let peer = self.peer.upgrade();
+
+        if write {
+            self.inner.lock().write_disabled = true;
+            self.base
+                .signal_change(Signal::WRITABLE, Signal::WRITE_DISABLED);
+            if let Some(peer) = &peer {
+                peer.inner.lock().read_disabled = true;
+                peer.base.signal_set(Signal::PEER_WRITE_DISABLED);
+            }
+        }
+        if read {
+            self.inner.lock().read_disabled = true;
+            self.base.signal_set(Signal::PEER_WRITE_DISABLED);
+            if let Some(peer) = &peer {
+                peer.inner.lock().write_disabled = true;
+                peer.base
+                    .signal_change(Signal::WRITABLE, Signal::WRITE_DISABLED);
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&self, writer: &Socket, data: &[u8]) -> Result<usize> {
+        let mut inner = self.inner.lock();
+        if inner.read_disabled {
+            return Err(Errno::BadState.with_message("Peer has disabled reading."));
+        }
+
+        let space = inner.space();
+        let count = match self.socket_type {
+            SocketType::Stream => data.len().min(space),
+            SocketType::Datagram if data.is_empty() => {
+                return Err(Errno::InvArg.with_message("Datagram is empty."));
+            }
+            SocketType::Datagram if data.len() > SOCKET_CAPACITY => {
+                return Err(Errno::TooBig.with_message("Datagram is larger than the buffer."));
+            }
+            SocketType::Datagram if data.len() > space => 0,
+            SocketType::Datagram => {
+                inner.datagrams.push_back(data.len());
+                data.len()
+            }
+        };
+        if count == 0 && !data.is_empty() {
+            // Wait for a read to make room, rather than for the buffer to be full.
+            inner.writer_blocked = true;
+            writer.base.signal_clear(Signal::WRITABLE);
+            return Err(Errno::ShouldWait.no_message());
+        }
+
+        inner.data.extend(&data[..count]);
+        if !inner.data.is_empty() {
+            self.base.signal_set(Signal::READABLE);
+        }
+        if inner.space() == 0 {
+            inner.writer_blocked = true;
+            writer.base.signal_clear(Signal::WRITABLE);
+        }
+        Ok(count)
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base
+                .signal_change(Signal::WRITABLE, Signal::PEER_CLOSED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::object::KernelObject;
+
+    use super::*;
+
+    #[test]
+    fn stream() {
+        let (socket0, socket1) = Socket::new(SocketType::Stream);
+
+        assert_eq!(socket0.write(b"Hello, world").unwrap(), 12);
+        assert!(socket1.signal().contains(Signal::READABLE));
+
+        assert_eq!(socket1.read(5).unwrap(), b"Hello");
+        assert_eq!(socket1.read(64).unwrap(), b", world");
+        assert!(!socket1.signal().contains(Signal::READABLE));
+        assert_eq!(socket1.read(64).unwrap_err().errno(), Errno::ShouldWait);
+    }
+
+    #[test]
+    fn datagram() {
+        let (socket0, socket1) = Socket::new(SocketType::Datagram);
+
+        socket0.write(b"first").unwrap();
+        socket0.write(b"second").unwrap();
+
+        assert_eq!(socket1.read(3).unwrap(), b"fir");
+        assert_eq!(socket1.read(64).unwrap(), b"second");
+    }
+
+    #[test]
+    fn backpressure() {
+        let (socket0, socket1) = Socket::new(SocketType::Stream);
+
+        let data = vec![0u8; SOCKET_CAPACITY + 1];
+        assert_eq!(socket0.write(&data).unwrap(), SOCKET_CAPACITY);
+        assert!(!socket0.signal().contains(Signal::WRITABLE));
+        assert_eq!(socket0.write(&data).unwrap_err().errno(), Errno::ShouldWait);
+
+        socket1.read(1).unwrap();
+        assert!(socket0.signal().contains(Signal::WRITABLE));
+        assert_eq!(socket0.write(&data).unwrap(), 1);
+    }
+
+    #[test]
+    fn datagram_backpressure() {
+        let (socket0, socket1) = Socket::new(SocketType::Datagram);
+
+        let data = vec![0u8; SOCKET_CAPACITY / 2 + 1];
+        socket0.write(&data).unwrap();
+        assert!(socket0.signal().contains(Signal::WRITABLE));
+        // Does not fit, so the writer has to wait for a read.
+        assert_eq!(socket0.write(&data).unwrap_err().errno(), Errno::ShouldWait);
+        assert!(!socket0.signal().contains(Signal::WRITABLE));
+
+        socket1.read(64).unwrap();
+        assert!(socket0.signal().contains(Signal::WRITABLE));
+        socket0.write(&data).unwrap();
+
+        assert_eq!(socket0.write(b"").unwrap_err().errno(), Errno::InvArg);
+    }
+
+    #[test]
+    fn shutdown() {
+        let (socket0, socket1) = Socket::new(SocketType::Stream);
+
+        socket0.write(b"bye").unwrap();
+        socket0.shutdown(false, true).unwrap();
+        assert!(socket0.write(b"more").is_err());
+        assert!(socket1.signal().contains(Signal::PEER_WRITE_DISABLED));
+
+        assert_eq!(socket1.read(64).unwrap(), b"bye");
+        assert_eq!(socket1.read(64).unwrap_err().errno(), Errno::PeerClosed);
+        assert_eq!(socket1.write(b"reply").unwrap(), 5);
+    }
+
+    #[test]
+    fn peer_closed() {
+        let (socket0, socket1) = Socket::new(SocketType::Stream);
+        drop(socket1);
+        assert!(socket0.signal().contains(Signal::PEER_CLOSED));
+        assert_eq!(socket0.write(b"x").unwrap_err().errno(), Errno::PeerClosed);
+    }
+}
@@ -5,10 +5,12 @@ extern crate alloc;
 
 use errors::*;
 
+pub mod dev;
 pub mod ipc;
 pub mod mem;
 pub mod object;
 pub mod task;
+pub mod time;
 
 pub fn init() {
     task::init();
@@ -1,7 +1,7 @@
 use kernel_hal::mem::PageSize;
 
-pub use vmar::Vmar;
-pub use vmo::Vmo;
+pub use vmar::{MapOptions, MappingInfo, Vmar};
+pub use vmo::{Vmo, VmoChildKind, VmoOp};
 
 mod vmar;
 mod vmo;
@@ -1,12 +1,15 @@
-use crate::{Errno, Result};
-use alloc::sync::Arc;
-use kernel_hal::mem::{MMUFlags, PageProperty, VirtAddr};
+use crate::{Errno, Result, mem::VmoChildKind};
+use alloc::sync::{Arc, Weak};
+use kernel_hal::mem::{MMUFlags, PageProperty, VirtAddr, VmSpace};
 
-use super::{PAGE_SIZE, Vmo, align_down_by_page_size};
+use super::{PAGE_SIZE, Vmo};
 
 #[derive(Debug)]
 pub struct VmMapping {
     vmo: Arc<Vmo>,
+    /// Where in the VMO the mapping starts, in bytes.
+    vmo_offset: usize,
+    vm_space: Weak<VmSpace>,
     start: VirtAddr,
     size: usize,
     prop: PageProperty,
@@ -16,13 +19,18 @@ pub struct VmMapping {
 impl VmMapping {
     pub fn new(
         vmo: Arc<Vmo>,
+        vmo_offset: usize,
+        vm_space: &Arc<VmSpace>,
         start: VirtAddr,
         size: usize,
         prop: PageProperty,
         perm: MMUFlags,
     ) -> Self {
+        vmo.add_mapping(vm_space, start, size, vmo_offset);
         VmMapping {
             vmo,
+            vmo_offset,
+            vm_space: Arc::downgrade(vm_space),
             start,
             size,
             prop,
@@ -37,8 +45,13 @@ impl VmMapping {
         &self.vmo
     }
 
-    pub fn vmo_mut(&mut self) -> &mut Arc<Vmo> {
-        &mut self.vmo
+    pub fn vmo_offset(&self) -> usize {
+        self.vmo_offset
+    }
+
+    /// Returns the VMO offset that `addr` maps.
+    pub fn vmo_offset_of(&self, addr: VirtAddr) -> usize {
+        addr - self.start + self.vmo_offset
     }
 
     pub fn start(&self) -> VirtAddr {
@@ -90,16 +103,25 @@ impl VmMapping {
             return Err(Errno::InvArg.no_message());
         }
 
+        let vm_space = self
+            .vm_space
+            .upgrade()
+            .ok_or(Errno::BadState.with_message("Address space is gone."))?;
         let offset = addr - self.start();
 
-        let left_vmo = self.vmo().clone();
-        let right_vmo = self
-            .vmo()
-            .split(align_down_by_page_size(offset) / PAGE_SIZE)?;
-
-        let left = Self::new(left_vmo, self.start(), offset, self.prop(), self.perm());
+        let left = Self::new(
+            self.vmo.clone(),
+            self.vmo_offset,
+            &vm_space,
+            self.start(),
+            offset,
+            self.prop(),
+            self.perm(),
+        );
         let right = Self::new(
-            right_vmo,
+            self.vmo.clone(),
+            self.vmo_offset + offset,
+            &vm_space,
             addr,
             self.size() - offset,
             self.prop(),
@@ -148,18 +170,28 @@ impl VmMapping {
 }
 
 impl VmMapping {
-    pub fn clone(&mut self) -> Result<Self> {
-        let mut prop = self.prop;
-        prop.flags.remove(MMUFlags::WRITE);
-
-        self.set_prop(prop);
+    /// Copies the mapping into `vm_space` over a snapshot of the mapped
+    /// window, whose pages are copied one at a time as either side writes them.
+    /// Device memory stays shared.
+    pub fn clone(&self, vm_space: &Arc<VmSpace>) -> Result<Self> {
+        let (vmo, vmo_offset) = if self.vmo.is_iomem() {
+            (self.vmo.clone(), self.vmo_offset)
+        } else {
+            let snapshot =
+                self.vmo
+                    .create_child(VmoChildKind::Snapshot, self.vmo_offset, self.size)?;
+            (snapshot, 0)
+        };
 
         Ok(Self::new(
-            self.vmo.clone(),
-            self.start,
-            self.size,
-            prop,
-            self.perm,
+            vmo, vmo_offset, vm_space, self.start, self.size, self.prop, self.perm,
         ))
     }
 }
+
+impl Drop for VmMapping {
+    fn drop(&mut self) {
+        self.vmo
+            .remove_mapping(&self.vm_space, self.start, self.size);
+    }
+}
@@ -1,10 +1,10 @@
 use core::ops::Range;
 
-use crate::object::KObjectBase;
+use crate::object::{KObjectBase, KernelObject, Koid};
 use crate::{Errno, Result, impl_kobj, new_kobj};
 use alloc::{sync::Arc, vec::Vec};
 use kernel_hal::mem::{
-    KERNEL_ASPACE_BASE, KERNEL_ASPACE_SIZE, MMUFlags, PageProperty, VirtAddr, VmSpace,
+    KERNEL_ASPACE_BASE, KERNEL_ASPACE_SIZE, MMUFlags, PageProperty, PhysAddr, VirtAddr, VmSpace,
 };
 use kernel_hal::mem::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use spin::{Lazy, Mutex, RwLock};
@@ -30,10 +30,76 @@ pub struct Vmar {
 
 impl_kobj!(Vmar);
 
+/// A snapshot of one mapping, as listed by [`Vmar::mappings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappingInfo {
+    pub base: VirtAddr,
+    pub size: usize,
+    pub perm: MMUFlags,
+    pub vmo_koid: Koid,
+}
+
+/// Where and how [`Vmar::map_with`] places a mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapOptions {
+    offset: Option<usize>,
+    overwrite: bool,
+    align: usize,
+    map_range: bool,
+}
+
+impl MapOptions {
+    /// Lets the VMAR choose a page-aligned free range.
+    pub const fn new() -> Self {
+        Self {
+            offset: None,
+            overwrite: false,
+            align: PAGE_SIZE,
+            map_range: false,
+        }
+    }
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapOptions {
+    /// Maps at `offset` from the VMAR base, which must be free.
+    pub fn specific(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self.overwrite = false;
+        self
+    }
+
+    /// Maps at `offset` from the VMAR base, replacing the mappings there.
+    pub fn specific_overwrite(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self.overwrite = true;
+        self
+    }
+
+    /// Sets the alignment of a range chosen by the VMAR,
+    /// a power of two no smaller than a page.
+    pub fn align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Commits and maps every page up front instead of on first access.
+    pub fn map_range(mut self) -> Self {
+        self.map_range = true;
+        self
+    }
+}
+
 #[derive(Debug)]
 struct VmarInner {
     vm_mappings: Vec<VmMapping>,
     children: Vec<Arc<Vmar>>,
+    destroyed: bool,
 }
 
 impl Vmar {
@@ -54,6 +120,7 @@ impl Vmar {
             inner: RwLock::new(VmarInner {
                 vm_mappings: Vec::new(),
                 children: Vec::new(),
+                destroyed: false,
             }),
             lock: Mutex::new(()),
             base_addr: USER_ASPACE_BASE,
@@ -69,6 +136,7 @@ impl Vmar {
                 inner: RwLock::new(VmarInner {
                     vm_mappings: Vec::new(),
                     children: Vec::new(),
+                    destroyed: false,
                 }),
                 lock: Mutex::new(()),
                 base_addr: KERNEL_ASPACE_BASE,
@@ -111,6 +179,7 @@ impl Vmar {
             inner: RwLock::new(VmarInner {
                 vm_mappings: Vec::new(),
                 children: Vec::new(),
+                destroyed: false,
             }),
             lock: Mutex::new(()),
             base_addr: base,
@@ -132,6 +201,7 @@ impl Vmar {
             panic!("Lock optimized");
         }
 
+        self.check_alive()?;
         if !self.range_is_completely_free(base, size) {
             return Err(Errno::OutOfMemory.no_message());
         }
@@ -153,6 +223,16 @@ impl Vmar {
             panic!("Lock optimized");
         }
 
+        self.check_alive()?;
+        let base = self
+            .find_free_range(size, PAGE_SIZE)
+            .ok_or(Errno::OutOfMemory.no_message())?;
+        self.add_child(base, size)
+    }
+
+    /// Finds the lowest `align`-aligned range of `size` bytes clear of
+    /// mappings and children.
+    fn find_free_range(&self, size: usize, align: usize) -> Option<VirtAddr> {
         let mut regions = {
             let inner = self.inner.read();
             inner
@@ -168,22 +248,19 @@ impl Vmar {
                 .collect::<Vec<_>>()
         };
         regions.sort();
+        regions.push((self.end(), self.end()));
 
         let mut last_end = self.base();
 
         for (start, end) in regions {
-            let usable = start - last_end;
-            if usable >= size {
-                return self.add_child(last_end, size);
+            let candidate = last_end.checked_next_multiple_of(align)?;
+            if candidate <= start && start - candidate >= size {
+                return Some(candidate);
             }
-            last_end = end;
+            last_end = last_end.max(end);
         }
 
-        if self.end() - last_end < size {
-            Err(Errno::OutOfMemory.no_message())
-        } else {
-            self.add_child(last_end, size)
-        }
+        None
     }
 }
 
@@ -203,6 +280,14 @@ impl Vmar {
         Ok(())
     }
 
+    /// Removes the pages put in place by [`Vmar::direct_map`].
+    pub fn direct_unmap(&self, offset: usize, size: usize) -> Result<()> {
+        let addr = align_down_by_page_size(self.base() + offset);
+        let mut cursor = self.vm_space.cursor(addr)?;
+        cursor.unmap(size)
+    }
+
+    /// Maps the whole of `vmo` at `offset` from the VMAR base.
     pub fn map(
         &self,
         offset: usize,
@@ -210,47 +295,103 @@ impl Vmar {
         prop: PageProperty,
         process_overlap: bool,
     ) -> Result<()> {
-        let addr = self.base() + offset;
-        let size = vmo.len();
+        if vmo.is_empty() {
+            return Ok(());
+        }
 
-        log::debug!(
-            "Vmar::map: addr={:#x} size={:#x} flags={:x}",
-            addr,
-            size,
-            prop.flags
-        );
+        let options = if process_overlap {
+            MapOptions::new().specific_overwrite(offset)
+        } else {
+            MapOptions::new().specific(offset)
+        };
+        self.map_with(options, vmo, 0, vmo.len(), prop)?;
+        Ok(())
+    }
 
-        if !self.contains_range(addr, size) {
-            return Err(Errno::InvArg.with_message("Out of VMAR range!"));
+    /// Maps `len` bytes of `vmo` from the page-aligned `vmo_offset` on,
+    /// placed as `options` says, and returns the address of the mapping.
+    pub fn map_with(
+        &self,
+        options: MapOptions,
+        vmo: &Arc<Vmo>,
+        vmo_offset: usize,
+        len: usize,
+        prop: PageProperty,
+    ) -> Result<VirtAddr> {
+        if len == 0 || !vmo_offset.is_multiple_of(PAGE_SIZE) {
+            return Err(Errno::InvArg.no_message());
         }
-        if size == 0 {
-            return Ok(());
+        if !options.align.is_power_of_two() || options.align < PAGE_SIZE {
+            return Err(Errno::InvArg.with_message("Invalid alignment."));
         }
+        if vmo_offset
+            .checked_add(len)
+            .is_none_or(|end| end > align_up_by_page_size(vmo.len()))
+        {
+            return Err(Errno::InvArg.with_message("Range is out of the VMO."));
+        }
+        let size = align_up_by_page_size(len);
 
         let _guard = self.lock.lock();
         if !self.lock.is_locked() {
             panic!("Lock optimized");
         }
 
-        if !self.range_is_child_free(addr, size) {
-            return Err(Errno::OutOfMemory.no_message());
-        }
+        self.check_alive()?;
+        let addr = match options.offset {
+            Some(offset) => {
+                let addr = self
+                    .base()
+                    .checked_add(offset)
+                    .filter(|addr| addr.is_multiple_of(PAGE_SIZE))
+                    .ok_or(Errno::InvArg.no_message())?;
+                if !self.contains_range(addr, size) {
+                    return Err(Errno::InvArg.with_message("Out of VMAR range!"));
+                }
+                if !self.range_is_child_free(addr, size)
+                    || (!options.overwrite && !self.range_is_completely_free(addr, size))
+                {
+                    return Err(Errno::OutOfMemory.no_message());
+                }
+                addr
+            }
+            None => self
+                .find_free_range(size, options.align)
+                .ok_or(Errno::OutOfMemory.no_message())?,
+        };
 
-        let aligned = align_down_by_page_size(addr);
-        let vm_mapping = VmMapping::new(vmo.clone(), aligned, size, prop, prop.flags);
+        log::debug!(
+            "Vmar::map: addr={:#x} size={:#x} vmo_offset={:#x} flags={:x}",
+            addr,
+            size,
+            vmo_offset,
+            prop.flags
+        );
 
-        if process_overlap {
+        let vm_mapping = VmMapping::new(
+            vmo.clone(),
+            vmo_offset,
+            &self.vm_space,
+            addr,
+            size,
+            prop,
+            prop.flags,
+        );
+
+        if options.overwrite {
             self.insert_truncate_others(vm_mapping)?;
+            self.vm_space.cursor(addr)?.clear(size);
         } else {
             self.insert(vm_mapping)?;
         }
 
-        #[cfg(feature = "libos")]
-        for id in 0..size / PAGE_SIZE {
-            self.handle_page_fault(aligned + id * PAGE_SIZE, prop.flags)?;
+        if options.map_range || cfg!(feature = "libos") {
+            for id in 0..size / PAGE_SIZE {
+                self.handle_page_fault(addr + id * PAGE_SIZE, prop.flags)?;
+            }
         }
 
-        Ok(())
+        Ok(addr)
     }
 
     pub fn unmap(&self, addr: VirtAddr, size: usize) -> Result<()> {
@@ -382,6 +523,101 @@ impl Vmar {
             .0
             .start()
     }
+
+    /// Resolves `addr` to a physical address through the VMO mapped there.
+    pub fn translate(&self, addr: VirtAddr) -> Result<PhysAddr> {
+        if let Some(child) = self.find_child(addr) {
+            return child.translate(addr);
+        }
+
+        let (vmo_offset, vmo) = self
+            .inner
+            .read()
+            .vm_mappings
+            .iter()
+            .find(|mapping| mapping.contains(addr))
+            .map(|mapping| (mapping.vmo_offset_of(addr), mapping.vmo().clone()))
+            .ok_or(Errno::PageFault.no_message())?;
+        vmo.physical_address(vmo_offset)
+    }
+}
+
+impl Vmar {
+    /// Unmaps everything in this VMAR and destroys its children.
+    /// A destroyed VMAR takes no new mappings or children,
+    /// and the page table of a destroyed root VMAR is released.
+    pub fn destroy(&self) {
+        // Releasing the page table of a root drops every translation at once.
+        self.destroy_impl(!self.is_root);
+        if self.is_root {
+            self.vm_space.release();
+        }
+    }
+
+    fn destroy_impl(&self, unmap: bool) {
+        let _guard = self.lock.lock();
+        let (mappings, children) = {
+            let mut inner = self.inner.write();
+            inner.destroyed = true;
+            (
+                core::mem::take(&mut inner.vm_mappings),
+                core::mem::take(&mut inner.children),
+            )
+        };
+        for child in children {
+            child.destroy_impl(unmap);
+        }
+        if unmap {
+            for mapping in mappings.iter() {
+                if let Ok(mut cursor) = self.vm_space.cursor(mapping.start()) {
+                    cursor.clear(mapping.size());
+                }
+            }
+        }
+    }
+
+    /// Destroys `child` and gives its range back to this VMAR.
+    pub fn destroy_child(&self, child: &Arc<Vmar>) {
+        child.destroy();
+        self.inner
+            .write()
+            .children
+            .retain(|other| !Arc::ptr_eq(other, child));
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.inner.read().destroyed
+    }
+
+    fn check_alive(&self) -> Result<()> {
+        if self.is_destroyed() {
+            Err(Errno::BadState.with_message("VMAR is destroyed."))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Vmar {
+    /// Lists the mappings of this VMAR and all its children, sorted by address.
+    pub fn mappings(&self) -> Vec<MappingInfo> {
+        let inner = self.inner.read();
+        let mut mappings = inner
+            .vm_mappings
+            .iter()
+            .map(|mapping| MappingInfo {
+                base: mapping.start(),
+                size: mapping.size(),
+                perm: mapping.perm(),
+                vmo_koid: mapping.vmo().koid(),
+            })
+            .collect::<Vec<_>>();
+        for child in inner.children.iter() {
+            mappings.extend(child.mappings());
+        }
+        mappings.sort_by_key(|mapping| mapping.base);
+        mappings
+    }
 }
 
 impl Vmar {
@@ -447,25 +683,18 @@ impl Vmar {
             panic!("Lock optimized");
         }
 
+        let vm_space = Arc::new(VmSpace::new_user());
         let mut vm_mappings = Vec::new();
-        for mapping in self.inner.write().vm_mappings.iter_mut() {
-            vm_mappings.push(mapping.clone()?);
-            if mapping.perm().contains(MMUFlags::WRITE) {
-                let address = mapping.start();
-                let size = mapping.size();
-
-                let mut cursor = self.vm_space.cursor(address)?;
-                cursor.protect(size, |cprop| {
-                    cprop.flags.remove(MMUFlags::WRITE);
-                })?;
-            }
+        for mapping in self.inner.read().vm_mappings.iter() {
+            vm_mappings.push(mapping.clone(&vm_space)?);
         }
 
         Ok(new_kobj!({
-            vm_space: Arc::new(VmSpace::new_user()),
+            vm_space,
             inner: RwLock::new(VmarInner {
                 vm_mappings,
                 children: Vec::new(),
+                destroyed: false,
             }),
             lock: Mutex::new(()),
             base_addr: self.base(),
@@ -575,6 +804,129 @@ mod tests {
         child.unmap(address, 4 * 1024).unwrap();
     }
 
+    #[test]
+    fn mappings() {
+        let vmar = Vmar::new_root();
+        let child = vmar.allocate_child(2 * PAGE_SIZE).unwrap();
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        child
+            .map(PAGE_SIZE, &vmo, PageProperty::user_data(), true)
+            .unwrap();
+
+        let mapping = vmar
+            .mappings()
+            .into_iter()
+            .find(|mapping| mapping.base == child.base() + PAGE_SIZE)
+            .unwrap();
+        assert_eq!(mapping.size, PAGE_SIZE);
+        assert_eq!(mapping.vmo_koid, vmo.koid());
+
+        child.unmap(child.base() + PAGE_SIZE, PAGE_SIZE).unwrap();
+    }
+
+    #[test]
+    fn destroy_child() {
+        let vmar = Vmar::new_root();
+        let child = vmar.allocate_child(2 * PAGE_SIZE).unwrap();
+        let grandchild = child.allocate_child(PAGE_SIZE).unwrap();
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        grandchild
+            .map(0, &vmo, PageProperty::user_data(), true)
+            .unwrap();
+
+        vmar.destroy_child(&child);
+        assert!(child.is_destroyed() && grandchild.is_destroyed());
+        assert!(
+            vmar.mappings()
+                .iter()
+                .all(|mapping| mapping.vmo_koid != vmo.koid())
+        );
+        assert!(child.allocate_child(PAGE_SIZE).is_err());
+        assert!(
+            grandchild
+                .map(0, &vmo, PageProperty::user_data(), true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn deep_clone_copies_written_pages() {
+        let vmar = Vmar::new_root();
+        let vmo = Vmo::allocate_ram(4).unwrap();
+        // Far from the children other tests allocate in the shared libos root.
+        let offset = vmar.size() - 64 * PAGE_SIZE;
+        vmar.map(offset, &vmo, PageProperty::user_data(), true)
+            .unwrap();
+        let addr = vmar.base() + offset;
+        vmar.write_val(addr, &1usize).unwrap();
+        vmar.write_val(addr + PAGE_SIZE, &2usize).unwrap();
+
+        let clone = vmar.deep_clone().unwrap();
+        clone.write_val(addr, &3usize).unwrap();
+        assert_eq!(vmar.read_val::<usize>(addr).unwrap(), 1);
+        assert_eq!(clone.read_val::<usize>(addr).unwrap(), 3);
+        assert_eq!(clone.read_val::<usize>(addr + PAGE_SIZE).unwrap(), 2);
+
+        // Only the written page was copied.
+        assert_ne!(
+            clone.translate(addr).unwrap(),
+            vmar.translate(addr).unwrap()
+        );
+        assert_eq!(
+            clone.translate(addr + PAGE_SIZE).unwrap(),
+            vmar.translate(addr + PAGE_SIZE).unwrap()
+        );
+
+        vmar.unmap(addr, 4 * PAGE_SIZE).unwrap();
+    }
+
+    #[test]
+    fn map_sub_range() {
+        let vmar = Vmar::new_root();
+        let child = vmar.allocate_child(64 * PAGE_SIZE).unwrap();
+        let vmo = Vmo::allocate_ram(8).unwrap();
+        vmo.write_val(5 * PAGE_SIZE, &42usize).unwrap();
+
+        let options = MapOptions::new().align(16 * PAGE_SIZE);
+        let addr = child
+            .map_with(options, &vmo, 5 * PAGE_SIZE, 1, PageProperty::user_data())
+            .unwrap();
+        assert!(addr.is_multiple_of(16 * PAGE_SIZE));
+        assert_eq!(child.read_val::<usize>(addr).unwrap(), 42);
+        assert_eq!(
+            child.translate(addr).unwrap(),
+            vmo.physical_address(5 * PAGE_SIZE).unwrap()
+        );
+
+        // The range is taken until overwritten.
+        let offset = addr - child.base();
+        let options = MapOptions::new().specific(offset);
+        assert!(
+            child
+                .map_with(options, &vmo, 0, PAGE_SIZE, PageProperty::user_data())
+                .is_err()
+        );
+        let options = MapOptions::new().specific_overwrite(offset).map_range();
+        child
+            .map_with(options, &vmo, 0, PAGE_SIZE, PageProperty::user_data())
+            .unwrap();
+        assert_eq!(child.read_val::<usize>(addr).unwrap(), 0);
+
+        assert!(
+            child
+                .map_with(
+                    MapOptions::new(),
+                    &vmo,
+                    7 * PAGE_SIZE,
+                    2 * PAGE_SIZE,
+                    PageProperty::user_data()
+                )
+                .is_err()
+        );
+
+        vmar.destroy_child(&child);
+    }
+
     #[test]
     fn read_direct() {
         let vmar = Vmar::new_root();
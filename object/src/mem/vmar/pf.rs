@@ -9,9 +9,10 @@ impl Vmar {
             return child.handle_page_fault(vaddr, perm_required);
         }
 
-        let mut inner = self.inner.write();
+        // Held for writing so that faults on the same page do not race.
+        let inner = self.inner.write();
         let mut handled = false;
-        for mapping in inner.vm_mappings.iter_mut() {
+        for mapping in inner.vm_mappings.iter() {
             if mapping.contains(vaddr) {
                 let perm = mapping.perm();
                 if !perm.contains(perm_required) {
@@ -27,45 +28,31 @@ impl Vmar {
                 handled = true;
 
                 let mut prop = mapping.prop();
+                prop.cache_policy = mapping.vmo().cache_policy();
                 let start = mapping.start();
 
                 if mapping.vmo().is_iomem() {
                     let vmo = mapping.vmo().clone();
 
                     let (io_mem, _) = vmo.get_iomem().unwrap();
+                    let vmo_offset = mapping.vmo_offset();
+                    let len = mapping.size().min(vmo.len() - vmo_offset);
                     self.vm_space
                         .cursor(start)?
-                        .map_iomem(&io_mem, prop, 0, vmo.len())?;
-                } else if perm_required.contains(MMUFlags::WRITE)
-                    && !prop.flags.contains(MMUFlags::WRITE)
-                {
-                    log::debug!("CoW");
-                    // Perform CoW.
-                    prop.flags |= MMUFlags::WRITE;
-                    mapping.set_prop(prop);
-
-                    *mapping.vmo_mut() = mapping.vmo().deep_clone()?;
-
-                    let vmo = mapping.vmo().clone();
-                    let count = vmo.len() / PAGE_SIZE;
-
-                    for id in 0..count {
-                        if !vmo.commited(id) {
-                            continue;
-                        }
-
-                        let start = start + id * PAGE_SIZE;
-
-                        let (_, frame) = vmo.get_ram(id * PAGE_SIZE)?.unwrap();
-
-                        self.vm_space.cursor(start)?.unmap(PAGE_SIZE)?;
-                        self.vm_space.cursor(start)?.map(&frame, prop)?;
-                    }
+                        .map_iomem(&io_mem, prop, vmo_offset, len)?;
                 } else {
-                    let (_, frame) = mapping.vmo().get_ram(vaddr - start)?.unwrap();
-
                     let aligned_vaddr = align_down_by_page_size(vaddr);
+                    let (frame, private) = mapping.vmo().commit_page(
+                        mapping.vmo_offset_of(aligned_vaddr) / PAGE_SIZE,
+                        perm_required.contains(MMUFlags::WRITE),
+                    )?;
+                    // A frame shared with a snapshot is mapped read-only, and
+                    // the write fault then gives this VMO its own copy of the page.
+                    if !private {
+                        prop.flags.remove(MMUFlags::WRITE);
+                    }
 
+                    self.vm_space.cursor(aligned_vaddr)?.clear(PAGE_SIZE);
                     let mut cursor = self.vm_space.cursor(aligned_vaddr)?;
                     cursor.map(&frame, prop)?;
                 }
@@ -67,22 +67,25 @@ impl Vmar {
         while read < buffer.len() {
             let current_address = address + read;
 
-            let (mapping_start, mapping_size, vmo) = self
+            let (vmo_offset, mapped, vmo) = self
                 .inner
                 .read()
                 .vm_mappings
                 .iter()
                 .find(|mapping| mapping.contains(current_address))
-                .map(|mapping| (mapping.start(), mapping.size(), mapping.vmo().clone()))
+                .map(|mapping| {
+                    (
+                        mapping.vmo_offset_of(current_address),
+                        mapping.end() - current_address,
+                        mapping.vmo().clone(),
+                    )
+                })
                 .unwrap();
 
             let remaining = buffer.len() - read;
-            let chunk_size = mapping_size.min(remaining);
+            let chunk_size = mapped.min(remaining);
 
-            vmo.read_bytes(
-                current_address - mapping_start,
-                &mut buffer[read..read + chunk_size],
-            )?;
+            vmo.read_bytes(vmo_offset, &mut buffer[read..read + chunk_size])?;
             read += chunk_size;
         }
 
@@ -99,22 +102,25 @@ impl Vmar {
         while written < buffer.len() {
             let current_address = address + written;
 
-            let (mapping_start, mapping_size, vmo) = self
+            let (vmo_offset, mapped, vmo) = self
                 .inner
                 .read()
                 .vm_mappings
                 .iter()
                 .find(|mapping| mapping.contains(current_address))
-                .map(|mapping| (mapping.start(), mapping.size(), mapping.vmo().clone()))
+                .map(|mapping| {
+                    (
+                        mapping.vmo_offset_of(current_address),
+                        mapping.end() - current_address,
+                        mapping.vmo().clone(),
+                    )
+                })
                 .ok_or(Errno::PageFault.no_message())?;
 
             let remaining = buffer.len() - written;
-            let chunk_size = mapping_size.min(remaining);
+            let chunk_size = mapped.min(remaining);
 
-            vmo.write_bytes(
-                current_address - mapping_start,
-                &buffer[written..written + chunk_size],
-            )?;
+            vmo.write_bytes(vmo_offset, &buffer[written..written + chunk_size])?;
             written += chunk_size;
         }
 
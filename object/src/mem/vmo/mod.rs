@@ -1,135 +1,401 @@
 #![allow(dead_code)]
 
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::{Errno, Result, impl_kobj, mem::PAGE_SIZE, new_kobj, object::KObjectBase};
-use alloc::{collections::btree_map::BTreeMap, sync::Arc};
+use alloc::{
+    collections::btree_map::BTreeMap,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
 use kernel_hal::{
     io::IoMem,
-    mem::{PhysicalMemory, PhysicalMemoryAllocOptions, VirtAddr},
+    mem::{CachePolicy, PhysAddr, PhysicalMemory, PhysicalMemoryAllocOptions, VirtAddr, VmSpace},
 };
-use spin::RwLock;
+use spin::{Mutex, RwLock};
 
 mod rw;
 
 #[derive(Debug)]
 pub struct Vmo {
     inner: VmoInner,
+    /// Page ranges pinned for DMA, one per pin. Slices pin in their parent,
+    /// since only the VMO owning the frames can keep them in place.
+    pinned: Mutex<Vec<Range<usize>>>,
+    /// How many bytes of the VMO hold data, at most its length.
+    content_size: AtomicUsize,
+    cache_policy: Mutex<CachePolicy>,
+    /// Where the VMO is mapped, whose translations go stale when a frame is replaced.
+    mappings: Mutex<Vec<MappingRef>>,
+    /// Slices of the VMO, which map its frames too.
+    slices: Mutex<Vec<Weak<Vmo>>>,
     base: KObjectBase,
 }
 
 impl_kobj!(Vmo);
 
-type PhysicalMemoryRef = Arc<PhysicalMemory>;
+type FrameRef = Arc<Frame>;
+
+/// A committed frame, which may be held by several VMOs after a snapshot.
+#[derive(Debug)]
+pub(super) struct Frame {
+    memory: PhysicalMemory,
+    /// How many VMOs hold the frame. References handed out for a single access don't count.
+    shares: AtomicUsize,
+}
+
+impl Frame {
+    fn new(memory: PhysicalMemory) -> FrameRef {
+        Arc::new(Self {
+            memory,
+            shares: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl core::ops::Deref for Frame {
+    type Target = PhysicalMemory;
+
+    fn deref(&self) -> &PhysicalMemory {
+        &self.memory
+    }
+}
+
+/// A VMO's hold on a frame, counted in its shares for as long as the VMO keeps it.
+#[derive(Debug)]
+struct FrameShare(FrameRef);
+
+impl FrameShare {
+    fn new(frame: FrameRef) -> Self {
+        frame.shares.fetch_add(1, Ordering::SeqCst);
+        Self(frame)
+    }
+
+    fn is_private(&self) -> bool {
+        self.0.shares.load(Ordering::SeqCst) == 1
+    }
+}
+
+impl Drop for FrameShare {
+    fn drop(&mut self) {
+        self.0.shares.fetch_sub(1, Ordering::SeqCst);
+    }
+}
 
 #[derive(Debug)]
 enum VmoInner {
+    /// Frames shared with a snapshot stay in both VMOs until one of them writes the page.
     Ram {
-        frames: RwLock<BTreeMap<usize, PhysicalMemoryRef>>,
+        frames: RwLock<BTreeMap<usize, FrameShare>>,
         count: AtomicUsize,
+        resizable: bool,
     },
     IoMem {
         iomem: Arc<IoMem>,
         offset: usize,
     },
+    /// A window of a RAM VMO, `offset` bytes in.
+    Slice {
+        parent: Arc<Vmo>,
+        offset: usize,
+        count: usize,
+    },
+}
+
+/// An address range mapping the VMO from `vmo_offset` on.
+#[derive(Debug)]
+struct MappingRef {
+    vm_space: Weak<VmSpace>,
+    start: VirtAddr,
+    size: usize,
+    vmo_offset: usize,
+}
+
+/// How a child VMO created by [`Vmo::create_child`] relates to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmoChildKind {
+    /// Shares the pages of the window, so writes on either side are seen by both.
+    Slice,
+    /// Starts with the content of the window, sharing its pages until either side writes them.
+    Snapshot,
 }
 
 impl Vmo {
-    pub fn allocate_ram(count: usize) -> Result<Arc<Self>> {
-        Ok(new_kobj!({
-            inner: VmoInner::Ram {
-                frames: RwLock::new(BTreeMap::new()),
-                count: AtomicUsize::new(count),
-            },
-        }))
+    fn new(inner: VmoInner, content_size: usize) -> Arc<Self> {
+        let cache_policy = match &inner {
+            VmoInner::IoMem { iomem, .. } => iomem.cache_policy(),
+            _ => CachePolicy::CacheCoherent,
+        };
+        new_kobj!({
+            cache_policy: Mutex::new(cache_policy),
+            inner,
+            pinned: Mutex::new(Vec::new()),
+            content_size: AtomicUsize::new(content_size),
+            mappings: Mutex::new(Vec::new()),
+            slices: Mutex::new(Vec::new()),
+        })
     }
 
-    pub fn allocate_continuous(count: usize) -> Result<Arc<Self>> {
-        let frames = PhysicalMemoryAllocOptions::new().count(count).allocate()?;
-        let start = frames.start();
-
-        let mut frames = BTreeMap::new();
-        for id in 0..count {
-            let start_address = start + id * PAGE_SIZE;
-            let frame = PhysicalMemory::from_start_address(start_address, 1);
-            frames.insert(id, Arc::new(frame));
-        }
-        Ok(new_kobj!({
-            inner: VmoInner::Ram {
+    fn new_ram(frames: BTreeMap<usize, FrameShare>, count: usize, resizable: bool) -> Arc<Self> {
+        Self::new(
+            VmoInner::Ram {
                 frames: RwLock::new(frames),
                 count: AtomicUsize::new(count),
+                resizable,
             },
-        }))
+            count * PAGE_SIZE,
+        )
+    }
+
+    pub fn allocate_ram(count: usize) -> Result<Arc<Self>> {
+        Ok(Self::new_ram(BTreeMap::new(), count, false))
+    }
+
+    /// Allocates RAM whose size can later be changed with [`Vmo::set_size`].
+    pub fn allocate_resizable(count: usize) -> Result<Arc<Self>> {
+        Ok(Self::new_ram(BTreeMap::new(), count, true))
+    }
+
+    pub fn allocate_continuous(count: usize) -> Result<Arc<Self>> {
+        let frames = PhysicalMemoryAllocOptions::new()
+            .count(count)
+            .allocate()?
+            .into_frames()
+            .map(|frame| FrameShare::new(Frame::new(frame)))
+            .enumerate()
+            .collect();
+        Ok(Self::new_ram(frames, count, false))
     }
 
     pub fn acquire_iomem(address: VirtAddr, length: usize) -> Result<Arc<Self>> {
-        Ok(new_kobj!({
-            inner: VmoInner::IoMem {
+        Ok(Self::new(
+            VmoInner::IoMem {
                 iomem: IoMem::acquire(address..address + length)?,
                 offset: address % PAGE_SIZE,
             },
-        }))
+            length,
+        ))
     }
 
     pub fn deep_clone(&self) -> Result<Arc<Self>> {
-        match &self.inner {
-            VmoInner::Ram { frames, count } => {
-                let frames = frames.read();
-                let count = count.load(Ordering::SeqCst);
-                let mut new_frames = BTreeMap::new();
-                for (&i, source) in frames.iter() {
-                    let dest = Arc::new(PhysicalMemoryAllocOptions::new().allocate()?);
+        if self.is_iomem() {
+            return Err(Errno::AccessDenied.with_message("Attempting to deep clone IoMem."));
+        }
 
-                    let mut buffer = alloc::vec![0u8; PAGE_SIZE];
-                    source.read_bytes(0, &mut buffer)?;
-                    dest.write_bytes(0, &buffer)?;
+        let count = self.len() / PAGE_SIZE;
+        let mut new_frames = BTreeMap::new();
+        for (i, source) in self.committed_frames(0..count)? {
+            new_frames.insert(i, FrameShare::new(Frame::new(copy_frame(&source)?)));
+        }
+        Ok(Self::new_ram(new_frames, count, false))
+    }
+
+    /// Creates a child VMO over the page-aligned window `offset..offset + size`.
+    pub fn create_child(
+        self: &Arc<Self>,
+        kind: VmoChildKind,
+        offset: usize,
+        size: usize,
+    ) -> Result<Arc<Self>> {
+        if !offset.is_multiple_of(PAGE_SIZE) || !size.is_multiple_of(PAGE_SIZE) {
+            return Err(Errno::InvArg.with_message("Child window is not page aligned."));
+        }
+        if offset.checked_add(size).is_none_or(|end| end > self.len()) {
+            return Err(Errno::InvArg.with_message("Child window is out of the VMO."));
+        }
+        if self.is_iomem() {
+            return Err(Errno::NotSupported.with_message("IoMem cannot have children."));
+        }
 
-                    new_frames.insert(i, dest);
+        let (start, count) = (offset / PAGE_SIZE, size / PAGE_SIZE);
+        match kind {
+            VmoChildKind::Slice => {
+                // Slices of slices hang off the VMO owning the frames.
+                let (parent, offset) = match &self.inner {
+                    VmoInner::Slice {
+                        parent,
+                        offset: base,
+                        ..
+                    } => (parent.clone(), base + offset),
+                    _ => (self.clone(), offset),
+                };
+                if matches!(
+                    parent.inner,
+                    VmoInner::Ram {
+                        resizable: true,
+                        ..
+                    }
+                ) {
+                    return Err(Errno::NotSupported.with_message("Cannot slice a resizable VMO."));
                 }
-                Ok(new_kobj!({
-                    inner: VmoInner::Ram {
-                        frames: RwLock::new(new_frames),
-                        count: AtomicUsize::new(count),
+                let child = Self::new(
+                    VmoInner::Slice {
+                        parent: parent.clone(),
+                        offset,
+                        count,
                     },
-                }))
+                    size,
+                );
+
+                let mut slices = parent.slices.lock();
+                slices.retain(|slice| slice.strong_count() > 0);
+                slices.push(Arc::downgrade(&child));
+                Ok(child)
             }
-            VmoInner::IoMem { .. } => {
-                Err(Errno::AccessDenied.with_message("Attempting to deep clone IoMem."))
+            VmoChildKind::Snapshot => {
+                let frames = self.share_frames(start..start + count)?;
+                // Writable translations of the now shared frames must fault again.
+                match &self.inner {
+                    VmoInner::Slice {
+                        parent,
+                        offset: base,
+                        ..
+                    } => parent.invalidate(base + offset..base + offset + size),
+                    _ => self.invalidate(offset..offset + size),
+                }
+                Ok(Self::new_ram(frames, count, false))
             }
         }
     }
 }
 
 impl Vmo {
-    pub(super) fn get_ram(&self, offset: usize) -> Result<Option<(usize, PhysicalMemoryRef)>> {
-        match &self.inner {
-            VmoInner::Ram { frames, count } => {
-                let id = offset / PAGE_SIZE;
-                let page_offset = offset % PAGE_SIZE;
+    pub(super) fn get_ram(&self, offset: usize) -> Result<Option<(usize, FrameRef)>> {
+        if self.is_iomem() {
+            return Ok(None);
+        }
+        let (frame, _) = self.commit_page(offset / PAGE_SIZE, false)?;
+        Ok(Some((offset % PAGE_SIZE, frame)))
+    }
+
+    /// Like [`Vmo::get_ram`], but first gives the VMO a private copy of a shared frame.
+    pub(super) fn get_ram_mut(&self, offset: usize) -> Result<Option<(usize, FrameRef)>> {
+        if self.is_iomem() {
+            return Ok(None);
+        }
+        let (frame, _) = self.commit_page(offset / PAGE_SIZE, true)?;
+        Ok(Some((offset % PAGE_SIZE, frame)))
+    }
 
+    /// Returns the frame of page `id`, committing it if needed, and whether the
+    /// frame is private to this VMO. A shared frame is copied first on `write`.
+    pub(super) fn commit_page(&self, id: usize, write: bool) -> Result<(FrameRef, bool)> {
+        match &self.inner {
+            VmoInner::Ram { frames, count, .. } => {
                 if id >= count.load(Ordering::SeqCst) {
                     return Err(Errno::InvArg.with_message("Offset out of bounds"));
                 }
 
-                let frame = frames.read().get(&id).cloned();
-                match frame {
-                    Some(frame) => Ok(Some((page_offset, frame))),
+                if let Some(frame) = frames.read().get(&id)
+                    && (!write || frame.is_private())
+                {
+                    return Ok((frame.0.clone(), frame.is_private()));
+                }
+
+                let mut frames = frames.write();
+                let shared = match frames.get(&id) {
+                    Some(frame) if !write || frame.is_private() => {
+                        return Ok((frame.0.clone(), frame.is_private()));
+                    }
+                    shared => shared,
+                };
+
+                let frame = match shared {
+                    // The device keeps using the pinned frame, so it can't be swapped out.
+                    Some(_) if self.any_pinned(id..id + 1) => {
+                        return Err(Errno::BadState.with_message("Shared page is pinned."));
+                    }
+                    Some(source) => copy_frame(&source.0)?,
                     None => {
-                        let frame = Arc::new(PhysicalMemoryAllocOptions::new().allocate()?);
-                        frames.write().insert(id, frame.clone());
+                        let frame = PhysicalMemoryAllocOptions::new().allocate()?;
                         frame.zero()?;
-                        Ok(Some((page_offset, frame)))
+                        frame
                     }
+                };
+                let frame = Frame::new(frame);
+                if let Some(_shared) = frames.insert(id, FrameShare::new(frame.clone())) {
+                    self.invalidate(id * PAGE_SIZE..(id + 1) * PAGE_SIZE);
+                }
+                Ok((frame, true))
+            }
+            VmoInner::Slice {
+                parent,
+                offset,
+                count,
+            } => {
+                if id >= *count {
+                    return Err(Errno::InvArg.with_message("Offset out of bounds"));
                 }
+                parent.commit_page(offset / PAGE_SIZE + id, write)
+            }
+            VmoInner::IoMem { .. } => {
+                Err(Errno::NotSupported.with_message("IoMem has no backing frames."))
             }
-            VmoInner::IoMem { .. } => Ok(None),
+        }
+    }
+
+    /// Returns the committed frames of the pages in `range`, keyed from `range.start`.
+    fn committed_frames(&self, range: Range<usize>) -> Result<BTreeMap<usize, FrameRef>> {
+        match &self.inner {
+            VmoInner::Ram { frames, .. } => Ok(frames
+                .read()
+                .range(range.clone())
+                .map(|(&id, frame)| (id - range.start, frame.0.clone()))
+                .collect()),
+            VmoInner::Slice { parent, offset, .. } => {
+                let base = offset / PAGE_SIZE;
+                parent.committed_frames(base + range.start..base + range.end)
+            }
+            VmoInner::IoMem { .. } => {
+                Err(Errno::NotSupported.with_message("IoMem has no backing frames."))
+            }
+        }
+    }
+
+    /// Shares the committed frames of the pages in `range` with a new snapshot,
+    /// keyed from `range.start`. The shares are taken under the frame lock, so
+    /// that a concurrent write copies the page rather than changing the snapshot.
+    fn share_frames(&self, range: Range<usize>) -> Result<BTreeMap<usize, FrameShare>> {
+        match &self.inner {
+            VmoInner::Ram { frames, .. } => {
+                let frames = frames.read();
+                let mut shares = BTreeMap::new();
+                for (&id, frame) in frames.range(range.clone()) {
+                    // Pinned frames must stay private to the pinned VMO, so the snapshot gets copies.
+                    let frame = if self.any_pinned(id..id + 1) {
+                        Frame::new(copy_frame(&frame.0)?)
+                    } else {
+                        frame.0.clone()
+                    };
+                    shares.insert(id - range.start, FrameShare::new(frame));
+                }
+                Ok(shares)
+            }
+            VmoInner::Slice { parent, offset, .. } => {
+                let base = offset / PAGE_SIZE;
+                parent.share_frames(base + range.start..base + range.end)
+            }
+            VmoInner::IoMem { .. } => {
+                Err(Errno::NotSupported.with_message("IoMem has no backing frames."))
+            }
+        }
+    }
+
+    /// Returns the physical address behind `offset`, committing the page if needed.
+    pub fn physical_address(&self, offset: usize) -> Result<PhysAddr> {
+        match self.get_ram(offset)? {
+            Some((page_offset, frame)) => Ok(frame.start() + page_offset),
+            None => Err(Errno::NotSupported.with_message("IoMem has no backing frames.")),
         }
     }
 
     pub(super) fn get_iomem(&self) -> Option<(Arc<IoMem>, usize)> {
         match &self.inner {
-            VmoInner::Ram { .. } => None,
             VmoInner::IoMem { iomem, offset } => Some((iomem.clone(), *offset)),
+            _ => None,
         }
     }
 
@@ -137,6 +403,71 @@ impl Vmo {
         match &self.inner {
             VmoInner::Ram { frames, .. } => frames.read().contains_key(&id),
             VmoInner::IoMem { .. } => true,
+            VmoInner::Slice { parent, offset, .. } => parent.commited(offset / PAGE_SIZE + id),
+        }
+    }
+}
+
+impl Vmo {
+    /// Records that `start..start + size` of `vm_space` maps the VMO from `vmo_offset` on.
+    pub(super) fn add_mapping(
+        &self,
+        vm_space: &Arc<VmSpace>,
+        start: VirtAddr,
+        size: usize,
+        vmo_offset: usize,
+    ) {
+        let mut mappings = self.mappings.lock();
+        mappings.retain(|mapping| mapping.vm_space.strong_count() > 0);
+        mappings.push(MappingRef {
+            vm_space: Arc::downgrade(vm_space),
+            start,
+            size,
+            vmo_offset,
+        });
+    }
+
+    pub(super) fn remove_mapping(&self, vm_space: &Weak<VmSpace>, start: VirtAddr, size: usize) {
+        let mut mappings = self.mappings.lock();
+        if let Some(index) = mappings.iter().position(|mapping| {
+            Weak::ptr_eq(&mapping.vm_space, vm_space)
+                && mapping.start == start
+                && mapping.size == size
+        }) {
+            mappings.remove(index);
+        }
+    }
+
+    /// Drops the translations of the byte range `range` in every mapping of
+    /// the VMO and of its slices, so that the next access faults in the current frame.
+    fn invalidate(&self, range: Range<usize>) {
+        for mapping in self.mappings.lock().iter() {
+            let Some(vm_space) = mapping.vm_space.upgrade() else {
+                continue;
+            };
+            let start = range.start.max(mapping.vmo_offset);
+            let end = range.end.min(mapping.vmo_offset + mapping.size);
+            if start < end
+                && let Ok(mut cursor) = vm_space.cursor(mapping.start + start - mapping.vmo_offset)
+            {
+                cursor.clear(end - start);
+            }
+        }
+
+        let slices = self
+            .slices
+            .lock()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        for slice in slices {
+            if let VmoInner::Slice { offset, count, .. } = &slice.inner {
+                let start = range.start.max(*offset);
+                let end = range.end.min(offset + count * PAGE_SIZE);
+                if start < end {
+                    slice.invalidate(start - offset..end - offset);
+                }
+            }
         }
     }
 }
@@ -145,63 +476,274 @@ impl Vmo {
     /// Returns the length of the VMO in bytes.
     pub fn len(&self) -> usize {
         match &self.inner {
-            VmoInner::Ram { frames: _, count } => count.load(Ordering::Acquire) * PAGE_SIZE,
+            VmoInner::Ram { count, .. } => count.load(Ordering::Acquire) * PAGE_SIZE,
             VmoInner::IoMem { iomem, .. } => iomem.size(),
+            VmoInner::Slice { count, .. } => count * PAGE_SIZE,
         }
     }
 
     pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how many pages are backed by physical memory.
+    pub fn committed_pages(&self) -> usize {
         match &self.inner {
-            VmoInner::Ram { frames: _, count } => count.load(Ordering::Acquire) == 0,
-            VmoInner::IoMem { iomem, .. } => iomem.size() == 0,
+            VmoInner::Ram { frames, .. } => frames.read().len(),
+            VmoInner::IoMem { iomem, .. } => iomem.size().div_ceil(PAGE_SIZE),
+            VmoInner::Slice { count, .. } => self
+                .committed_frames(0..*count)
+                .map_or(0, |frames| frames.len()),
         }
     }
 
     pub fn is_iomem(&self) -> bool {
-        match &self.inner {
-            VmoInner::Ram { .. } => false,
-            VmoInner::IoMem { .. } => true,
-        }
+        matches!(self.inner, VmoInner::IoMem { .. })
     }
 
     /// # Safety
     /// The caller must ensure the ram is continunous, and it is RAM.
     pub unsafe fn start(&self) -> usize {
         match &self.inner {
-            VmoInner::Ram { frames, count: _ } => frames.read().get(&0).unwrap().start(),
+            VmoInner::Ram { frames, .. } => frames.read().get(&0).unwrap().0.start(),
+            VmoInner::Slice { parent, offset, .. } => unsafe { parent.start() + offset },
             VmoInner::IoMem { iomem: _, .. } => unreachable!(),
         }
     }
 }
 
 impl Vmo {
-    pub fn split(&self, id: usize) -> Result<Arc<Self>> {
+    /// Returns how user mappings of the VMO are cached.
+    pub fn cache_policy(&self) -> CachePolicy {
         match &self.inner {
-            VmoInner::Ram {
-                frames,
-                count: a_count,
-            } => {
+            VmoInner::Slice { parent, .. } => parent.cache_policy(),
+            _ => *self.cache_policy.lock(),
+        }
+    }
+
+    /// Sets how the VMO is cached. RAM can only change policy while it has no
+    /// committed pages and no mappings, device memory at any time.
+    pub fn set_cache_policy(&self, policy: CachePolicy) -> Result<()> {
+        match &self.inner {
+            VmoInner::Ram { .. } => {
+                if self.committed_pages() > 0 || !self.mappings.lock().is_empty() {
+                    return Err(Errno::BadState.with_message("VMO is committed or mapped."));
+                }
+                *self.cache_policy.lock() = policy;
+            }
+            VmoInner::IoMem { iomem, .. } => {
+                *self.cache_policy.lock() = policy;
+                iomem.set_cache_policy(policy)?;
+                // Existing user translations fault again with the new policy.
+                self.invalidate(0..self.len());
+            }
+            VmoInner::Slice { .. } => {
+                return Err(Errno::NotSupported.with_message("Slices share their parent's policy."));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Operations of [`Vmo::op_range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmoOp {
+    /// Commits the pages covering the range ahead of use.
+    Commit,
+    /// Gives the pages covering the range back to the frame allocator, so they read as zero.
+    Decommit,
+    /// Zeroes the range.
+    Zero,
+}
+
+impl Vmo {
+    /// Resizes a VMO from [`Vmo::allocate_resizable`] to `size` bytes, rounded
+    /// up to whole pages, and sets the content size to `size`.
+    /// Pages past the new end are freed.
+    pub fn set_size(&self, size: usize) -> Result<()> {
+        let VmoInner::Ram {
+            frames,
+            count,
+            resizable: true,
+        } = &self.inner
+        else {
+            return Err(Errno::NotSupported.with_message("VMO is not resizable."));
+        };
+        let new_count = size
+            .checked_next_multiple_of(PAGE_SIZE)
+            .ok_or(Errno::OutOfMemory.no_message())?
+            / PAGE_SIZE;
+
+        {
+            let mut frames = frames.write();
+            let old_count = count.load(Ordering::SeqCst);
+            if new_count < old_count {
+                if self.any_pinned(new_count..old_count) {
+                    return Err(Errno::BadState.with_message("Pages past the new end are pinned."));
+                }
+                let _removed = frames.split_off(&new_count);
+                self.invalidate(new_count * PAGE_SIZE..old_count * PAGE_SIZE);
+            }
+            count.store(new_count, Ordering::SeqCst);
+        }
+
+        // Stale bytes past `size` would show up again if the VMO grows.
+        self.zero_range(size..new_count * PAGE_SIZE)?;
+        self.content_size.store(size, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn content_size(&self) -> usize {
+        self.content_size.load(Ordering::SeqCst)
+    }
+
+    pub fn set_content_size(&self, size: usize) -> Result<()> {
+        if size > self.len() {
+            return Err(Errno::InvArg.with_message("Content size is past the end of the VMO."));
+        }
+        self.content_size.store(size, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Applies `op` to the byte range `offset..offset + len`.
+    pub fn op_range(&self, op: VmoOp, offset: usize, len: usize) -> Result<()> {
+        let end = offset
+            .checked_add(len)
+            .filter(|&end| end <= self.len())
+            .ok_or(Errno::InvArg.with_message("Range is out of the VMO."))?;
+        if self.is_iomem() {
+            return Err(Errno::NotSupported.with_message("IoMem has no backing frames."));
+        }
+
+        let pages = offset / PAGE_SIZE..end.div_ceil(PAGE_SIZE);
+        match op {
+            VmoOp::Commit => {
+                for id in pages {
+                    self.commit_page(id, true)?;
+                }
+                Ok(())
+            }
+            VmoOp::Decommit => self.decommit(pages),
+            VmoOp::Zero => self.zero_range(offset..end),
+        }
+    }
+
+    fn decommit(&self, pages: Range<usize>) -> Result<()> {
+        match &self.inner {
+            VmoInner::Ram { frames, .. } => {
+                if self.any_pinned(pages.clone()) {
+                    return Err(Errno::BadState.with_message("Pages are pinned."));
+                }
                 let mut frames = frames.write();
-                let count = a_count.load(Ordering::Acquire);
-                a_count.store(id, Ordering::SeqCst);
-                let new_frames = frames
-                    .split_off(&id)
-                    .into_iter()
-                    .map(|(id, f)| (id - count, f))
-                    .collect();
-
-                Ok(new_kobj!({
-                    inner: VmoInner::Ram {
-                        frames: RwLock::new(new_frames),
-                        count: AtomicUsize::new(count - id),
-                    },
-                }))
+                let mut removed = frames.split_off(&pages.start);
+                frames.append(&mut removed.split_off(&pages.end));
+                // Dropped after the translations, unshared frames go back to the allocator.
+                self.invalidate(pages.start * PAGE_SIZE..pages.end * PAGE_SIZE);
+                Ok(())
+            }
+            VmoInner::Slice { parent, offset, .. } => {
+                let base = offset / PAGE_SIZE;
+                parent.decommit(base + pages.start..base + pages.end)
+            }
+            VmoInner::IoMem { .. } => {
+                Err(Errno::NotSupported.with_message("IoMem has no backing frames."))
+            }
+        }
+    }
+
+    fn zero_range(&self, range: Range<usize>) -> Result<()> {
+        let zeros = alloc::vec![0u8; PAGE_SIZE];
+        for id in range.start / PAGE_SIZE..range.end.div_ceil(PAGE_SIZE) {
+            // Pages never committed already read as zero.
+            if !self.commited(id) {
+                continue;
+            }
+            let start = range.start.max(id * PAGE_SIZE);
+            let end = range.end.min((id + 1) * PAGE_SIZE);
+            let (page_offset, frame) = self.get_ram_mut(start)?.unwrap();
+            frame.write_bytes(page_offset, &zeros[..end - start])?;
+        }
+        Ok(())
+    }
+}
+
+impl Vmo {
+    /// Commits and pins the pages covering `offset..offset + len`,
+    /// returning the physical address of each page.
+    pub fn pin(&self, offset: usize, len: usize) -> Result<Vec<PhysAddr>> {
+        if !offset.is_multiple_of(PAGE_SIZE) || !len.is_multiple_of(PAGE_SIZE) || len == 0 {
+            return Err(Errno::InvArg.with_message("Pinned range is not page aligned."));
+        }
+        if offset.checked_add(len).is_none_or(|end| end > self.len()) {
+            return Err(Errno::InvArg.with_message("Pinned range is out of the VMO."));
+        }
+        if let VmoInner::Slice {
+            parent,
+            offset: base,
+            ..
+        } = &self.inner
+        {
+            return parent.pin(base + offset, len);
+        }
+
+        // Private frames, so that no copy-on-write moves them while pinned.
+        let pages = offset / PAGE_SIZE..(offset + len) / PAGE_SIZE;
+        let addrs = pages
+            .clone()
+            .map(|id| Ok(self.commit_page(id, true)?.0.start()))
+            .collect::<Result<_>>()?;
+        self.pinned.lock().push(pages);
+        Ok(addrs)
+    }
+
+    /// Releases a pin taken by [`Vmo::pin`] over the same range.
+    pub fn unpin(&self, offset: usize, len: usize) {
+        if let VmoInner::Slice {
+            parent,
+            offset: base,
+            ..
+        } = &self.inner
+        {
+            return parent.unpin(base + offset, len);
+        }
+
+        let pages = offset / PAGE_SIZE..(offset + len) / PAGE_SIZE;
+        let mut pinned = self.pinned.lock();
+        if let Some(index) = pinned.iter().position(|range| *range == pages) {
+            pinned.swap_remove(index);
+        }
+    }
+
+    /// Returns whether any page of the VMO is pinned for DMA.
+    pub fn is_pinned(&self) -> bool {
+        self.any_pinned(0..self.len() / PAGE_SIZE)
+    }
+
+    /// Returns whether any of the pages in `pages` is pinned.
+    fn any_pinned(&self, pages: Range<usize>) -> bool {
+        match &self.inner {
+            VmoInner::Slice { parent, offset, .. } => {
+                let base = offset / PAGE_SIZE;
+                parent.any_pinned(base + pages.start..base + pages.end)
             }
-            VmoInner::IoMem { .. } => Err(Errno::InvArg.no_message()),
+            _ => self
+                .pinned
+                .lock()
+                .iter()
+                .any(|range| range.start < pages.end && pages.start < range.end),
         }
     }
 }
 
+/// Allocates a frame holding a copy of `source`.
+fn copy_frame(source: &PhysicalMemory) -> Result<PhysicalMemory> {
+    let frame = PhysicalMemoryAllocOptions::new().allocate()?;
+    let mut buffer = alloc::vec![0u8; PAGE_SIZE];
+    source.read_bytes(0, &mut buffer)?;
+    frame.write_bytes(0, &buffer)?;
+    Ok(frame)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,10 +762,201 @@ mod tests {
     }
 
     #[test]
-    fn vmo_split() {
+    fn committed_pages() {
+        let vmo = Vmo::allocate_ram(4).unwrap();
+        assert_eq!(vmo.committed_pages(), 0);
+        vmo.write_val(PAGE_SIZE, &42usize).unwrap();
+        assert_eq!(vmo.committed_pages(), 1);
+    }
+
+    #[test]
+    fn frames_freed_on_drop() {
+        // 1 GiB in total, more than the libos physical memory.
+        for _ in 0..256 {
+            let vmo = Vmo::allocate_ram(256).unwrap();
+            for page in 0..256 {
+                vmo.write_val(page * PAGE_SIZE, &page).unwrap();
+            }
+            let clone = vmo.deep_clone().unwrap();
+            assert_eq!(clone.read_val::<usize>(PAGE_SIZE).unwrap(), 1);
+
+            let _continuous = Vmo::allocate_continuous(256).unwrap();
+        }
+    }
+
+    #[test]
+    fn resize() {
+        let vmo = Vmo::allocate_resizable(2).unwrap();
+        vmo.write_val(PAGE_SIZE + 8, &42usize).unwrap();
+        vmo.set_size(PAGE_SIZE + 8).unwrap();
+        assert_eq!(vmo.len(), 2 * PAGE_SIZE);
+        assert_eq!(vmo.content_size(), PAGE_SIZE + 8);
+
+        vmo.set_size(PAGE_SIZE).unwrap();
+        assert_eq!(vmo.committed_pages(), 0);
+        assert!(vmo.read_val::<usize>(PAGE_SIZE).is_err());
+
+        vmo.set_size(3 * PAGE_SIZE).unwrap();
+        assert_eq!(vmo.read_val::<usize>(PAGE_SIZE + 8).unwrap(), 0);
+        assert!(Vmo::allocate_ram(1).unwrap().set_size(0).is_err());
+    }
+
+    #[test]
+    fn op_range() {
+        let vmo = Vmo::allocate_ram(4).unwrap();
+        vmo.op_range(VmoOp::Commit, 0, 2 * PAGE_SIZE).unwrap();
+        assert_eq!(vmo.committed_pages(), 2);
+
+        vmo.write_val(8, &42usize).unwrap();
+        vmo.write_val(PAGE_SIZE, &7usize).unwrap();
+        vmo.op_range(VmoOp::Zero, 8, 8).unwrap();
+        assert_eq!(vmo.read_val::<usize>(8).unwrap(), 0);
+        assert_eq!(vmo.read_val::<usize>(PAGE_SIZE).unwrap(), 7);
+
+        vmo.op_range(VmoOp::Decommit, PAGE_SIZE, PAGE_SIZE).unwrap();
+        assert_eq!(vmo.committed_pages(), 1);
+        assert_eq!(vmo.read_val::<usize>(PAGE_SIZE).unwrap(), 0);
+        assert!(vmo.op_range(VmoOp::Zero, PAGE_SIZE, 4 * PAGE_SIZE).is_err());
+    }
+
+    #[test]
+    fn cache_policy() {
+        let iomem = Vmo::acquire_iomem(0x1000000, 4096).unwrap();
+        assert_eq!(iomem.cache_policy(), CachePolicy::StronglyOrderedUnCached);
+        iomem
+            .set_cache_policy(CachePolicy::WeaklyOrderedUnCached)
+            .unwrap();
+        assert_eq!(iomem.cache_policy(), CachePolicy::WeaklyOrderedUnCached);
+
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        assert_eq!(vmo.cache_policy(), CachePolicy::CacheCoherent);
+        vmo.set_cache_policy(CachePolicy::StronglyOrderedUnCached)
+            .unwrap();
+        vmo.write_val(0, &42usize).unwrap();
+        assert!(vmo.set_cache_policy(CachePolicy::CacheCoherent).is_err());
+    }
+
+    #[test]
+    fn slice_shares_pages() {
         let vmo = Vmo::allocate_ram(10).unwrap();
-        let vmo1 = vmo.split(5).unwrap();
-        assert_eq!(vmo.len(), 5 * PAGE_SIZE);
-        assert_eq!(vmo1.len(), 5 * PAGE_SIZE);
+        let slice = vmo
+            .create_child(VmoChildKind::Slice, 5 * PAGE_SIZE, 4 * PAGE_SIZE)
+            .unwrap();
+        assert_eq!(vmo.len(), 10 * PAGE_SIZE);
+        assert_eq!(slice.len(), 4 * PAGE_SIZE);
+
+        slice.write_val(0, &42usize).unwrap();
+        assert_eq!(vmo.read_val::<usize>(5 * PAGE_SIZE).unwrap(), 42);
+        vmo.write_val(6 * PAGE_SIZE, &7usize).unwrap();
+        assert_eq!(slice.read_val::<usize>(PAGE_SIZE).unwrap(), 7);
+
+        let inner = slice
+            .create_child(VmoChildKind::Slice, PAGE_SIZE, PAGE_SIZE)
+            .unwrap();
+        assert_eq!(inner.read_val::<usize>(0).unwrap(), 7);
+        assert_eq!(inner.committed_pages(), 1);
+        assert!(
+            slice
+                .create_child(VmoChildKind::Slice, 2 * PAGE_SIZE, 4 * PAGE_SIZE)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn snapshot_copies_on_write() {
+        let vmo = Vmo::allocate_ram(4).unwrap();
+        vmo.write_val(0, &1usize).unwrap();
+        vmo.write_val(PAGE_SIZE, &2usize).unwrap();
+
+        let snapshot = vmo
+            .create_child(VmoChildKind::Snapshot, 0, 4 * PAGE_SIZE)
+            .unwrap();
+        assert_eq!(snapshot.committed_pages(), 2);
+        assert_eq!(
+            snapshot.physical_address(0).unwrap(),
+            vmo.physical_address(0).unwrap()
+        );
+
+        snapshot.write_val(0, &3usize).unwrap();
+        vmo.write_val(PAGE_SIZE, &4usize).unwrap();
+        assert_eq!(vmo.read_val::<usize>(0).unwrap(), 1);
+        assert_eq!(snapshot.read_val::<usize>(0).unwrap(), 3);
+        assert_eq!(vmo.read_val::<usize>(PAGE_SIZE).unwrap(), 4);
+        assert_eq!(snapshot.read_val::<usize>(PAGE_SIZE).unwrap(), 2);
+        assert_ne!(
+            snapshot.physical_address(0).unwrap(),
+            vmo.physical_address(0).unwrap()
+        );
+    }
+
+    #[test]
+    fn pin_by_page() {
+        let vmo = Vmo::allocate_ram(4).unwrap();
+        vmo.pin(0, PAGE_SIZE).unwrap();
+        assert!(vmo.op_range(VmoOp::Decommit, 0, 2 * PAGE_SIZE).is_err());
+        vmo.op_range(VmoOp::Decommit, PAGE_SIZE, PAGE_SIZE).unwrap();
+
+        let slice = vmo
+            .create_child(VmoChildKind::Slice, 2 * PAGE_SIZE, 2 * PAGE_SIZE)
+            .unwrap();
+        let addrs = slice.pin(PAGE_SIZE, PAGE_SIZE).unwrap();
+        assert!(
+            vmo.op_range(VmoOp::Decommit, 3 * PAGE_SIZE, PAGE_SIZE)
+                .is_err()
+        );
+        assert_eq!(vmo.physical_address(3 * PAGE_SIZE).unwrap(), addrs[0]);
+
+        slice.unpin(PAGE_SIZE, PAGE_SIZE);
+        vmo.unpin(0, PAGE_SIZE);
+        assert!(!vmo.is_pinned());
+        vmo.op_range(VmoOp::Decommit, 0, 4 * PAGE_SIZE).unwrap();
+    }
+
+    #[test]
+    fn pinned_page_stays_on_write() {
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        vmo.write_val(0, &1usize).unwrap();
+        let addrs = vmo.pin(0, PAGE_SIZE).unwrap();
+
+        let snapshot = vmo
+            .create_child(VmoChildKind::Snapshot, 0, PAGE_SIZE)
+            .unwrap();
+        vmo.write_val(0, &2usize).unwrap();
+        assert_eq!(vmo.physical_address(0).unwrap(), addrs[0]);
+        assert_eq!(snapshot.read_val::<usize>(0).unwrap(), 1);
+    }
+
+    #[test]
+    fn held_frame_stays_private() {
+        let vmo = Vmo::allocate_ram(1).unwrap();
+        vmo.write_val(0, &1usize).unwrap();
+        let addrs = vmo.pin(0, PAGE_SIZE).unwrap();
+
+        // A reference taken for an access doesn't make the frame shared.
+        let (_, held) = vmo.get_ram(0).unwrap().unwrap();
+        vmo.write_val(0, &2usize).unwrap();
+        assert_eq!(vmo.physical_address(0).unwrap(), addrs[0]);
+        assert_eq!(held.start(), addrs[0]);
+        assert!(vmo.commit_page(0, true).unwrap().1);
+    }
+
+    #[test]
+    fn dropped_snapshot_unshares() {
+        let vmo = Vmo::allocate_ram(2).unwrap();
+        vmo.write_val(0, &1usize).unwrap();
+        let address = vmo.physical_address(0).unwrap();
+
+        let snapshot = vmo
+            .create_child(VmoChildKind::Snapshot, 0, 2 * PAGE_SIZE)
+            .unwrap();
+        let (_, held) = snapshot.get_ram(0).unwrap().unwrap();
+        assert!(!vmo.commit_page(0, false).unwrap().1);
+
+        // Only the snapshot's share counts, not the reference still held.
+        drop(snapshot);
+        assert!(vmo.commit_page(0, false).unwrap().1);
+        vmo.write_val(0, &2usize).unwrap();
+        assert_eq!(vmo.physical_address(0).unwrap(), address);
+        assert_eq!(held.start(), address);
     }
 }
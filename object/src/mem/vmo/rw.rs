@@ -41,7 +41,7 @@ impl Vmo {
             while written < buffer.len() {
                 let current_offset = offset + written;
 
-                let (page_offset, frame) = self.get_ram(current_offset)?.unwrap();
+                let (page_offset, frame) = self.get_ram_mut(current_offset)?.unwrap();
                 let remaining = buffer.len() - written;
                 let chunk_size = (PAGE_SIZE - page_offset).min(remaining);
 
@@ -1,4 +1,7 @@
-use crate::object::{KObject, Rights};
+use crate::{
+    Errno, Result,
+    object::{KObject, Rights},
+};
 
 #[derive(Clone)]
 pub struct Handle {
@@ -13,6 +16,21 @@ impl Handle {
             rights,
         }
     }
+
+    /// Returns a handle to the same object with `rights`, which must be a
+    /// subset of this handle's rights. `SAME_RIGHTS` keeps them unchanged.
+    pub fn reduced(&self, rights: Rights) -> Result<Self> {
+        if rights == Rights::SAME_RIGHTS {
+            return Ok(self.clone());
+        }
+        if !Rights::ALL.contains(rights) {
+            return Err(Errno::InvArg.with_message("Unknown rights."));
+        }
+        if !self.rights.contains(rights) {
+            return Err(Errno::InvArg.with_message("Can't add rights to a handle!"));
+        }
+        Ok(Handle::new(self.object.clone(), rights))
+    }
 }
 
 #[cfg(test)]
@@ -36,4 +54,20 @@ mod tests {
         let obj = DummyObject::new();
         let _handle = Handle::new(obj, Rights::BASIC);
     }
+
+    #[test]
+    fn reduced() {
+        let handle = Handle::new(DummyObject::new(), Rights::BASIC);
+        assert_eq!(handle.reduced(Rights::READ).unwrap().rights, Rights::READ);
+        assert_eq!(
+            handle.reduced(Rights::SAME_RIGHTS).unwrap().rights,
+            Rights::BASIC
+        );
+        assert!(handle.reduced(Rights::MANAGE).is_err());
+
+        let handle = Handle::new(DummyObject::new(), Rights::ALL);
+        assert!(!handle.rights.contains(Rights::SAME_RIGHTS));
+        assert!(handle.reduced(Rights::from_bits_retain(1 << 20)).is_err());
+        assert!(handle.reduced(Rights::READ | Rights::SAME_RIGHTS).is_err());
+    }
 }
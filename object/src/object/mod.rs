@@ -1,7 +1,9 @@
 pub use handle::*;
 pub use obj::*;
 pub use rights::*;
+pub use signal::*;
 
 mod handle;
 mod obj;
 mod rights;
+mod signal;
@@ -1,19 +1,62 @@
-use alloc::{string::String, sync::Arc};
+use core::{
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use downcast_rs::{DowncastSync, impl_downcast};
 use spin::Mutex;
 
-use crate::{Errno, Result};
+use crate::{Errno, Result, object::Signal};
 
 pub type KObject = Arc<dyn KernelObject>;
 
+/// A kernel object ID, unique for the lifetime of the system. 0 is never used.
+pub type Koid = u64;
+
+/// Called once with the signal state when the object asserts any of the
+/// watched signals. It runs without the object locked, so it may signal
+/// other objects.
+pub type SignalCallback = Box<dyn FnOnce(Signal) + Send>;
+
+/// Identifies a callback added by `add_signal_callback`, for removing it.
+pub type SignalCallbackId = u64;
+
 pub trait KernelObject: DowncastSync + Sync + Send {
+    fn koid(&self) -> Koid;
     fn type_name(&self) -> &str;
     fn name(&self) -> String;
     fn set_name(&self, name: String);
 
+    fn signal(&self) -> Signal;
+    fn signal_set(&self, signal: Signal);
+    fn signal_clear(&self, signal: Signal);
+    fn signal_change(&self, clear: Signal, set: Signal);
+    fn add_signal_callback(&self, signals: Signal, callback: SignalCallback) -> SignalCallbackId;
+    fn remove_signal_callback(&self, id: SignalCallbackId);
+
+    /// Signals that user space may change with `user_signal`.
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL
+    }
+
+    fn user_signal(&self, clear: Signal, set: Signal) -> Result<()> {
+        if !self.allowed_signals().contains(clear | set) {
+            return Err(Errno::InvArg.with_message("Signal is not allowed to be changed!"));
+        }
+        self.signal_change(clear, set);
+        Ok(())
+    }
+
     fn peer(&self) -> Result<Arc<dyn KernelObject>> {
         Err(Errno::NotSupported.no_message())
     }
+
+    /// The koid of the object this one is tied to, such as a channel's peer
+    /// or a thread's process, or 0 if there is none.
+    fn related_koid(&self) -> Koid {
+        self.peer().map(|peer| peer.koid()).unwrap_or(0)
+    }
 }
 impl_downcast!(sync KernelObject);
 
@@ -27,17 +70,45 @@ impl<T: KernelObject> Upcast for T {
     }
 }
 
-#[derive(Debug, Default)]
 pub struct KObjectBase {
+    koid: Koid,
     inner: Mutex<KObjectBaseInner>,
 }
 
-#[derive(Debug, Default)]
+impl Default for KObjectBase {
+    fn default() -> Self {
+        static NEXT_KOID: AtomicU64 = AtomicU64::new(1);
+        Self {
+            koid: NEXT_KOID.fetch_add(1, Ordering::SeqCst),
+            inner: Mutex::default(),
+        }
+    }
+}
+
+#[derive(Default)]
 struct KObjectBaseInner {
     name: String,
+    signal: Signal,
+    signal_callbacks: Vec<(SignalCallbackId, Signal, SignalCallback)>,
+    next_callback_id: SignalCallbackId,
+}
+
+impl Debug for KObjectBase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let inner = self.inner.lock();
+        f.debug_struct("KObjectBase")
+            .field("koid", &self.koid)
+            .field("name", &inner.name)
+            .field("signal", &inner.signal)
+            .finish()
+    }
 }
 
 impl KObjectBase {
+    pub fn koid(&self) -> Koid {
+        self.koid
+    }
+
     pub fn name(&self) -> String {
         self.inner.lock().name.clone()
     }
@@ -47,10 +118,78 @@ impl KObjectBase {
     }
 }
 
+impl KObjectBase {
+    pub fn signal(&self) -> Signal {
+        self.inner.lock().signal
+    }
+
+    pub fn signal_set(&self, signal: Signal) {
+        self.signal_change(Signal::empty(), signal);
+    }
+
+    pub fn signal_clear(&self, signal: Signal) {
+        self.signal_change(signal, Signal::empty());
+    }
+
+    /// Clears `clear` and then sets `set`.
+    /// Callbacks are only notified if the state actually changes.
+    pub fn signal_change(&self, clear: Signal, set: Signal) {
+        let (new_signal, fired) = {
+            let mut inner = self.inner.lock();
+            let old_signal = inner.signal;
+            let new_signal = (old_signal - clear) | set;
+            if new_signal == old_signal {
+                return;
+            }
+            inner.signal = new_signal;
+            let fired = inner
+                .signal_callbacks
+                .extract_if(.., |(_, signals, _)| new_signal.intersects(*signals))
+                .collect::<Vec<_>>();
+            (new_signal, fired)
+        };
+        for (_, _, callback) in fired {
+            callback(new_signal);
+        }
+    }
+
+    /// Adds a callback for any of `signals`,
+    /// which is called right away if one is already asserted.
+    pub fn add_signal_callback(
+        &self,
+        signals: Signal,
+        callback: SignalCallback,
+    ) -> SignalCallbackId {
+        let mut inner = self.inner.lock();
+        let id = inner.next_callback_id;
+        inner.next_callback_id += 1;
+        let current = inner.signal;
+        if current.intersects(signals) {
+            drop(inner);
+            callback(current);
+        } else {
+            inner.signal_callbacks.push((id, signals, callback));
+        }
+        id
+    }
+
+    /// Removes a callback, if it is still there.
+    pub fn remove_signal_callback(&self, id: SignalCallbackId) {
+        self.inner
+            .lock()
+            .signal_callbacks
+            .retain(|(other, _, _)| *other != id);
+    }
+}
+
 #[macro_export]
 macro_rules! impl_kobj {
     ($ty: ident $( $f: tt )*) => {
         impl $crate::object::KernelObject for $ty {
+            fn koid(&self) -> $crate::object::Koid {
+                self.base.koid()
+            }
+
             fn type_name(&self) -> &str {
                 stringify!($ty)
             }
@@ -63,6 +202,38 @@ macro_rules! impl_kobj {
                 self.base.set_name(name);
             }
 
+            fn signal(&self) -> $crate::object::Signal {
+                self.base.signal()
+            }
+
+            fn signal_set(&self, signal: $crate::object::Signal) {
+                self.base.signal_set(signal);
+            }
+
+            fn signal_clear(&self, signal: $crate::object::Signal) {
+                self.base.signal_clear(signal);
+            }
+
+            fn signal_change(
+                &self,
+                clear: $crate::object::Signal,
+                set: $crate::object::Signal,
+            ) {
+                self.base.signal_change(clear, set);
+            }
+
+            fn add_signal_callback(
+                &self,
+                signals: $crate::object::Signal,
+                callback: $crate::object::SignalCallback,
+            ) -> $crate::object::SignalCallbackId {
+                self.base.add_signal_callback(signals, callback)
+            }
+
+            fn remove_signal_callback(&self, id: $crate::object::SignalCallbackId) {
+                self.base.remove_signal_callback(id);
+            }
+
             $($f)*
         }
     };
@@ -126,6 +297,93 @@ mod tests {
         assert_eq!(obj.field, 42);
     }
 
+    #[test]
+    fn signal() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        let obj = TestObject::new(42);
+        assert_eq!(obj.signal(), Signal::empty());
+
+        let called = Arc::new(AtomicUsize::new(0));
+        let counter = called.clone();
+        obj.add_signal_callback(
+            Signal::READABLE,
+            Box::new(move |signal| {
+                assert!(signal.contains(Signal::READABLE));
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        obj.signal_set(Signal::WRITABLE);
+        assert_eq!(called.load(Ordering::SeqCst), 0);
+
+        obj.signal_set(Signal::READABLE);
+        obj.signal_clear(Signal::READABLE);
+        obj.signal_set(Signal::READABLE);
+        assert_eq!(called.load(Ordering::SeqCst), 1);
+        assert_eq!(obj.signal(), Signal::READABLE | Signal::WRITABLE);
+
+        // Already asserted, so called right away.
+        let counter = called.clone();
+        obj.add_signal_callback(
+            Signal::WRITABLE,
+            Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        assert_eq!(called.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn remove_signal_callback() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        let obj = TestObject::new(42);
+        let called = Arc::new(AtomicUsize::new(0));
+        let counter = called.clone();
+        let id = obj.add_signal_callback(
+            Signal::READABLE,
+            Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        obj.remove_signal_callback(id);
+        obj.signal_set(Signal::READABLE);
+        assert_eq!(called.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn signal_callback_signals_object() {
+        let obj = TestObject::new(42);
+        let other = obj.clone();
+        // Runs unlocked, so it can signal the object it watches.
+        obj.add_signal_callback(
+            Signal::USER_0,
+            Box::new(move |_| other.signal_set(Signal::USER_1)),
+        );
+        obj.signal_set(Signal::USER_0);
+        assert_eq!(obj.signal(), Signal::USER_0 | Signal::USER_1);
+    }
+
+    #[test]
+    fn user_signal() {
+        let obj = TestObject::new(42);
+        obj.user_signal(Signal::empty(), Signal::USER_0).unwrap();
+        assert_eq!(obj.signal(), Signal::USER_0);
+        assert!(obj.user_signal(Signal::empty(), Signal::READABLE).is_err());
+        obj.user_signal(Signal::USER_0, Signal::USER_1).unwrap();
+        assert_eq!(obj.signal(), Signal::USER_1);
+    }
+
+    #[test]
+    fn koid() {
+        let obj = TestObject::new(42);
+        let other = TestObject::new(42);
+        assert_ne!(obj.koid(), 0);
+        assert_ne!(obj.koid(), other.koid());
+        assert_eq!(obj.upcast().koid(), obj.koid());
+        assert_eq!(obj.related_koid(), 0);
+    }
+
     #[test]
     fn upcast() {
         let obj = TestObject::new(42);
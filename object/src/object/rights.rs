@@ -13,15 +13,26 @@ bitflags! {
         const SIGNAL    = 1 << 7;
         const MANAGE    = 1 << 8;
 
+        /// Asks duplicate and replace to keep the rights of the source handle.
+        const SAME_RIGHTS = 1 << 31;
+
         const BASIC = Self::READ.bits() | Self::WRITE.bits() | Self::WAIT.bits();
-        const ALL = u32::MAX;
+        const ALL = Self::BASIC.bits()
+                        | Self::EXECUTE.bits()
+                        | Self::MAP.bits()
+                        | Self::DUPLICATE.bits()
+                        | Self::TRANSFER.bits()
+                        | Self::SIGNAL.bits()
+                        | Self::MANAGE.bits();
 
         const VMAR = Self::BASIC.bits()
+                        | Self::EXECUTE.bits()
                         | Self::TRANSFER.bits()
                         | Self::MANAGE.bits()
                         | Self::MAP.bits()
                         | Self::DUPLICATE.bits();
         const VMO = Self::BASIC.bits()
+                        | Self::EXECUTE.bits()
                         | Self::TRANSFER.bits()
                         | Self::MANAGE.bits()
                         | Self::MAP.bits()
@@ -30,6 +41,23 @@ bitflags! {
                         | Self::TRANSFER.bits()
                         | Self::MANAGE.bits()
                         | Self::DUPLICATE.bits();
+        const JOB = Self::BASIC.bits()
+                        | Self::TRANSFER.bits()
+                        | Self::MANAGE.bits()
+                        | Self::DUPLICATE.bits();
+        const BTI = Self::READ.bits()
+                        | Self::WRITE.bits()
+                        | Self::MAP.bits()
+                        | Self::TRANSFER.bits()
+                        | Self::DUPLICATE.bits();
+        const INTERRUPT = Self::BASIC.bits()
+                        | Self::TRANSFER.bits()
+                        | Self::DUPLICATE.bits();
+        const RESOURCE = Self::WRITE.bits()
+                        | Self::TRANSFER.bits()
+                        | Self::DUPLICATE.bits();
+        const EXCEPTION = Self::BASIC.bits()
+                        | Self::TRANSFER.bits();
         const THREAD = Self::BASIC.bits()
                         | Self::MANAGE.bits()
                         | Self::DUPLICATE.bits();
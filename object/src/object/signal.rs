@@ -0,0 +1,28 @@
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Signal: u32 {
+        const READABLE    = 1 << 0;
+        const WRITABLE    = 1 << 1;
+        const PEER_CLOSED = 1 << 2;
+        const SIGNALED    = 1 << 3;
+
+        const PEER_WRITE_DISABLED = 1 << 4;
+        const WRITE_DISABLED      = 1 << 5;
+        /// Asserted by a thread while it is stopped by a suspend token.
+        const SUSPENDED           = 1 << 6;
+        /// Asserted by a thread or process once it is dead.
+        const TERMINATED          = 1 << 7;
+
+        const USER_0      = 1 << 24;
+        const USER_1      = 1 << 25;
+        const USER_2      = 1 << 26;
+        const USER_3      = 1 << 27;
+        const USER_4      = 1 << 28;
+        const USER_5      = 1 << 29;
+        const USER_6      = 1 << 30;
+        const USER_7      = 1 << 31;
+        const USER_ALL    = 0xff << 24;
+    }
+}
@@ -3,11 +3,23 @@ pub fn init() {
 }
 
 use {
-    crate::task::Process,
+    crate::{
+        Errno, impl_kobj,
+        ipc::{Channel, MessagePacket},
+        new_kobj,
+        object::{Handle, KObjectBase, KernelObject, Koid, Rights},
+        task::{Process, Thread},
+    },
+    alloc::{
+        sync::{Arc, Weak},
+        vec,
+    },
     kernel_hal::{
         platform::trap::CpuExceptionInfo,
-        task::{PageFaultInfo, inject_user_page_fault_handler},
+        task::{PageFaultInfo, UserContext, inject_user_page_fault_handler},
     },
+    pod::{IntoBytes, derive},
+    spin::Mutex,
 };
 
 pub(super) fn exception_handler(info: &CpuExceptionInfo) -> Result<(), ()> {
@@ -32,3 +44,212 @@ pub(super) fn page_fault_handler(info: &CpuExceptionInfo) -> Result<(), ()> {
         Err(())
     }
 }
+
+/// What is sent over an exception channel, along with an [`Exception`] handle.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ExceptionReport {
+    /// An [`ExceptionKind`](kernel_hal::task::ExceptionKind) discriminant.
+    pub kind: usize,
+    pub code: usize,
+    pub badv: usize,
+    pub pc: usize,
+    pub pid: Koid,
+    pub tid: Koid,
+    /// General registers, in the order of the architecture.
+    pub regs: [usize; 32],
+}
+
+impl ExceptionReport {
+    pub fn new(thread: &Thread, info: &CpuExceptionInfo, ctx: &UserContext) -> Self {
+        Self {
+            kind: info.kind as usize,
+            code: info.code,
+            badv: info.badv,
+            pc: ctx.get_ip(),
+            pid: thread.related_koid(),
+            tid: thread.koid(),
+            regs: ctx.get_regs(),
+        }
+    }
+}
+
+/// How a handler resolved an exception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExceptionState {
+    /// Passes the exception on to the next handler.
+    #[default]
+    TryNext,
+    /// Resumes the faulting thread.
+    Resume,
+    /// Terminates the faulting process.
+    Kill,
+}
+
+/// A pending exception, handed to a handler.
+/// Closing the last handle resumes the faulting thread with its state.
+pub struct Exception {
+    report: ExceptionReport,
+    thread: Weak<Thread>,
+    state: Mutex<ExceptionState>,
+    resolved: Arc<Mutex<Option<ExceptionState>>>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Exception);
+
+impl Exception {
+    fn new(
+        thread: &Arc<Thread>,
+        report: ExceptionReport,
+        resolved: Arc<Mutex<Option<ExceptionState>>>,
+    ) -> Arc<Self> {
+        new_kobj!({
+            report,
+            thread: Arc::downgrade(thread),
+            state: Mutex::new(ExceptionState::default()),
+            resolved,
+        })
+    }
+
+    pub fn report(&self) -> &ExceptionReport {
+        &self.report
+    }
+
+    pub fn thread(&self) -> Option<Arc<Thread>> {
+        self.thread.upgrade()
+    }
+
+    pub fn set_state(&self, state: ExceptionState) {
+        *self.state.lock() = state;
+    }
+}
+
+impl Drop for Exception {
+    fn drop(&mut self) {
+        *self.resolved.lock() = Some(*self.state.lock());
+        if let Some(thread) = self.thread.upgrade() {
+            thread.wake();
+        }
+    }
+}
+
+/// The exception channel of a thread, process or job.
+#[derive(Default)]
+pub struct Exceptionate {
+    channel: Mutex<Option<Arc<Channel>>>,
+}
+
+impl Exceptionate {
+    /// Creates the channel exceptions are sent to, returning the handler's end.
+    /// Fails if a handler is still bound.
+    pub fn create_channel(&self) -> crate::Result<Arc<Channel>> {
+        let mut channel = self.channel.lock();
+        if channel
+            .as_ref()
+            .is_some_and(|channel| !channel.peer_closed())
+        {
+            return Err(Errno::BadState.with_message("Exception channel is already bound."));
+        }
+        let (kernel_end, user_end) = Channel::new();
+        *channel = Some(kernel_end);
+        Ok(user_end)
+    }
+
+    /// Sends the exception to the handler, if there is one,
+    /// returning where its resolution will be stored.
+    pub(super) fn send(
+        &self,
+        thread: &Arc<Thread>,
+        report: ExceptionReport,
+    ) -> Option<Arc<Mutex<Option<ExceptionState>>>> {
+        let channel = self.channel.lock().clone()?;
+        let resolved = Arc::new(Mutex::new(None));
+        let exception = Exception::new(thread, report, resolved.clone());
+        channel
+            .write(MessagePacket {
+                data: report.as_bytes().to_vec(),
+                handles: vec![Handle::new(exception, Rights::EXCEPTION)],
+            })
+            .ok()?;
+        Some(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::time::Duration;
+
+    use pod::{FromZeros, Pod};
+
+    use super::*;
+    use crate::task::Job;
+
+    /// Answers the next exception on `channel` with `state`, or just closes it.
+    fn handle_next(
+        channel: Arc<Channel>,
+        state: Option<ExceptionState>,
+    ) -> std::thread::JoinHandle<ExceptionReport> {
+        std::thread::spawn(move || {
+            let msg = loop {
+                match channel.read() {
+                    Ok(msg) => break msg,
+                    Err(_) => std::thread::sleep(Duration::from_millis(10)),
+                }
+            };
+            let exception = msg.handles[0]
+                .object
+                .clone()
+                .downcast_arc::<Exception>()
+                .ok()
+                .unwrap();
+            if let Some(state) = state {
+                exception.set_state(state);
+            }
+            ExceptionReport::from_bytes(&msg.data)
+        })
+    }
+
+    #[test]
+    fn resume() {
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
+        let handler = handle_next(
+            process.exceptionate().create_channel().unwrap(),
+            Some(ExceptionState::Resume),
+        );
+
+        let mut report = ExceptionReport::new_zeroed();
+        report.tid = thread.koid();
+        assert!(thread.handle_exception(report));
+        assert_eq!(handler.join().unwrap().tid, thread.koid());
+    }
+
+    #[test]
+    fn try_next() {
+        let job = Job::root().new_child().unwrap();
+        let process = Process::new(&job).unwrap();
+        let thread = process.new_thread().unwrap();
+        let thread_handler = handle_next(thread.exceptionate().create_channel().unwrap(), None);
+        let job_handler = handle_next(
+            job.exceptionate().create_channel().unwrap(),
+            Some(ExceptionState::Kill),
+        );
+
+        assert!(!thread.handle_exception(ExceptionReport::new_zeroed()));
+        thread_handler.join().unwrap();
+        job_handler.join().unwrap();
+    }
+
+    #[test]
+    fn single_handler() {
+        let process = Process::new(&Job::root()).unwrap();
+        let channel = process.exceptionate().create_channel().unwrap();
+        assert!(process.exceptionate().create_channel().is_err());
+
+        drop(channel);
+        assert!(process.exceptionate().create_channel().is_ok());
+    }
+}
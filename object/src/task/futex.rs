@@ -0,0 +1,242 @@
+use core::time::Duration;
+
+use alloc::{
+    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+    sync::{Arc, Weak},
+};
+use kernel_hal::mem::{PhysAddr, VirtAddr};
+use spin::Mutex;
+
+use crate::{Errno, Result, mem::Vmar, task::Thread};
+
+/// Waiters keyed by the physical address of the futex word,
+/// so that processes sharing a VMO share the same queue.
+static FUTEXES: Mutex<BTreeMap<PhysAddr, VecDeque<Arc<FutexWaiter>>>> = Mutex::new(BTreeMap::new());
+
+struct FutexWaiter {
+    thread: Weak<Thread>,
+    woken: Arc<Mutex<Option<()>>>,
+}
+
+impl FutexWaiter {
+    fn wake(&self) {
+        let mut woken = self.woken.lock();
+        *woken = Some(());
+        if let Some(thread) = self.thread.upgrade() {
+            thread.wake();
+        }
+    }
+}
+
+fn futex_key(vmar: &Vmar, addr: VirtAddr) -> Result<PhysAddr> {
+    if !addr.is_multiple_of(align_of::<u32>()) {
+        return Err(Errno::InvArg.with_message("Futex address is not aligned."));
+    }
+    vmar.translate(addr)
+}
+
+/// Blocks `thread` until the futex at `addr` is woken, if it still holds `expected`.
+pub fn futex_wait(
+    thread: &Arc<Thread>,
+    vmar: &Vmar,
+    addr: VirtAddr,
+    expected: u32,
+    deadline: Option<Duration>,
+) -> Result<()> {
+    let key = futex_key(vmar, addr)?;
+
+    let waiter = Arc::new(FutexWaiter {
+        thread: Arc::downgrade(thread),
+        woken: Arc::new(Mutex::new(None)),
+    });
+    {
+        // Check the value under the table lock, so that a wake can't slip in between.
+        let mut futexes = FUTEXES.lock();
+        if vmar.read_val::<u32>(addr)? != expected {
+            return Err(Errno::BadState.with_message("Futex value has changed."));
+        }
+        futexes.entry(key).or_default().push_back(waiter.clone());
+    }
+
+    let result = thread.block_until(&waiter.woken, deadline);
+    if result.is_err() {
+        remove_waiter(key, &waiter);
+        // A wake may have raced with the timeout.
+        if waiter.woken.lock().is_some() {
+            return Ok(());
+        }
+    }
+    result
+}
+
+/// Wakes up to `count` threads waiting on the futex at `addr`.
+/// Returns the number of threads woken.
+pub fn futex_wake(vmar: &Vmar, addr: VirtAddr, count: usize) -> Result<usize> {
+    let key = futex_key(vmar, addr)?;
+
+    let mut futexes = FUTEXES.lock();
+    let Some(queue) = futexes.get_mut(&key) else {
+        return Ok(0);
+    };
+    let woken = count.min(queue.len());
+    for waiter in queue.drain(..woken) {
+        waiter.wake();
+    }
+    if queue.is_empty() {
+        futexes.remove(&key);
+    }
+    Ok(woken)
+}
+
+/// Wakes up to `wake_count` waiters of the futex at `addr` and moves up to
+/// `requeue_count` of the rest to the futex at `requeue_addr`,
+/// if `addr` still holds `expected`.
+pub fn futex_requeue(
+    vmar: &Vmar,
+    addr: VirtAddr,
+    wake_count: usize,
+    expected: u32,
+    requeue_addr: VirtAddr,
+    requeue_count: usize,
+) -> Result<()> {
+    let key = futex_key(vmar, addr)?;
+    let requeue_key = futex_key(vmar, requeue_addr)?;
+    if key == requeue_key {
+        return Err(Errno::InvArg.with_message("Can't requeue to the same futex."));
+    }
+
+    let mut futexes = FUTEXES.lock();
+    if vmar.read_val::<u32>(addr)? != expected {
+        return Err(Errno::BadState.with_message("Futex value has changed."));
+    }
+    let Some(mut queue) = futexes.remove(&key) else {
+        return Ok(());
+    };
+
+    let woken = wake_count.min(queue.len());
+    for waiter in queue.drain(..woken) {
+        waiter.wake();
+    }
+    let moved = requeue_count.min(queue.len());
+    futexes
+        .entry(requeue_key)
+        .or_default()
+        .extend(queue.drain(..moved));
+    if !queue.is_empty() {
+        futexes.insert(key, queue);
+    }
+    if futexes
+        .get(&requeue_key)
+        .is_some_and(|queue| queue.is_empty())
+    {
+        futexes.remove(&requeue_key);
+    }
+    Ok(())
+}
+
+fn remove_waiter(key: PhysAddr, waiter: &Arc<FutexWaiter>) {
+    let mut futexes = FUTEXES.lock();
+    if let Some(queue) = futexes.get_mut(&key) {
+        queue.retain(|other| !Arc::ptr_eq(other, waiter));
+        if queue.is_empty() {
+            futexes.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use kernel_hal::{mem::PageProperty, timer::monotonic};
+
+    use crate::mem::Vmo;
+
+    use super::*;
+
+    fn futex_vmar() -> (Arc<Vmar>, VirtAddr) {
+        let vmar = Vmar::new_root();
+        let child = vmar.allocate_child(4 * 1024).unwrap();
+        child
+            .map(
+                0,
+                &Vmo::allocate_ram(child.page_count()).unwrap(),
+                PageProperty::user_data(),
+                false,
+            )
+            .unwrap();
+        let address = child.base();
+        (vmar, address)
+    }
+
+    #[test]
+    fn wait_wake() {
+        let (vmar, address) = futex_vmar();
+        let thread = Thread::new(Weak::new());
+
+        {
+            let vmar = vmar.clone();
+            std::thread::spawn(move || {
+                while futex_wake(&vmar, address, 1).unwrap() == 0 {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            });
+        }
+
+        futex_wait(&thread, &vmar, address, 0, None).unwrap();
+    }
+
+    #[test]
+    fn wait_mismatch() {
+        let (vmar, address) = futex_vmar();
+        let thread = Thread::new(Weak::new());
+
+        let err = futex_wait(&thread, &vmar, address, 1, None).unwrap_err();
+        assert_eq!(err.errno(), Errno::BadState);
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let (vmar, address) = futex_vmar();
+        let thread = Thread::new(Weak::new());
+
+        let deadline = monotonic() + Duration::from_millis(50);
+        let err = futex_wait(&thread, &vmar, address, 0, Some(deadline)).unwrap_err();
+        assert_eq!(err.errno(), Errno::TimedOut);
+        assert_eq!(futex_wake(&vmar, address, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn requeue() {
+        let (vmar, address) = futex_vmar();
+        let requeue_address = address + size_of::<u32>();
+
+        let threads: std::vec::Vec<_> = (0..2)
+            .map(|_| {
+                let vmar = vmar.clone();
+                std::thread::spawn(move || {
+                    let thread = Thread::new(Weak::new());
+                    futex_wait(&thread, &vmar, address, 0, None).unwrap();
+                })
+            })
+            .collect();
+
+        let queued = || {
+            FUTEXES
+                .lock()
+                .get(&vmar.translate(address).unwrap())
+                .map_or(0, |q| q.len())
+        };
+        while queued() < 2 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        futex_requeue(&vmar, address, 1, 0, requeue_address, 1).unwrap();
+        assert_eq!(futex_wake(&vmar, address, 1).unwrap(), 0);
+        assert_eq!(futex_wake(&vmar, requeue_address, 1).unwrap(), 1);
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+}
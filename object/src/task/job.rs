@@ -0,0 +1,208 @@
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use bitflags::bitflags;
+use spin::{Lazy, Mutex};
+
+use crate::{
+    Errno, Result, impl_kobj, new_kobj,
+    object::KObjectBase,
+    task::{Exceptionate, Process},
+};
+
+bitflags! {
+    /// Actions denied to the processes of a job and of all its descendants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct JobPolicy: u32 {
+        const DENY_NEW_PROCESS = 1 << 0;
+        const DENY_NEW_JOB     = 1 << 1;
+        const DENY_ACQUIRE_VMO = 1 << 2;
+    }
+}
+
+/// A group of processes and child jobs, which are killed together.
+pub struct Job {
+    parent: Option<Arc<Job>>,
+    inner: Mutex<JobInner>,
+    exceptionate: Exceptionate,
+    base: KObjectBase,
+}
+
+struct JobInner {
+    children: Vec<Weak<Job>>,
+    processes: Vec<Weak<Process>>,
+    policy: JobPolicy,
+    killed: bool,
+}
+
+impl_kobj!(Job
+    fn related_koid(&self) -> crate::object::Koid {
+        self.parent.as_ref().map(|parent| parent.koid()).unwrap_or(0)
+    }
+);
+
+impl Job {
+    /// The job every other job descends from.
+    pub fn root() -> Arc<Self> {
+        static ROOT: Lazy<Arc<Job>> = Lazy::new(|| Job::new_impl(None, JobPolicy::empty()));
+        ROOT.clone()
+    }
+
+    fn new_impl(parent: Option<Arc<Job>>, policy: JobPolicy) -> Arc<Self> {
+        new_kobj!({
+            parent,
+            inner: Mutex::new(JobInner {
+                children: Vec::new(),
+                processes: Vec::new(),
+                policy,
+                killed: false,
+            }),
+            exceptionate: Exceptionate::default(),
+        })
+    }
+
+    /// Creates a child job, which inherits this job's policy.
+    pub fn new_child(self: &Arc<Self>) -> Result<Arc<Self>> {
+        let mut inner = self.inner.lock();
+        if inner.killed {
+            return Err(Errno::BadState.with_message("Job has been killed."));
+        }
+        let child = Job::new_impl(Some(self.clone()), inner.policy);
+        inner.children.retain(|child| child.strong_count() > 0);
+        inner.children.push(Arc::downgrade(&child));
+        Ok(child)
+    }
+
+    pub(super) fn add_process(&self, process: &Arc<Process>) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.killed {
+            return Err(Errno::BadState.with_message("Job has been killed."));
+        }
+        inner.processes.retain(|process| process.strong_count() > 0);
+        inner.processes.push(Arc::downgrade(process));
+        Ok(())
+    }
+}
+
+impl Job {
+    pub fn parent(&self) -> Option<Arc<Job>> {
+        self.parent.clone()
+    }
+
+    pub fn children(&self) -> Vec<Arc<Job>> {
+        let inner = self.inner.lock();
+        inner.children.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    pub fn processes(&self) -> Vec<Arc<Process>> {
+        let inner = self.inner.lock();
+        inner.processes.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    pub fn exceptionate(&self) -> &Exceptionate {
+        &self.exceptionate
+    }
+
+    pub fn policy(&self) -> JobPolicy {
+        self.inner.lock().policy
+    }
+
+    /// Adds `policy` to this job. Policy can only be tightened,
+    /// and only on a job that has no children or processes yet.
+    pub fn set_policy(&self, policy: JobPolicy) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.children.iter().any(|child| child.strong_count() > 0)
+            || inner
+                .processes
+                .iter()
+                .any(|process| process.strong_count() > 0)
+        {
+            return Err(Errno::BadState.with_message("Job is not empty."));
+        }
+        inner.policy |= policy;
+        Ok(())
+    }
+
+    /// Fails if the job's policy denies any of `policy`.
+    pub fn check_policy(&self, policy: JobPolicy) -> Result<()> {
+        if self.policy().intersects(policy) {
+            Err(Errno::AccessDenied.with_message("Denied by job policy."))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.inner.lock().killed
+    }
+
+    /// Kills every process in this job and its descendants.
+    /// A killed job can't get new children or processes.
+    pub fn kill(&self) {
+        let (children, processes) = {
+            let mut inner = self.inner.lock();
+            inner.killed = true;
+            (
+                core::mem::take(&mut inner.children),
+                core::mem::take(&mut inner.processes),
+            )
+        };
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.kill();
+        }
+        for process in processes.iter().filter_map(Weak::upgrade) {
+            process.kill();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hierarchy() {
+        let job = Job::root().new_child().unwrap();
+        let child = job.new_child().unwrap();
+        let process = Process::new(&child).unwrap();
+
+        assert!(Arc::ptr_eq(&child.parent().unwrap(), &job));
+        assert_eq!(job.children().len(), 1);
+        assert!(Arc::ptr_eq(&child.processes()[0], &process));
+        assert!(Arc::ptr_eq(process.job(), &child));
+
+        drop(process);
+        assert!(child.processes().is_empty());
+    }
+
+    #[test]
+    fn kill_tree() {
+        let job = Job::root().new_child().unwrap();
+        let child = job.new_child().unwrap();
+        let process = Process::new(&child).unwrap();
+
+        job.kill();
+        assert!(child.is_killed());
+        assert_eq!(process.exit_status(), Some(-1));
+        assert!(Process::new(&child).is_err_and(|err| err.errno() == Errno::BadState));
+        assert!(job.new_child().is_err());
+    }
+
+    #[test]
+    fn policy() {
+        let job = Job::root().new_child().unwrap();
+        job.set_policy(JobPolicy::DENY_ACQUIRE_VMO).unwrap();
+
+        let child = job.new_child().unwrap();
+        assert_eq!(
+            child
+                .check_policy(JobPolicy::DENY_ACQUIRE_VMO)
+                .unwrap_err()
+                .errno(),
+            Errno::AccessDenied
+        );
+        assert!(child.check_policy(JobPolicy::DENY_NEW_PROCESS).is_ok());
+        assert!(job.set_policy(JobPolicy::DENY_NEW_PROCESS).is_err());
+    }
+}
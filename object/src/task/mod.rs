@@ -1,9 +1,14 @@
 use alloc::sync::{Arc, Weak};
+pub use exception::{Exception, ExceptionReport, ExceptionState, Exceptionate};
+pub use futex::*;
+pub use job::*;
 pub use process::*;
 use spin::Once;
 pub use thread::*;
 
 mod exception;
+mod futex;
+mod job;
 mod process;
 mod thread;
 
@@ -13,6 +18,8 @@ pub fn init() {
     exception::init();
 
     let idle = Thread::new(Weak::new());
-    idle.start(|| {});
+    // The idle thread fires expired timers and dispatches device interrupts
+    // while everything else is blocked.
+    idle.start(kernel_hal::task::wait_for_interrupt);
     IDLE.call_once(|| idle.clone());
 }
@@ -12,8 +12,8 @@ use crate::{
     Errno, Result, impl_kobj,
     mem::Vmar,
     new_kobj,
-    object::{Handle, KObjectBase, KernelObject, Rights},
-    task::Thread,
+    object::{Handle, KObjectBase, KernelObject, Rights, Signal},
+    task::{Exceptionate, Job, Thread},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -41,16 +41,44 @@ impl Display for ProcessId {
 pub struct Process {
     inner: Mutex<ProcessInner>,
     vmar: Arc<Vmar>,
+    job: Arc<Job>,
+    exceptionate: Exceptionate,
     base: KObjectBase,
     id: ProcessId,
 }
 
+/// The lifecycle of a process, which only moves forward.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProcessState {
+    /// Not started yet.
+    #[default]
+    Created,
+    Running,
+    /// Exited or killed, waiting for its threads to go away.
+    Dying,
+    /// Every thread is gone, `TERMINATED` is asserted.
+    Dead,
+}
+
 struct ProcessInner {
     threads: Vec<Arc<Thread>>,
     handles: BTreeMap<HandleId, Handle>,
+    state: ProcessState,
     exit_status: Option<i32>,
 }
 
+impl ProcessInner {
+    fn add_handle(&mut self, handle: Handle) -> HandleId {
+        let id = HandleId(
+            (0u32..)
+                .find(|idx| !self.handles.contains_key(&HandleId(*idx)))
+                .unwrap(),
+        );
+        self.handles.insert(id, handle);
+        id
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Pod)]
 #[repr(transparent)]
 pub struct HandleId(u32);
@@ -65,20 +93,29 @@ impl HandleId {
     }
 }
 
-impl_kobj!(Process);
+impl_kobj!(Process
+    fn related_koid(&self) -> crate::object::Koid {
+        self.job.koid()
+    }
+);
 
 impl Process {
-    pub fn new() -> Arc<Self> {
+    pub fn new(job: &Arc<Job>) -> Result<Arc<Self>> {
         let vmar = Vmar::new_root();
-        new_kobj!({
+        let process = new_kobj!({
             inner: Mutex::new(ProcessInner {
                 threads: Vec::new(),
                 handles: BTreeMap::new(),
+                state: ProcessState::default(),
                 exit_status: None,
             }),
             id: ProcessId::new(),
             vmar,
-        })
+            job: job.clone(),
+            exceptionate: Exceptionate::default(),
+        });
+        job.add_process(&process)?;
+        Ok(process)
     }
 }
 
@@ -87,11 +124,24 @@ impl Process {
         &self.vmar
     }
 
+    pub fn job(&self) -> &Arc<Job> {
+        &self.job
+    }
+
+    pub fn exceptionate(&self) -> &Exceptionate {
+        &self.exceptionate
+    }
+
+    /// The status the process exited with, set once it starts dying.
     pub fn exit_status(&self) -> Option<i32> {
         let inner = self.inner.lock();
         inner.exit_status
     }
 
+    pub fn state(&self) -> ProcessState {
+        self.inner.lock().state
+    }
+
     pub fn id(&self) -> ProcessId {
         self.id
     }
@@ -104,10 +154,11 @@ impl Process {
 }
 
 impl Process {
-    pub fn new_thread(self: &Arc<Self>) -> Arc<Thread> {
+    /// Creates a thread in the process, unless it is already exiting.
+    pub fn new_thread(self: &Arc<Self>) -> Result<Arc<Thread>> {
         let thread = Thread::new(Arc::downgrade(self));
-        self.add_thread(thread.clone());
-        thread
+        self.add_thread(thread.clone())?;
+        Ok(thread)
     }
 }
 
@@ -119,45 +170,98 @@ impl Process {
         stack: usize,
         initializer: impl FnOnce(&mut UserContext),
         syscall_handler: impl Fn(&Arc<Self>, &mut UserContext) + Send + 'static,
-    ) {
+    ) -> Result<()> {
+        {
+            let mut inner = self.inner.lock();
+            if inner.state != ProcessState::Created {
+                return Err(Errno::BadState.with_message("Process has already been started."));
+            }
+            inner.state = ProcessState::Running;
+        }
         thread.start_user(self.clone(), entry, stack, initializer, syscall_handler);
+        Ok(())
     }
 
     pub fn exit(&self, status: i32) {
         let current_thread = Thread::current().unwrap();
-        {
-            for thread in self.inner.lock().threads.clone() {
-                if thread.id() != current_thread.id() {
-                    thread.kill();
-                }
+        self.set_dying(status);
+        for thread in self.threads() {
+            if thread.id() != current_thread.id() {
+                thread.kill();
             }
-            let mut inner = self.inner.lock();
-            inner.exit_status = Some(status);
         }
         current_thread.exit();
     }
 
     pub fn kill(&self) {
-        {
-            let mut inner = self.inner.lock();
-            inner.exit_status = Some(-1);
-        }
-        for thread in self.inner.lock().threads.clone() {
+        self.set_dying(-1);
+        for thread in self.threads() {
             thread.kill();
         }
+        // A process without threads has nobody left to finish its death.
+        self.try_finish();
+    }
+
+    /// Records `status` unless the process is already on its way out.
+    fn set_dying(&self, status: i32) {
+        let mut inner = self.inner.lock();
+        if inner.state < ProcessState::Dying {
+            inner.state = ProcessState::Dying;
+            inner.exit_status = Some(status);
+        }
+    }
+
+    /// Moves a dying process without threads to `Dead`, closing its handles
+    /// and tearing its address space down.
+    fn try_finish(&self) {
+        let mut inner = self.inner.lock();
+        if inner.state != ProcessState::Dying || !inner.threads.is_empty() {
+            return;
+        }
+        inner.state = ProcessState::Dead;
+        let handles = core::mem::take(&mut inner.handles);
+        drop(inner);
+
+        // Peers of the closed handles see PEER_CLOSED from here.
+        drop(handles);
+        // Under libos, every process shares one root VMAR.
+        #[cfg(not(feature = "libos"))]
+        self.vmar.destroy();
+        self.base.signal_set(Signal::TERMINATED);
     }
 }
 
 impl Process {
     pub fn add_handle(&self, handle: Handle) -> HandleId {
         let mut inner = self.inner.lock();
-        let id = HandleId(
-            (0u32..)
-                .find(|idx| !inner.handles.contains_key(&HandleId(*idx)))
-                .unwrap(),
-        );
-        inner.handles.insert(id, handle);
-        id
+        inner.add_handle(handle)
+    }
+
+    /// Duplicates the handle `id` with `rights`, or with the same rights if
+    /// `rights` is `SAME_RIGHTS`. The handle must have `DUPLICATE`.
+    pub fn duplicate_handle(&self, id: HandleId, rights: Rights) -> Result<HandleId> {
+        let mut inner = self.inner.lock();
+        let handle = inner
+            .handles
+            .get(&id)
+            .ok_or(Errno::BadHandle.with_message("Handle not found!"))?;
+        if !handle.rights.contains(Rights::DUPLICATE) {
+            return Err(Errno::AccessDenied.with_message("Handle can't be duplicated!"));
+        }
+        let handle = handle.reduced(rights)?;
+        Ok(inner.add_handle(handle))
+    }
+
+    /// Swaps the handle `id` for a new one with `rights`, or with the same rights
+    /// if `rights` is `SAME_RIGHTS`. The old handle is closed either way.
+    pub fn replace_handle(&self, id: HandleId, rights: Rights) -> Result<HandleId> {
+        let mut inner = self.inner.lock();
+        let handle = inner
+            .handles
+            .remove(&id)
+            .ok_or(Errno::BadHandle.with_message("Handle not found!"))?;
+        let handle = handle.reduced(rights)?;
+        Ok(inner.add_handle(handle))
     }
 
     pub fn remove_handle(&self, id: HandleId) -> Result<Handle> {
@@ -194,16 +298,37 @@ impl Process {
         Ok(handle.clone())
     }
 
-    pub fn add_thread(&self, thread: Arc<Thread>) {
+    pub fn get_handle_with_rights(&self, id: HandleId, desired_rights: Rights) -> Result<Handle> {
+        let handle = self.get_handle(id)?;
+        if handle.rights.contains(desired_rights) {
+            Ok(handle)
+        } else {
+            Err(Errno::AccessDenied.with_message("Handle does not have the desired rights!"))
+        }
+    }
+
+    pub fn threads(&self) -> Vec<Arc<Thread>> {
+        self.inner.lock().threads.clone()
+    }
+
+    pub fn add_thread(&self, thread: Arc<Thread>) -> Result<()> {
         let mut inner = self.inner.lock();
+        if inner.state >= ProcessState::Dying {
+            return Err(Errno::BadState.with_message("Process is exiting."));
+        }
         inner.threads.push(thread);
+        Ok(())
     }
 
     pub fn remove_thread(&self, thread: &Arc<Thread>) {
         let mut inner = self.inner.lock();
         inner.threads.retain(|t| t.id() != thread.id());
-        if inner.threads.is_empty() {
-            inner.exit_status = Some(0);
+        let last = inner.threads.is_empty();
+        drop(inner);
+        if last {
+            // The last thread leaving on its own is a normal exit.
+            self.set_dying(0);
+            self.try_finish();
         }
     }
 }
@@ -236,7 +361,7 @@ impl Process {
 mod tests {
     use kernel_hal::{mem::PageProperty, task::ThreadState};
 
-    use crate::{mem::Vmo, object::Upcast};
+    use crate::{ipc::Channel, mem::Vmo, object::Upcast};
 
     use super::*;
 
@@ -244,22 +369,116 @@ mod tests {
 
     #[test]
     fn proc_handle() {
-        let proc = Process::new();
+        let proc = Process::new(&Job::root()).unwrap();
         let handle = proc.add_handle(Handle::new(proc.clone().upcast(), Rights::READ));
         assert_eq!(handle, HandleId(0));
         proc.remove_handle(handle).unwrap();
     }
 
     #[test]
-    fn proc_start() {
-        const STACK_SIZE: usize = 8 * 1024 * 1024;
+    fn duplicate_handle() {
+        let proc = Process::new(&Job::root()).unwrap();
+        let handle = proc.add_handle(Handle::new(
+            proc.clone().upcast(),
+            Rights::READ | Rights::WRITE | Rights::DUPLICATE,
+        ));
+
+        let read_only = proc.duplicate_handle(handle, Rights::READ).unwrap();
+        assert_eq!(proc.get_handle(read_only).unwrap().rights, Rights::READ);
+        assert!(
+            proc.duplicate_handle(read_only, Rights::SAME_RIGHTS)
+                .is_err()
+        );
+        assert!(proc.duplicate_handle(handle, Rights::MANAGE).is_err());
+
+        let same = proc.duplicate_handle(handle, Rights::SAME_RIGHTS).unwrap();
+        assert_eq!(
+            proc.get_handle(same).unwrap().rights,
+            proc.get_handle(handle).unwrap().rights
+        );
+    }
 
-        extern "C" fn user_entry(_vmo_start: VirtAddr) {
-            loop {}
+    #[test]
+    fn replace_handle() {
+        let proc = Process::new(&Job::root()).unwrap();
+        let handle = proc.add_handle(Handle::new(
+            proc.clone().upcast(),
+            Rights::READ | Rights::WRITE,
+        ));
+
+        let replaced = proc.replace_handle(handle, Rights::READ).unwrap();
+        assert_eq!(proc.get_handle(replaced).unwrap().rights, Rights::READ);
+
+        // Asking for more rights fails, and still closes the handle.
+        assert!(proc.replace_handle(replaced, Rights::WRITE).is_err());
+        assert!(proc.get_handle(replaced).is_err());
+    }
+
+    #[test]
+    fn related_koids() {
+        let job = Job::root().new_child().unwrap();
+        let proc = Process::new(&job).unwrap();
+        let thread = proc.new_thread().unwrap();
+
+        assert_eq!(proc.related_koid(), job.koid());
+        assert_eq!(thread.related_koid(), proc.koid());
+        assert_eq!(proc.threads()[0].koid(), thread.koid());
+    }
+
+    #[test]
+    fn kill_keeps_status() {
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
+        assert_eq!(process.state(), ProcessState::Created);
+
+        process.kill();
+        assert_eq!(process.state(), ProcessState::Dead);
+        assert_eq!(process.exit_status(), Some(-1));
+        assert!(process.signal().contains(Signal::TERMINATED));
+        assert!(thread.signal().contains(Signal::TERMINATED));
+
+        assert_eq!(
+            process.new_thread().err().map(|err| err.errno()),
+            Some(Errno::BadState)
+        );
+        assert!(process.threads().is_empty());
+    }
+
+    #[test]
+    fn teardown_closes_handles() {
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
+        let (channel0, channel1) = Channel::new();
+        process.add_handle(Handle::new(channel0, Rights::BASIC));
+        process.add_handle(Handle::new(process.clone().upcast(), Rights::PROCESS));
+
+        thread.kill();
+        assert!(channel1.peer_closed());
+        assert!(process.get_handle(HandleId(0)).is_err());
+    }
+
+    #[test]
+    fn last_thread_exits() {
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
+
+        thread.kill();
+        assert_eq!(process.state(), ProcessState::Dead);
+        assert_eq!(process.exit_status(), Some(0));
+    }
+
+    extern "C" fn user_entry(_vmo_start: VirtAddr) {
+        loop {
+            core::hint::spin_loop();
         }
+    }
+
+    #[test]
+    fn proc_start() {
+        const STACK_SIZE: usize = 8 * 1024 * 1024;
 
-        let process = Process::new();
-        let thread = process.new_thread();
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
 
         let stack = process.root_vmar().allocate_child(STACK_SIZE).unwrap();
         stack
@@ -271,14 +490,49 @@ mod tests {
             )
             .unwrap();
 
-        process.start(
-            thread.clone(),
-            user_entry as *const () as VirtAddr,
-            stack.end(),
-            |_| {},
-            |_, _| {},
-        );
+        process
+            .start(
+                thread.clone(),
+                user_entry as *const () as VirtAddr,
+                stack.end(),
+                |_| {},
+                |_, _| {},
+            )
+            .unwrap();
+        assert_eq!(process.state(), ProcessState::Running);
         std::thread::sleep(std::time::Duration::from_millis(100));
         thread.set_state(ThreadState::Blocked);
     }
+
+    #[test]
+    fn killed_process_is_released() {
+        const STACK_SIZE: usize = 8 * 1024 * 1024;
+        let process = Process::new(&Job::root()).unwrap();
+        let thread = process.new_thread().unwrap();
+        let stack = process.root_vmar().allocate_child(STACK_SIZE).unwrap();
+        stack
+            .map(
+                0,
+                &Vmo::allocate_ram(stack.page_count()).unwrap(),
+                PageProperty::user_data(),
+                false,
+            )
+            .unwrap();
+        process
+            .start(
+                thread.clone(),
+                user_entry as *const () as VirtAddr,
+                stack.end(),
+                |_| {},
+                |_, _| {},
+            )
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        // The thread never gets back from user space, yet it must not keep the process.
+        process.kill();
+        let weak = Arc::downgrade(&process);
+        drop((process, thread, stack));
+        assert!(weak.upgrade().is_none());
+    }
 }
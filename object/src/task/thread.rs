@@ -1,19 +1,30 @@
 use core::{
+    cell::Cell,
     fmt::Display,
     sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
 };
 
-use alloc::sync::{Arc, Weak};
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
 use kernel_hal::{
     mem::{PageProperty, VirtAddr},
     task::{HwThread, ReturnReason, ThreadState, UserContext},
+    timer::{monotonic, timer_cancel, timer_set},
 };
+use spin::Mutex;
 
 use crate::{
-    impl_kobj,
+    Errno, Result, impl_kobj,
     mem::{Vmar, Vmo},
-    object::KObjectBase,
-    task::{Process, exception::exception_handler},
+    new_kobj,
+    object::{KObject, KObjectBase, KernelObject, Signal},
+    task::{
+        ExceptionReport, ExceptionState, Exceptionate, Job, Process, exception::exception_handler,
+    },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -41,33 +52,95 @@ impl Display for ThreadId {
 pub struct Thread {
     process: Weak<Process>,
     tid: ThreadId,
+    exceptionate: Exceptionate,
+    stop: Mutex<ThreadStop>,
+    kernel_stack: Option<KernelStack>,
     base: KObjectBase,
     ctx: Arc<HwThread>,
 }
 
-impl_kobj!(Thread);
+/// A kernel stack, mapped directly in the kernel VMAR and unmapped on drop.
+struct KernelStack {
+    vmar: Arc<Vmar>,
+    _vmo: Arc<Vmo>,
+}
+
+impl Drop for KernelStack {
+    fn drop(&mut self) {
+        self.vmar.direct_unmap(0, self.vmar.size()).unwrap();
+        Vmar::kernel().destroy_child(&self.vmar);
+    }
+}
+
+/// Kernel stacks of dropped threads. A stack is still in use until the thread
+/// has unwound and switched away for the last time, which drops its context.
+static DEAD_KERNEL_STACKS: Mutex<Vec<(KernelStack, Weak<HwThread>)>> = Mutex::new(Vec::new());
+
+#[derive(Default)]
+struct ThreadStop {
+    suspend_count: usize,
+    /// Filled when the last suspend token is closed.
+    resumed: Option<Arc<Mutex<Option<()>>>>,
+    /// The user context while the thread is stopped in the kernel,
+    /// where debuggers can reach it.
+    user_ctx: Option<UserContext>,
+}
+
+impl_kobj!(Thread
+    fn related_koid(&self) -> crate::object::Koid {
+        self.process().map(|process| process.koid()).unwrap_or(0)
+    }
+);
 
 impl Thread {
     pub fn new(process: Weak<Process>) -> Arc<Self> {
         static KERNEL_STACK_SIZE: usize = 32 * 1024;
 
-        Arc::new_cyclic(|this: &Weak<Self>| Self {
-            process,
-            tid: ThreadId::new(),
-            base: KObjectBase::default(),
-            ctx: Arc::new(HwThread::new(this.clone(), || {
+        DEAD_KERNEL_STACKS
+            .lock()
+            .retain(|(_, ctx)| ctx.strong_count() > 0);
+
+        Arc::new_cyclic(|this: &Weak<Self>| {
+            let kernel_stack = Cell::new(None);
+            let ctx = Arc::new(HwThread::new(this.clone(), || {
                 let vmar = Vmar::kernel();
                 let stack = vmar.allocate_child(KERNEL_STACK_SIZE).unwrap();
                 let vmo = Vmo::allocate_ram(stack.page_count()).unwrap();
+                vmo.set_name("kernel-stack".into());
                 stack
                     .direct_map(0, &vmo, PageProperty::kernel_data())
                     .unwrap();
-                stack.end()
-            })),
+                let end = stack.end();
+                kernel_stack.set(Some(KernelStack {
+                    vmar: stack,
+                    _vmo: vmo,
+                }));
+                end
+            }));
+            Self {
+                process,
+                tid: ThreadId::new(),
+                exceptionate: Exceptionate::default(),
+                stop: Mutex::default(),
+                kernel_stack: kernel_stack.take(),
+                base: KObjectBase::default(),
+                ctx,
+            }
         })
     }
 }
 
+impl Drop for Thread {
+    fn drop(&mut self) {
+        // The last reference may be dropped on the thread's own stack.
+        if let Some(stack) = self.kernel_stack.take() {
+            DEAD_KERNEL_STACKS
+                .lock()
+                .push((stack, Arc::downgrade(&self.ctx)));
+        }
+    }
+}
+
 impl Thread {
     pub fn id(&self) -> ThreadId {
         self.tid
@@ -88,6 +161,10 @@ impl Thread {
     pub fn process(&self) -> Option<Arc<Process>> {
         self.process.upgrade()
     }
+
+    pub fn exceptionate(&self) -> &Exceptionate {
+        &self.exceptionate
+    }
 }
 
 impl Thread {
@@ -117,9 +194,18 @@ impl Thread {
 
         initializer(&mut user_ctx);
 
+        // Held weakly, so that a thread left on its way out doesn't keep the process alive.
+        let process = Arc::downgrade(&process);
         self.start(move || {
-            process.root_vmar().activate();
+            match process.upgrade() {
+                Some(process) => process.root_vmar().activate(),
+                None => return,
+            }
             let reason = user_ctx.enter_user_space();
+            let Some(process) = process.upgrade() else {
+                return;
+            };
+            let thread = Thread::current().unwrap();
             match reason {
                 ReturnReason::KernelEvent => {}
                 ReturnReason::Syscall => {
@@ -127,33 +213,253 @@ impl Thread {
                 }
                 ReturnReason::Exception(info) => {
                     if exception_handler(&info).is_err() {
+                        let report = ExceptionReport::new(&thread, &info, &user_ctx);
+                        if thread
+                            .park_user_context(&mut user_ctx, || thread.handle_exception(report))
+                        {
+                            return;
+                        }
                         log::error!("Unhandled exception, info: {:#x?}", info);
                         log::error!("Trap Frame: {:#x?}", user_ctx.as_trap_frame());
-                        log::error!("Process Id: {:?}", process.id());
+                        log::error!(
+                            "Process: {} {:?}, thread: {:?}",
+                            process.id(),
+                            process.name(),
+                            thread.name(),
+                        );
                         process.exit(-1);
-                        kernel_hal::platform::idle_loop();
+                        return;
                     }
                 }
             }
+            thread.stop_if_suspended(&mut user_ctx);
         });
     }
 
+    /// Kills the current thread, which leaves once its updater returns.
     pub fn exit(self: &Arc<Self>) {
-        self.set_state(ThreadState::Dead);
-        if let Some(process) = self.process() {
-            process.remove_thread(self);
-        }
+        self.kill();
         self.context().exit();
     }
 
     pub fn kill(self: &Arc<Self>) {
         self.set_state(ThreadState::Dead);
+        self.base.signal_set(Signal::TERMINATED);
         if let Some(process) = self.process() {
             process.remove_thread(self);
         }
     }
 }
 
+impl Thread {
+    /// Stops the thread the next time it leaves user space, until the returned
+    /// token is dropped. Suspending an already suspended thread stacks.
+    pub fn suspend(self: &Arc<Self>) -> Arc<SuspendToken> {
+        let mut stop = self.stop.lock();
+        stop.suspend_count += 1;
+        if stop.suspend_count == 1 {
+            stop.resumed = Some(Arc::new(Mutex::new(None)));
+        }
+        drop(stop);
+        SuspendToken::new(self.clone())
+    }
+
+    fn resume(&self) {
+        let mut stop = self.stop.lock();
+        stop.suspend_count -= 1;
+        if stop.suspend_count == 0 {
+            if let Some(resumed) = stop.resumed.take() {
+                *resumed.lock() = Some(());
+            }
+            drop(stop);
+            self.wake();
+        }
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.stop.lock().suspend_count > 0
+    }
+
+    fn stop_if_suspended(self: &Arc<Self>, user_ctx: &mut UserContext) {
+        let Some(resumed) = self.stop.lock().resumed.clone() else {
+            return;
+        };
+        self.park_user_context(user_ctx, || {
+            self.base.signal_set(Signal::SUSPENDED);
+            let _ = self.block_until(&resumed, None);
+            self.base.signal_clear(Signal::SUSPENDED);
+        });
+    }
+
+    /// Runs `f` with the user context parked in the thread,
+    /// so that it can be read and written while the thread is stopped.
+    fn park_user_context<R>(&self, user_ctx: &mut UserContext, f: impl FnOnce() -> R) -> R {
+        self.stop.lock().user_ctx = Some(core::mem::take(user_ctx));
+        let result = f();
+        *user_ctx = self.stop.lock().user_ctx.take().unwrap();
+        result
+    }
+
+    /// Runs `f` on the user context of the thread, which must be stopped
+    /// by a suspend token or an exception.
+    pub fn with_user_context<R>(&self, f: impl FnOnce(&mut UserContext) -> R) -> Result<R> {
+        let mut stop = self.stop.lock();
+        let user_ctx = stop
+            .user_ctx
+            .as_mut()
+            .ok_or(Errno::BadState.with_message("Thread is not stopped."))?;
+        Ok(f(user_ctx))
+    }
+
+    /// Suspends the thread and offers the exception to the handlers of the thread,
+    /// its process and its jobs from the innermost out, until one resolves it.
+    /// Returns whether the thread may resume.
+    pub fn handle_exception(self: &Arc<Self>, report: ExceptionReport) -> bool {
+        let process = self.process();
+        let jobs = process
+            .iter()
+            .flat_map(|process| {
+                core::iter::successors(Some(process.job().clone()), |job| job.parent())
+            })
+            .collect::<Vec<Arc<Job>>>();
+        let exceptionates = core::iter::once(&self.exceptionate)
+            .chain(process.iter().map(|process| process.exceptionate()))
+            .chain(jobs.iter().map(|job| job.exceptionate()));
+
+        for exceptionate in exceptionates {
+            let Some(resolved) = exceptionate.send(self, report) else {
+                continue;
+            };
+            match self.block_until(&resolved, None) {
+                Ok(ExceptionState::TryNext) => continue,
+                Ok(state) => return state == ExceptionState::Resume,
+                // Killed while suspended.
+                Err(_) => return false,
+            }
+        }
+        false
+    }
+}
+
+impl Thread {
+    /// Makes a blocked thread ready again.
+    pub fn wake(&self) {
+        // Checked and set at once, so that a thread killed meanwhile stays dead.
+        self.ctx
+            .compare_set_state(ThreadState::Blocked, ThreadState::Ready);
+    }
+
+    /// Blocks the current thread until `object` asserts any of `signal`
+    /// or `deadline` passes, in which case `TimedOut` is returned.
+    /// Returns the signal state observed when the wait was satisfied.
+    pub fn wait_signal(
+        self: &Arc<Self>,
+        object: &KObject,
+        signal: Signal,
+        deadline: Option<Duration>,
+    ) -> Result<Signal> {
+        // A poll is answered from the current state, leaving nothing behind.
+        if deadline.is_some_and(|deadline| deadline <= monotonic()) {
+            let current = object.signal();
+            return if current.intersects(signal) {
+                Ok(current)
+            } else {
+                Err(Errno::TimedOut.no_message())
+            };
+        }
+
+        let observed = Arc::new(Mutex::new(None));
+        let callback = {
+            let observed = observed.clone();
+            let thread = Arc::downgrade(self);
+            object.add_signal_callback(
+                signal,
+                Box::new(move |current| {
+                    *observed.lock() = Some(current);
+                    if let Some(thread) = thread.upgrade() {
+                        thread.wake();
+                    }
+                }),
+            )
+        };
+
+        let result = self.block_until(&observed, deadline);
+        // Timed out or killed, the callback must not outlive the wait.
+        object.remove_signal_callback(callback);
+        result
+    }
+
+    /// Blocks the current thread until `deadline` passes.
+    pub fn sleep_until(self: &Arc<Self>, deadline: Duration) -> Result<()> {
+        let observed = Arc::new(Mutex::new(None::<()>));
+        match self.block_until(&observed, Some(deadline)) {
+            Err(err) if err.errno() == Errno::TimedOut => Ok(()),
+            result => result,
+        }
+    }
+
+    pub(crate) fn block_until<T: Copy + Send + 'static>(
+        self: &Arc<Self>,
+        observed: &Arc<Mutex<Option<T>>>,
+        deadline: Option<Duration>,
+    ) -> Result<T> {
+        let timer = deadline
+            .filter(|&deadline| deadline > monotonic())
+            .map(|deadline| {
+                let observed = observed.clone();
+                let thread = Arc::downgrade(self);
+                timer_set(deadline, move |_| {
+                    let _observed = observed.lock();
+                    if let Some(thread) = thread.upgrade() {
+                        thread.wake();
+                    }
+                })
+            });
+
+        let result = loop {
+            // Keep it locked until blocked, so that the wake up can't be missed.
+            let observed = observed.lock();
+            if let Some(value) = *observed {
+                break Ok(value);
+            }
+            if self.state().dead() {
+                break Err(Errno::Canceled.with_message("Thread is killed while waiting."));
+            }
+            if deadline.is_some_and(|deadline| monotonic() >= deadline) {
+                break Err(Errno::TimedOut.no_message());
+            }
+            self.set_state(ThreadState::Blocked);
+            drop(observed);
+            self.ctx.yield_now();
+        };
+
+        if let Some(timer) = timer {
+            timer_cancel(timer);
+        }
+        result
+    }
+}
+
+/// Keeps a thread suspended until closed.
+pub struct SuspendToken {
+    thread: Arc<Thread>,
+    base: KObjectBase,
+}
+
+impl_kobj!(SuspendToken);
+
+impl SuspendToken {
+    fn new(thread: Arc<Thread>) -> Arc<Self> {
+        new_kobj!({ thread })
+    }
+}
+
+impl Drop for SuspendToken {
+    fn drop(&mut self) {
+        self.thread.resume();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -171,6 +477,18 @@ mod tests {
         assert_eq!(thread.state(), ThreadState::Ready);
     }
 
+    #[test]
+    fn wake() {
+        let thread = Thread::new(Weak::new());
+        thread.set_state(ThreadState::Blocked);
+        thread.wake();
+        assert_eq!(thread.state(), ThreadState::Ready);
+
+        thread.kill();
+        thread.wake();
+        assert_eq!(thread.state(), ThreadState::Dead);
+    }
+
     #[test]
     fn start_thread() {
         let thread = Thread::new(Weak::new());
@@ -181,6 +499,99 @@ mod tests {
         thread.set_state(ThreadState::Blocked);
     }
 
+    #[test]
+    fn wait_signal() {
+        use crate::{ipc::Channel, ipc::MessagePacket, object::Upcast};
+
+        let thread = Thread::new(Weak::new());
+        let (channel0, channel1) = Channel::new();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            channel1.write(MessagePacket::default()).unwrap();
+        });
+
+        let observed = thread
+            .wait_signal(&channel0.upcast(), Signal::READABLE, None)
+            .unwrap();
+        assert!(observed.contains(Signal::READABLE));
+        assert_eq!(thread.state(), ThreadState::Ready);
+    }
+
+    #[test]
+    fn wait_signal_timeout() {
+        use crate::{ipc::Channel, ipc::MessagePacket, object::Upcast};
+
+        let thread = Thread::new(Weak::new());
+        let (channel0, channel1) = Channel::new();
+
+        let deadline = monotonic() + Duration::from_millis(50);
+        let result = thread.wait_signal(&channel0.upcast(), Signal::READABLE, Some(deadline));
+        assert_eq!(result.unwrap_err().errno(), Errno::TimedOut);
+        assert!(monotonic() >= deadline);
+
+        // The abandoned wait no longer wakes the thread.
+        thread.set_state(ThreadState::Blocked);
+        channel1.write(MessagePacket::default()).unwrap();
+        assert_eq!(thread.state(), ThreadState::Blocked);
+    }
+
+    #[test]
+    fn wait_signal_poll() {
+        use crate::{ipc::Channel, ipc::MessagePacket, object::Upcast};
+
+        let thread = Thread::new(Weak::new());
+        let (channel0, channel1) = Channel::new();
+
+        let result = thread.wait_signal(&channel0.upcast(), Signal::READABLE, Some(Duration::ZERO));
+        assert_eq!(result.unwrap_err().errno(), Errno::TimedOut);
+
+        channel1.write(MessagePacket::default()).unwrap();
+        let observed = thread
+            .wait_signal(&channel0.upcast(), Signal::READABLE, Some(Duration::ZERO))
+            .unwrap();
+        assert!(observed.contains(Signal::READABLE));
+    }
+
+    #[test]
+    fn sleep_until() {
+        let thread = Thread::new(Weak::new());
+        let deadline = monotonic() + Duration::from_millis(50);
+        thread.sleep_until(deadline).unwrap();
+        assert!(monotonic() >= deadline);
+    }
+
+    #[test]
+    fn suspend() {
+        let thread = Thread::new(Weak::new());
+        let token0 = thread.suspend();
+        let token1 = thread.suspend();
+        assert!(thread.with_user_context(|_| ()).is_err());
+
+        let stopped = thread.clone();
+        let handle = std::thread::spawn(move || {
+            let mut user_ctx = UserContext::default();
+            user_ctx.set_ip(0x1000);
+            stopped.stop_if_suspended(&mut user_ctx);
+            user_ctx.get_ip()
+        });
+        while !thread.signal().contains(Signal::SUSPENDED) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        thread
+            .with_user_context(|user_ctx| user_ctx.set_ip(0x2000))
+            .unwrap();
+
+        drop(token0);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!handle.is_finished());
+
+        drop(token1);
+        assert_eq!(handle.join().unwrap(), 0x2000);
+        assert!(!thread.is_suspended());
+        assert!(!thread.signal().contains(Signal::SUSPENDED));
+    }
+
     #[test]
     fn user_thread() {
         fn entry_point() {
@@ -0,0 +1,3 @@
+pub use timer::*;
+
+mod timer;
@@ -0,0 +1,96 @@
+use core::time::Duration;
+
+use alloc::sync::Arc;
+use kernel_hal::timer::{TimerKey, timer_cancel, timer_set};
+use spin::Mutex;
+
+use crate::{
+    impl_kobj, new_kobj,
+    object::{KObjectBase, Signal},
+};
+
+/// A one-shot timer, which asserts `SIGNALED` when its deadline passes.
+pub struct Timer {
+    /// The pending deadline and the key of its HAL timer.
+    deadline: Mutex<Option<(Duration, TimerKey)>>,
+    base: KObjectBase,
+}
+
+impl_kobj!(Timer);
+
+impl Timer {
+    pub fn new() -> Arc<Self> {
+        new_kobj!({
+            deadline: Mutex::new(None),
+        })
+    }
+}
+
+impl Timer {
+    /// Starts the timer, replacing any pending deadline.
+    pub fn set(self: &Arc<Self>, deadline: Duration) {
+        let mut current = self.deadline.lock();
+        if let Some((_, key)) = current.take() {
+            timer_cancel(key);
+        }
+        self.base.signal_clear(Signal::SIGNALED);
+
+        let timer = Arc::downgrade(self);
+        let key = timer_set(deadline, move |_| {
+            if let Some(timer) = timer.upgrade() {
+                timer.fire(deadline);
+            }
+        });
+        *current = Some((deadline, key));
+    }
+
+    /// Stops a pending deadline and deasserts `SIGNALED`.
+    pub fn cancel(&self) {
+        let mut current = self.deadline.lock();
+        if let Some((_, key)) = current.take() {
+            timer_cancel(key);
+        }
+        self.base.signal_clear(Signal::SIGNALED);
+    }
+
+    fn fire(&self, deadline: Duration) {
+        let mut current = self.deadline.lock();
+        // A deadline replaced while firing is left alone.
+        if current.is_some_and(|(pending, _)| pending == deadline) {
+            *current = None;
+            drop(current);
+            self.base.signal_set(Signal::SIGNALED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use kernel_hal::timer::monotonic;
+
+    use crate::object::KernelObject;
+
+    use super::*;
+
+    #[test]
+    fn fire() {
+        let timer = Timer::new();
+        timer.set(monotonic() + Duration::from_millis(20));
+        assert!(!timer.signal().contains(Signal::SIGNALED));
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(timer.signal().contains(Signal::SIGNALED));
+    }
+
+    #[test]
+    fn cancel() {
+        let timer = Timer::new();
+        timer.set(monotonic() + Duration::from_millis(20));
+        timer.cancel();
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!timer.signal().contains(Signal::SIGNALED));
+    }
+}
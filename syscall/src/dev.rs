@@ -0,0 +1,147 @@
+use alloc::sync::Arc;
+use errors::Errno;
+use object::{
+    dev::{Bti, Interrupt, Pmt, Resource, ResourceKind},
+    ipc::Port,
+    mem::Vmo,
+    object::{Handle, Rights},
+    task::{HandleId, Process, Thread},
+};
+use protocol::{
+    BTI_PERM_READ, BTI_PERM_WRITE, PinRequest, RESOURCE_KIND_IRQ, RESOURCE_KIND_MEMORY,
+};
+
+use crate::{SyscallResult, time::deadline_from_raw};
+
+pub fn new_resource(
+    process: &Arc<Process>,
+    parent_handle: u32,
+    kind: u32,
+    base: usize,
+    size: usize,
+    handle_ptr: usize,
+) -> SyscallResult {
+    let parent = process
+        .find_object_with_rights::<Resource>(HandleId::from_raw(parent_handle), Rights::WRITE)?;
+    let kind = match kind {
+        RESOURCE_KIND_MEMORY => ResourceKind::Memory,
+        RESOURCE_KIND_IRQ => ResourceKind::Irq,
+        _ => return Err(Errno::InvArg.with_message("Unknown resource kind.")),
+    };
+
+    let resource = parent.new_child(kind, base, size)?;
+    let handle = process.add_handle(Handle::new(resource, Rights::RESOURCE));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+
+    Ok(0)
+}
+
+pub fn new_interrupt(
+    process: &Arc<Process>,
+    resource: u32,
+    vector: usize,
+    handle_ptr: usize,
+) -> SyscallResult {
+    process
+        .find_object_with_rights::<Resource>(HandleId::from_raw(resource), Rights::empty())?
+        .validate(ResourceKind::Irq, vector, 1)?;
+
+    let interrupt = Interrupt::new(vector)?;
+    let handle = process.add_handle(Handle::new(interrupt, Rights::INTERRUPT));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+
+    Ok(0)
+}
+
+pub fn interrupt_wait(
+    process: &Arc<Process>,
+    handle: u32,
+    deadline: u64,
+    timestamp_ptr: usize,
+) -> SyscallResult {
+    let interrupt =
+        process.find_object_with_rights::<Interrupt>(HandleId::from_raw(handle), Rights::WAIT)?;
+
+    let timestamp = interrupt.wait(&Thread::current().unwrap(), deadline_from_raw(deadline))?;
+    if timestamp_ptr != 0 {
+        process
+            .root_vmar()
+            .write_val(timestamp_ptr, &(timestamp.as_nanos() as u64))?;
+    }
+
+    Ok(0)
+}
+
+pub fn interrupt_ack(process: &Arc<Process>, handle: u32) -> SyscallResult {
+    let interrupt =
+        process.find_object_with_rights::<Interrupt>(HandleId::from_raw(handle), Rights::WRITE)?;
+    interrupt.ack();
+    Ok(0)
+}
+
+pub fn interrupt_bind(process: &Arc<Process>, handle: u32, port: u32, key: u64) -> SyscallResult {
+    let interrupt =
+        process.find_object_with_rights::<Interrupt>(HandleId::from_raw(handle), Rights::READ)?;
+    let port = process.find_object_with_rights::<Port>(HandleId::from_raw(port), Rights::WRITE)?;
+    interrupt.bind_port(&port, key)?;
+    Ok(0)
+}
+
+pub fn new_bti(process: &Arc<Process>, resource: u32, handle_ptr: usize) -> SyscallResult {
+    process
+        .find_object_with_rights::<Resource>(HandleId::from_raw(resource), Rights::empty())?
+        .validate_root()?;
+
+    let handle = process.add_handle(Handle::new(Bti::new(), Rights::BTI));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+
+    Ok(0)
+}
+
+pub fn bti_pin(
+    process: &Arc<Process>,
+    bti: u32,
+    request_ptr: usize,
+    addrs_ptr: usize,
+    pmt_handle_ptr: usize,
+) -> SyscallResult {
+    let vmar = process.root_vmar();
+    let bti = process.find_object_with_rights::<Bti>(HandleId::from_raw(bti), Rights::MAP)?;
+    let request: PinRequest = vmar.read_val(request_ptr)?;
+
+    if request.perms & !(BTI_PERM_READ | BTI_PERM_WRITE) != 0 {
+        return Err(Errno::InvArg.with_message("Unknown pin permissions."));
+    }
+    let mut rights = Rights::MAP;
+    if request.perms & BTI_PERM_READ != 0 {
+        rights |= Rights::READ;
+    }
+    if request.perms & BTI_PERM_WRITE != 0 {
+        rights |= Rights::WRITE;
+    }
+    let vmo = process.find_object_with_rights::<Vmo>(HandleId::from_raw(request.vmo), rights)?;
+
+    let pmt = bti.pin(&vmo, request.offset, request.len)?;
+    if let Err(err) = vmar.write_array(addrs_ptr, pmt.addrs()) {
+        pmt.unpin()?;
+        return Err(err);
+    }
+    let handle = process.add_handle(Handle::new(pmt, Rights::empty()));
+    vmar.write_val(pmt_handle_ptr, &handle)?;
+
+    Ok(0)
+}
+
+pub fn bti_release_quarantine(process: &Arc<Process>, handle: u32) -> SyscallResult {
+    let bti = process.find_object_with_rights::<Bti>(HandleId::from_raw(handle), Rights::WRITE)?;
+    bti.release_quarantine();
+    Ok(0)
+}
+
+pub fn pmt_unpin(process: &Arc<Process>, handle: u32) -> SyscallResult {
+    let handle = HandleId::from_raw(handle);
+    let pmt = process.find_object_with_rights::<Pmt>(handle, Rights::empty())?;
+    pmt.unpin()?;
+    process.remove_handle(handle)?;
+    Ok(0)
+}
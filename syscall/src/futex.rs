@@ -0,0 +1,43 @@
+use alloc::sync::Arc;
+use object::task::{Process, Thread};
+
+use crate::{SyscallResult, time::deadline_from_raw};
+
+pub fn futex_wait(
+    process: &Arc<Process>,
+    addr: usize,
+    expected: u32,
+    deadline: u64,
+) -> SyscallResult {
+    object::task::futex_wait(
+        &Thread::current().unwrap(),
+        process.root_vmar(),
+        addr,
+        expected,
+        deadline_from_raw(deadline),
+    )?;
+    Ok(0)
+}
+
+pub fn futex_wake(process: &Arc<Process>, addr: usize, count: usize) -> SyscallResult {
+    object::task::futex_wake(process.root_vmar(), addr, count)
+}
+
+pub fn futex_requeue(
+    process: &Arc<Process>,
+    addr: usize,
+    wake_count: usize,
+    expected: u32,
+    requeue_addr: usize,
+    requeue_count: usize,
+) -> SyscallResult {
+    object::task::futex_requeue(
+        process.root_vmar(),
+        addr,
+        wake_count,
+        expected,
+        requeue_addr,
+        requeue_count,
+    )?;
+    Ok(0)
+}
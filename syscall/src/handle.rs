@@ -1,5 +1,8 @@
 use alloc::sync::Arc;
-use object::task::{HandleId, Process};
+use object::{
+    object::Rights,
+    task::{HandleId, Process},
+};
 
 use crate::SyscallResult;
 
@@ -11,11 +14,24 @@ pub fn remove_handle(process: &Arc<Process>, handle: u32) -> SyscallResult {
 pub fn duplicate_handle(
     process: &Arc<Process>,
     handle: u32,
+    rights: u32,
     new_handle_ptr: usize,
 ) -> SyscallResult {
-    let handle = process.get_handle(HandleId::from_raw(handle))?;
+    let new_handle =
+        process.duplicate_handle(HandleId::from_raw(handle), Rights::from_bits_retain(rights))?;
+    process.root_vmar().write_val(new_handle_ptr, &new_handle)?;
+
+    Ok(0)
+}
 
-    let new_handle = process.add_handle(handle);
+pub fn replace_handle(
+    process: &Arc<Process>,
+    handle: u32,
+    rights: u32,
+    new_handle_ptr: usize,
+) -> SyscallResult {
+    let new_handle =
+        process.replace_handle(HandleId::from_raw(handle), Rights::from_bits_retain(rights))?;
     process.root_vmar().write_val(new_handle_ptr, &new_handle)?;
 
     Ok(0)
@@ -0,0 +1,146 @@
+use alloc::{sync::Arc, vec::Vec};
+use errors::{Errno, Result};
+use kernel_hal::task::ThreadState;
+use object::{
+    mem::{Vmar, Vmo},
+    object::{KernelObject, Rights},
+    task::{HandleId, Process, ProcessState, Thread},
+};
+use pod::Pod;
+use protocol::{
+    HandleBasicInfo, INFO_HANDLE_BASIC, INFO_PROCESS, INFO_PROCESS_THREADS, INFO_THREAD,
+    INFO_VMAR_MAPS, INFO_VMO, MapInfo, OBJECT_TYPE_NAME_LEN, PROCESS_STATE_CREATED,
+    PROCESS_STATE_DEAD, PROCESS_STATE_DYING, PROCESS_STATE_RUNNING, ProcessInfo,
+    THREAD_STATE_BLOCKED, THREAD_STATE_DEAD, THREAD_STATE_READY, THREAD_STATE_RUNNING, ThreadInfo,
+    VmoInfo,
+};
+
+use crate::SyscallResult;
+
+/// Writes the records of `topic` about `handle` into `buf`.
+/// `actual_ptr` receives how many records were written
+/// and `avail_ptr` how many there are; either may be 0.
+pub fn object_get_info(
+    process: &Arc<Process>,
+    handle: u32,
+    topic: u32,
+    buf: usize,
+    buf_len: usize,
+    actual_ptr: usize,
+    avail_ptr: usize,
+) -> SyscallResult {
+    let handle_id = HandleId::from_raw(handle);
+    let vmar = process.root_vmar();
+
+    let (actual, avail) = match topic {
+        INFO_HANDLE_BASIC => {
+            let handle = process.get_handle(handle_id)?;
+            let name = handle.object.type_name().as_bytes();
+            let len = name.len().min(OBJECT_TYPE_NAME_LEN);
+            let mut type_name = [0; OBJECT_TYPE_NAME_LEN];
+            type_name[..len].copy_from_slice(&name[..len]);
+
+            let info = HandleBasicInfo {
+                koid: handle.object.koid(),
+                related_koid: handle.object.related_koid(),
+                rights: handle.rights.bits(),
+                reserved: 0,
+                type_name,
+            };
+            write_record(vmar, buf, buf_len, &info)?
+        }
+        INFO_PROCESS_THREADS => {
+            let child = process.find_object_with_rights::<Process>(handle_id, Rights::READ)?;
+            let koids = child
+                .threads()
+                .iter()
+                .map(|thread| thread.koid())
+                .collect::<Vec<_>>();
+            write_records(vmar, buf, buf_len, &koids)?
+        }
+        INFO_VMAR_MAPS => {
+            let target = process.find_object_with_rights::<Vmar>(handle_id, Rights::READ)?;
+            let maps = target
+                .mappings()
+                .iter()
+                .map(|mapping| MapInfo {
+                    base: mapping.base,
+                    size: mapping.size,
+                    vmo_koid: mapping.vmo_koid,
+                    perm: mapping.perm.bits(),
+                    reserved: 0,
+                })
+                .collect::<Vec<_>>();
+            write_records(vmar, buf, buf_len, &maps)?
+        }
+        INFO_VMO => {
+            let vmo = process.find_object_with_rights::<Vmo>(handle_id, Rights::READ)?;
+            let info = VmoInfo {
+                koid: vmo.koid(),
+                size: vmo.len(),
+                committed_pages: vmo.committed_pages(),
+            };
+            write_record(vmar, buf, buf_len, &info)?
+        }
+        INFO_THREAD => {
+            let thread = process.find_object_with_rights::<Thread>(handle_id, Rights::READ)?;
+            let state = match thread.state() {
+                ThreadState::Running => THREAD_STATE_RUNNING,
+                ThreadState::Ready => THREAD_STATE_READY,
+                ThreadState::Blocked => THREAD_STATE_BLOCKED,
+                ThreadState::Dead => THREAD_STATE_DEAD,
+            };
+            let info = ThreadInfo { state, reserved: 0 };
+            write_record(vmar, buf, buf_len, &info)?
+        }
+        INFO_PROCESS => {
+            let target = process.find_object_with_rights::<Process>(handle_id, Rights::READ)?;
+            let state = match target.state() {
+                ProcessState::Created => PROCESS_STATE_CREATED,
+                ProcessState::Running => PROCESS_STATE_RUNNING,
+                ProcessState::Dying => PROCESS_STATE_DYING,
+                ProcessState::Dead => PROCESS_STATE_DEAD,
+            };
+            let info = ProcessInfo {
+                return_code: target.exit_status().unwrap_or(0) as i64,
+                state,
+                reserved: 0,
+            };
+            write_record(vmar, buf, buf_len, &info)?
+        }
+        _ => return Err(Errno::InvArg.with_message("Unknown info topic.")),
+    };
+
+    if actual_ptr != 0 {
+        vmar.write_val(actual_ptr, &actual)?;
+    }
+    if avail_ptr != 0 {
+        vmar.write_val(avail_ptr, &avail)?;
+    }
+    Ok(0)
+}
+
+fn write_record<T: Pod>(
+    vmar: &Vmar,
+    buf: usize,
+    buf_len: usize,
+    record: &T,
+) -> Result<(usize, usize)> {
+    if buf_len < size_of::<T>() {
+        return Err(Errno::TooBig.with_message("Buffer is too small for the record."));
+    }
+    vmar.write_val(buf, record)?;
+    Ok((1, 1))
+}
+
+/// Writes as many of `records` as fit into `buf`.
+fn write_records<T: Pod>(
+    vmar: &Vmar,
+    buf: usize,
+    buf_len: usize,
+    records: &[T],
+) -> Result<(usize, usize)> {
+    let actual = records.len().min(buf_len / size_of::<T>());
+    vmar.write_array(buf, &records[..actual])?;
+    Ok((actual, records.len()))
+}
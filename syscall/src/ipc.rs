@@ -1,11 +1,14 @@
 use alloc::{sync::Arc, vec};
 use errors::Errno;
 use object::{
-    ipc::{Channel, MessagePacket},
+    ipc::{Channel, Fifo, MessagePacket, SOCKET_CAPACITY, Socket, SocketType},
     object::{Handle, Rights},
     task::{HandleId, Process},
 };
-use protocol::{ReadBuffer, WriteBuffer};
+use protocol::{
+    ReadBuffer, SOCKET_DATAGRAM, SOCKET_SHUTDOWN_READ, SOCKET_SHUTDOWN_WRITE, SOCKET_STREAM,
+    WriteBuffer,
+};
 
 use crate::SyscallResult;
 
@@ -93,3 +96,118 @@ pub fn write_channel(
 
     Ok(0)
 }
+
+pub fn new_socket(
+    process: &Arc<Process>,
+    socket_type: u32,
+    handle0_ptr: usize,
+    handle1_ptr: usize,
+) -> SyscallResult {
+    let socket_type = match socket_type {
+        SOCKET_STREAM => SocketType::Stream,
+        SOCKET_DATAGRAM => SocketType::Datagram,
+        _ => return Err(Errno::InvArg.with_message("Unknown socket type.")),
+    };
+    let (socket0, socket1) = Socket::new(socket_type);
+    let handle0 = process.add_handle(Handle::new(socket0, Rights::ALL));
+    let handle1 = process.add_handle(Handle::new(socket1, Rights::ALL));
+    process.root_vmar().write_val(handle0_ptr, &handle0)?;
+    process.root_vmar().write_val(handle1_ptr, &handle1)?;
+    Ok(0)
+}
+
+pub fn read_socket(
+    process: &Arc<Process>,
+    handle: u32,
+    buffer: usize,
+    len: usize,
+) -> SyscallResult {
+    let socket =
+        process.find_object_with_rights::<Socket>(HandleId::from_raw(handle), Rights::READ)?;
+    let data = socket.read(len)?;
+    process.root_vmar().write(buffer, &data)?;
+    Ok(data.len())
+}
+
+pub fn write_socket(
+    process: &Arc<Process>,
+    handle: u32,
+    buffer: usize,
+    len: usize,
+) -> SyscallResult {
+    let socket =
+        process.find_object_with_rights::<Socket>(HandleId::from_raw(handle), Rights::WRITE)?;
+    // No more than the buffer holds is ever written, so don't copy in more.
+    let len = match socket.socket_type() {
+        SocketType::Stream => len.min(SOCKET_CAPACITY),
+        SocketType::Datagram if len > SOCKET_CAPACITY => {
+            return Err(Errno::TooBig.with_message("Datagram is larger than the buffer."));
+        }
+        SocketType::Datagram => len,
+    };
+    let mut data = vec![0u8; len];
+    process.root_vmar().read(buffer, &mut data)?;
+    socket.write(&data)
+}
+
+pub fn shutdown_socket(process: &Arc<Process>, handle: u32, options: u32) -> SyscallResult {
+    let socket =
+        process.find_object_with_rights::<Socket>(HandleId::from_raw(handle), Rights::WRITE)?;
+    if options & !(SOCKET_SHUTDOWN_READ | SOCKET_SHUTDOWN_WRITE) != 0 {
+        return Err(Errno::InvArg.with_message("Unknown shutdown options."));
+    }
+    socket.shutdown(
+        options & SOCKET_SHUTDOWN_READ != 0,
+        options & SOCKET_SHUTDOWN_WRITE != 0,
+    )?;
+    Ok(0)
+}
+
+pub fn new_fifo(
+    process: &Arc<Process>,
+    elem_count: usize,
+    elem_size: usize,
+    handle0_ptr: usize,
+    handle1_ptr: usize,
+) -> SyscallResult {
+    let (fifo0, fifo1) = Fifo::new(elem_count, elem_size)?;
+    let handle0 = process.add_handle(Handle::new(fifo0, Rights::ALL));
+    let handle1 = process.add_handle(Handle::new(fifo1, Rights::ALL));
+    process.root_vmar().write_val(handle0_ptr, &handle0)?;
+    process.root_vmar().write_val(handle1_ptr, &handle1)?;
+    Ok(0)
+}
+
+pub fn read_fifo(
+    process: &Arc<Process>,
+    handle: u32,
+    elem_size: usize,
+    buffer: usize,
+    count: usize,
+) -> SyscallResult {
+    let fifo = process.find_object_with_rights::<Fifo>(HandleId::from_raw(handle), Rights::READ)?;
+    if elem_size != fifo.elem_size() {
+        return Err(Errno::InvArg.with_message("Element size mismatch."));
+    }
+    let data = fifo.read(count)?;
+    process.root_vmar().write(buffer, &data)?;
+    Ok(data.len() / elem_size)
+}
+
+pub fn write_fifo(
+    process: &Arc<Process>,
+    handle: u32,
+    elem_size: usize,
+    buffer: usize,
+    count: usize,
+) -> SyscallResult {
+    let fifo =
+        process.find_object_with_rights::<Fifo>(HandleId::from_raw(handle), Rights::WRITE)?;
+    if elem_size != fifo.elem_size() {
+        return Err(Errno::InvArg.with_message("Element size mismatch."));
+    }
+    // No more than a full fifo can be written at once.
+    let mut data = vec![0u8; count.min(fifo.elem_count()) * elem_size];
+    process.root_vmar().read(buffer, &mut data)?;
+    fifo.write(&data)
+}
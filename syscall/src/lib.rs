@@ -11,22 +11,45 @@ use object::task::Process;
 
 use crate::{
     debug::debug,
-    handle::{duplicate_handle, remove_handle},
-    ipc::{new_channel, read_channel, write_channel},
+    dev::{
+        bti_pin, bti_release_quarantine, interrupt_ack, interrupt_bind, interrupt_wait, new_bti,
+        new_interrupt, new_resource, pmt_unpin,
+    },
+    futex::{futex_requeue, futex_wait, futex_wake},
+    handle::{duplicate_handle, remove_handle, replace_handle},
+    info::object_get_info,
+    ipc::{
+        new_channel, new_fifo, new_socket, read_channel, read_fifo, read_socket, shutdown_socket,
+        write_channel, write_fifo, write_socket,
+    },
+    property::{object_get_property, object_set_property},
+    signal::{
+        new_event, new_event_pair, new_port, object_signal, object_signal_peer, object_wait_async,
+        object_wait_one, port_cancel, port_queue, port_wait,
+    },
     task::{
-        exit, exit_thread, kill_process, kill_thread, new_process, new_thread, start_process,
-        start_thread,
+        create_exception_channel, exception_set_state, exit, exit_thread, kill_job, kill_process,
+        kill_thread, new_job, new_process, new_thread, set_job_policy, start_process, start_thread,
+        thread_read_state, thread_suspend, thread_write_state,
     },
+    time::{cancel_timer, clock_get_monotonic, nanosleep, new_timer, set_timer},
     vm::{
-        acquire_vmo, allocate_vmar, allocate_vmar_at, allocate_vmo, get_vmar_base, get_vmar_size,
-        get_vmo_paddr, map_vmar, protect_vmar, read_vmo, unmap_vmar, write_vmo,
+        acquire_vmo, allocate_vmar, allocate_vmar_at, allocate_vmo, create_vmo_child,
+        get_vmar_base, get_vmar_size, get_vmo_paddr, map_vmar, protect_vmar, read_vmo,
+        set_vmo_cache_policy, set_vmo_size, unmap_vmar, vmar_map, vmo_op_range, write_vmo,
     },
 };
 
 mod debug;
+mod dev;
+mod futex;
 mod handle;
+mod info;
 mod ipc;
+mod property;
+mod signal;
 mod task;
+mod time;
 mod vm;
 
 type SyscallResult = Result<usize>;
@@ -80,9 +103,9 @@ fn syscall_impl(process: &Arc<Process>, user_ctx: &mut UserContext) -> Result<us
         7 => map_vmar(process, arg1 as u32, arg2, arg3 as u32, arg4 as u32),
         8 => unmap_vmar(process, arg1 as u32, arg2, arg3),
         9 => protect_vmar(process, arg1 as u32, arg2, arg3, arg4 as u32),
-        10 => allocate_vmo(process, arg1, arg2 != 0, arg3),
+        10 => allocate_vmo(process, arg1, arg2 as u32, arg3, arg4 as u32),
         11 => exit(process, arg1 as i32),
-        12 => new_process(process, arg1, arg2, arg3, arg4),
+        12 => new_process(process, arg1 as u32, arg2, arg3, arg4, arg5),
         13 => start_process(
             process,
             arg1 as u32,
@@ -97,13 +120,72 @@ fn syscall_impl(process: &Arc<Process>, user_ctx: &mut UserContext) -> Result<us
         16 => exit_thread(),
         17 => kill_process(process, arg1 as u32),
         18 => kill_thread(process, arg1 as u32),
-        19 => duplicate_handle(process, arg1 as u32, arg2),
+        19 => duplicate_handle(process, arg1 as u32, arg2 as u32, arg3),
         20 => read_vmo(process, arg1 as u32, arg2, arg3, arg4),
         21 => write_vmo(process, arg1 as u32, arg2, arg3, arg4),
         22 => get_vmar_base(process, arg1 as u32),
         23 => get_vmar_size(process, arg1 as u32),
-        24 => acquire_vmo(process, arg1, arg2, arg3),
-        25 => get_vmo_paddr(process, arg1 as u32),
+        24 => acquire_vmo(process, arg1 as u32, arg2, arg3, arg4),
+        25 => get_vmo_paddr(process, arg1 as u32, arg2 as u32),
+        26 => object_wait_one(process, arg1 as u32, arg2 as u32, arg3 as u64, arg4),
+        27 => new_port(process, arg1),
+        28 => object_wait_async(process, arg1 as u32, arg2 as u32, arg3 as u64, arg4 as u32),
+        29 => port_wait(process, arg1 as u32, arg2 as u64, arg3),
+        30 => port_queue(process, arg1 as u32, arg2),
+        31 => new_event(process, arg1),
+        32 => new_event_pair(process, arg1, arg2),
+        33 => object_signal(process, arg1 as u32, arg2 as u32, arg3 as u32),
+        34 => object_signal_peer(process, arg1 as u32, arg2 as u32, arg3 as u32),
+        35 => clock_get_monotonic(process),
+        36 => nanosleep(process, arg1 as u64),
+        37 => new_timer(process, arg1),
+        38 => set_timer(process, arg1 as u32, arg2 as u64),
+        39 => cancel_timer(process, arg1 as u32),
+        40 => futex_wait(process, arg1, arg2 as u32, arg3 as u64),
+        41 => futex_wake(process, arg1, arg2),
+        42 => futex_requeue(process, arg1, arg2, arg3 as u32, arg4, arg5),
+        43 => new_socket(process, arg1 as u32, arg2, arg3),
+        44 => read_socket(process, arg1 as u32, arg2, arg3),
+        45 => write_socket(process, arg1 as u32, arg2, arg3),
+        46 => shutdown_socket(process, arg1 as u32, arg2 as u32),
+        47 => new_fifo(process, arg1, arg2, arg3, arg4),
+        48 => read_fifo(process, arg1 as u32, arg2, arg3, arg4),
+        49 => write_fifo(process, arg1 as u32, arg2, arg3, arg4),
+        50 => new_job(process, arg1 as u32, arg2),
+        51 => set_job_policy(process, arg1 as u32, arg2 as u32),
+        52 => kill_job(process, arg1 as u32),
+        53 => new_resource(process, arg1 as u32, arg2 as u32, arg3, arg4, arg5),
+        54 => new_interrupt(process, arg1 as u32, arg2, arg3),
+        55 => interrupt_wait(process, arg1 as u32, arg2 as u64, arg3),
+        56 => interrupt_ack(process, arg1 as u32),
+        57 => interrupt_bind(process, arg1 as u32, arg2 as u32, arg3 as u64),
+        58 => new_bti(process, arg1 as u32, arg2),
+        59 => bti_pin(process, arg1 as u32, arg2, arg3, arg4),
+        60 => pmt_unpin(process, arg1 as u32),
+        61 => replace_handle(process, arg1 as u32, arg2 as u32, arg3),
+        62 => object_get_info(process, arg1 as u32, arg2 as u32, arg3, arg4, arg5, arg6),
+        63 => object_get_property(process, arg1 as u32, arg2 as u32, arg3, arg4),
+        64 => object_set_property(process, arg1 as u32, arg2 as u32, arg3, arg4),
+        65 => create_exception_channel(process, arg1 as u32, arg2),
+        66 => exception_set_state(process, arg1 as u32, arg2 as u32),
+        67 => thread_suspend(process, arg1 as u32, arg2),
+        68 => thread_read_state(process, arg1 as u32, arg2 as u32, arg3, arg4),
+        69 => thread_write_state(process, arg1 as u32, arg2 as u32, arg3, arg4),
+        70 => create_vmo_child(process, arg1 as u32, arg2 as u32, arg3, arg4, arg5),
+        71 => set_vmo_size(process, arg1 as u32, arg2),
+        72 => vmo_op_range(process, arg1 as u32, arg2 as u32, arg3, arg4),
+        73 => set_vmo_cache_policy(process, arg1 as u32, arg2 as u32),
+        74 => vmar_map(
+            process,
+            arg1 as u32,
+            arg2 as u32,
+            arg3,
+            arg4 as u32,
+            arg5,
+            arg6,
+        ),
+        75 => port_cancel(process, arg1 as u32, arg2 as u32, arg3 as u64),
+        76 => bti_release_quarantine(process, arg1 as u32),
         _ => Err(Errno::InvSyscall.no_message()),
     }
 }
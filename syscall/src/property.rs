@@ -0,0 +1,78 @@
+use alloc::{string::String, sync::Arc};
+use errors::Errno;
+use object::{
+    mem::Vmo,
+    object::Rights,
+    task::{HandleId, Process},
+};
+use protocol::{MAX_NAME_LEN, PROP_NAME, PROP_VMO_CONTENT_SIZE};
+
+use crate::SyscallResult;
+
+/// Copies property `prop` of the object into `buf`, truncated to `buf_len`,
+/// and returns its full length.
+pub fn object_get_property(
+    process: &Arc<Process>,
+    handle: u32,
+    prop: u32,
+    buf: usize,
+    buf_len: usize,
+) -> SyscallResult {
+    let handle_id = HandleId::from_raw(handle);
+    let handle = process.get_handle_with_rights(handle_id, Rights::READ)?;
+
+    match prop {
+        PROP_NAME => {
+            let name = handle.object.name();
+            let len = name.len().min(buf_len);
+            process.root_vmar().write(buf, &name.as_bytes()[..len])?;
+            Ok(name.len())
+        }
+        PROP_VMO_CONTENT_SIZE => {
+            let vmo = process.find_object_with_rights::<Vmo>(handle_id, Rights::READ)?;
+            if buf_len < size_of::<usize>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small."));
+            }
+            process.root_vmar().write_val(buf, &vmo.content_size())?;
+            Ok(size_of::<usize>())
+        }
+        _ => Err(Errno::InvArg.with_message("Unknown property.")),
+    }
+}
+
+pub fn object_set_property(
+    process: &Arc<Process>,
+    handle: u32,
+    prop: u32,
+    buf: usize,
+    buf_len: usize,
+) -> SyscallResult {
+    let handle_id = HandleId::from_raw(handle);
+    let handle = process.get_handle_with_rights(handle_id, Rights::WRITE)?;
+
+    match prop {
+        PROP_NAME => {
+            let mut name = alloc::vec![0; buf_len.min(MAX_NAME_LEN)];
+            process.root_vmar().read(buf, &mut name)?;
+            // Truncation may split the last character, which is dropped.
+            let name = match core::str::from_utf8(&name) {
+                Ok(name) => name,
+                Err(err) if err.error_len().is_none() => {
+                    core::str::from_utf8(&name[..err.valid_up_to()]).unwrap()
+                }
+                Err(_) => return Err(Errno::InvArg.with_message("Name is not valid UTF-8.")),
+            };
+            handle.object.set_name(String::from(name));
+            Ok(0)
+        }
+        PROP_VMO_CONTENT_SIZE => {
+            let vmo = process.find_object_with_rights::<Vmo>(handle_id, Rights::WRITE)?;
+            if buf_len < size_of::<usize>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small."));
+            }
+            vmo.set_content_size(process.root_vmar().read_val(buf)?)?;
+            Ok(0)
+        }
+        _ => Err(Errno::InvArg.with_message("Unknown property.")),
+    }
+}
@@ -0,0 +1,182 @@
+use alloc::sync::Arc;
+use errors::Errno;
+use object::{
+    ipc::{Event, EventPair, PacketPayload, Port, PortPacket},
+    object::{Handle, Rights, Signal, Upcast},
+    task::{HandleId, Process, Thread},
+};
+use pod::IntoBytes;
+use protocol::{
+    InterruptPacket, PORT_PACKET_INTERRUPT, PORT_PACKET_SIGNAL, PORT_PACKET_USER, SignalPacket,
+};
+
+use crate::{SyscallResult, time::deadline_from_raw};
+
+pub fn object_wait_one(
+    process: &Arc<Process>,
+    handle: u32,
+    signals: u32,
+    deadline: u64,
+    observed_ptr: usize,
+) -> SyscallResult {
+    let handle = process.get_handle_with_rights(HandleId::from_raw(handle), Rights::WAIT)?;
+    let signal = Signal::from_bits_truncate(signals);
+
+    let observed = match Thread::current().unwrap().wait_signal(
+        &handle.object,
+        signal,
+        deadline_from_raw(deadline),
+    ) {
+        Ok(observed) => observed,
+        Err(err) if err.errno() == Errno::TimedOut => handle.object.signal(),
+        Err(err) => return Err(err),
+    };
+
+    if observed_ptr != 0 {
+        process
+            .root_vmar()
+            .write_val(observed_ptr, &observed.bits())?;
+    }
+
+    if observed.intersects(signal) {
+        Ok(0)
+    } else {
+        Err(Errno::TimedOut.no_message())
+    }
+}
+
+pub fn object_signal(process: &Arc<Process>, handle: u32, clear: u32, set: u32) -> SyscallResult {
+    let handle = process.get_handle_with_rights(HandleId::from_raw(handle), Rights::SIGNAL)?;
+    handle.object.user_signal(
+        Signal::from_bits_retain(clear),
+        Signal::from_bits_retain(set),
+    )?;
+    Ok(0)
+}
+
+pub fn object_signal_peer(
+    process: &Arc<Process>,
+    handle: u32,
+    clear: u32,
+    set: u32,
+) -> SyscallResult {
+    let handle = process.get_handle_with_rights(HandleId::from_raw(handle), Rights::SIGNAL)?;
+    handle.object.peer()?.user_signal(
+        Signal::from_bits_retain(clear),
+        Signal::from_bits_retain(set),
+    )?;
+    Ok(0)
+}
+
+pub fn new_event(process: &Arc<Process>, handle_ptr: usize) -> SyscallResult {
+    let handle = process.add_handle(Handle::new(Event::new(), Rights::ALL));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+    Ok(0)
+}
+
+pub fn new_event_pair(
+    process: &Arc<Process>,
+    handle0_ptr: usize,
+    handle1_ptr: usize,
+) -> SyscallResult {
+    let (event0, event1) = EventPair::new();
+    let handle0 = process.add_handle(Handle::new(event0, Rights::ALL));
+    let handle1 = process.add_handle(Handle::new(event1, Rights::ALL));
+    process.root_vmar().write_val(handle0_ptr, &handle0)?;
+    process.root_vmar().write_val(handle1_ptr, &handle1)?;
+    Ok(0)
+}
+
+pub fn object_wait_async(
+    process: &Arc<Process>,
+    handle: u32,
+    port: u32,
+    key: u64,
+    signals: u32,
+) -> SyscallResult {
+    let handle = process.get_handle_with_rights(HandleId::from_raw(handle), Rights::WAIT)?;
+    let port = process.find_object_with_rights::<Port>(HandleId::from_raw(port), Rights::WRITE)?;
+    port.wait_async(&handle.object, key, Signal::from_bits_truncate(signals))?;
+    Ok(0)
+}
+
+pub fn new_port(process: &Arc<Process>, handle_ptr: usize) -> SyscallResult {
+    let handle = process.add_handle(Handle::new(Port::new(), Rights::ALL));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+    Ok(0)
+}
+
+pub fn port_wait(
+    process: &Arc<Process>,
+    port: u32,
+    deadline: u64,
+    packet_ptr: usize,
+) -> SyscallResult {
+    let port = process.find_object_with_rights::<Port>(HandleId::from_raw(port), Rights::READ)?;
+
+    let packet = loop {
+        if let Some(packet) = port.pop() {
+            break packet;
+        }
+        Thread::current().unwrap().wait_signal(
+            &port.upcast(),
+            Signal::READABLE,
+            deadline_from_raw(deadline),
+        )?;
+    };
+
+    let mut raw_packet = protocol::PortPacket {
+        key: packet.key,
+        kind: PORT_PACKET_USER,
+        payload: [0; 32],
+    };
+    match packet.payload {
+        PacketPayload::Signal { trigger, observed } => {
+            let signal_packet = SignalPacket {
+                trigger: trigger.bits(),
+                observed: observed.bits(),
+                reserved: [0; 3],
+            };
+            raw_packet.kind = PORT_PACKET_SIGNAL;
+            raw_packet.payload.copy_from_slice(signal_packet.as_bytes());
+        }
+        PacketPayload::User(payload) => raw_packet.payload = payload,
+        PacketPayload::Interrupt { timestamp } => {
+            let interrupt_packet = InterruptPacket {
+                timestamp: timestamp.as_nanos() as u64,
+                reserved: [0; 3],
+            };
+            raw_packet.kind = PORT_PACKET_INTERRUPT;
+            raw_packet
+                .payload
+                .copy_from_slice(interrupt_packet.as_bytes());
+        }
+    }
+    // A packet that can't be delivered stays for the next wait.
+    if let Err(err) = process.root_vmar().write_val(packet_ptr, &raw_packet) {
+        port.requeue(packet);
+        return Err(err);
+    }
+
+    Ok(0)
+}
+
+pub fn port_cancel(process: &Arc<Process>, port: u32, handle: u32, key: u64) -> SyscallResult {
+    let port = process.find_object_with_rights::<Port>(HandleId::from_raw(port), Rights::WRITE)?;
+    let handle = process.get_handle(HandleId::from_raw(handle))?;
+    port.cancel(&handle.object, key)?;
+    Ok(0)
+}
+
+pub fn port_queue(process: &Arc<Process>, port: u32, packet_ptr: usize) -> SyscallResult {
+    let port = process.find_object_with_rights::<Port>(HandleId::from_raw(port), Rights::WRITE)?;
+    let raw_packet: protocol::PortPacket = process.root_vmar().read_val(packet_ptr)?;
+    if raw_packet.kind != PORT_PACKET_USER {
+        return Err(Errno::InvArg.with_message("Only user packets can be queued."));
+    }
+    port.queue(PortPacket {
+        key: raw_packet.key,
+        payload: PacketPayload::User(raw_packet.payload),
+    });
+    Ok(0)
+}
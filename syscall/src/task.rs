@@ -1,22 +1,58 @@
 use alloc::sync::Arc;
+use errors::Errno;
+use kernel_hal::task::{DebugRegs, WatchFlags, Watchpoint};
 use object::{
     object::{Handle, Rights},
-    task::{HandleId, Process, Thread},
+    task::{Exception, ExceptionState, HandleId, Job, JobPolicy, Process, ProcessState, Thread},
+};
+use protocol::{
+    EXCEPTION_STATE_KILL, EXCEPTION_STATE_RESUME, EXCEPTION_STATE_TRY_NEXT, FIRST_HANDLE,
+    THREAD_REGS_DEBUG, THREAD_REGS_GENERAL, THREAD_REGS_SINGLE_STEP, ThreadDebugRegs,
+    ThreadGeneralRegs, WatchpointRegs,
 };
-use protocol::FIRST_HANDLE;
 
 use crate::{SyscallResult, syscall_handler};
 
+pub fn new_job(process: &Arc<Process>, parent_handle: u32, handle_ptr: usize) -> SyscallResult {
+    let parent = process
+        .find_object_with_rights::<Job>(HandleId::from_raw(parent_handle), Rights::MANAGE)?;
+    process.job().check_policy(JobPolicy::DENY_NEW_JOB)?;
+
+    let job = parent.new_child()?;
+    let handle = process.add_handle(Handle::new(job, Rights::JOB));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+
+    Ok(0)
+}
+
+pub fn set_job_policy(process: &Arc<Process>, handle: u32, policy: u32) -> SyscallResult {
+    let job = process.find_object_with_rights::<Job>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let policy =
+        JobPolicy::from_bits(policy).ok_or(Errno::InvArg.with_message("Unknown job policy."))?;
+    job.set_policy(policy)?;
+    Ok(0)
+}
+
+pub fn kill_job(process: &Arc<Process>, handle: u32) -> SyscallResult {
+    let job = process.find_object_with_rights::<Job>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    job.kill();
+    Ok(0)
+}
+
 pub fn new_process(
     process: &Arc<Process>,
+    job_handle: u32,
     handle_ptr: usize,
     vmar_handle_ptr: usize,
     base_ptr: usize,
     size_ptr: usize,
 ) -> SyscallResult {
+    let job =
+        process.find_object_with_rights::<Job>(HandleId::from_raw(job_handle), Rights::MANAGE)?;
+    process.job().check_policy(JobPolicy::DENY_NEW_PROCESS)?;
     let vmar = process.root_vmar();
 
-    let child = Process::new();
+    let child = Process::new(&job)?;
     let child_vmar = child.root_vmar();
 
     let handle = process.add_handle(Handle::new(child.clone(), Rights::PROCESS));
@@ -58,6 +94,10 @@ pub fn start_process(
     let thread = process
         .find_object_with_rights::<Thread>(HandleId::from_raw(thread_handle), Rights::MANAGE)?;
 
+    if child.state() != ProcessState::Created {
+        return Err(Errno::BadState.with_message("Process has already been started."));
+    }
+
     let boot_handle = process.remove_handle(HandleId::from_raw(boot_handle))?;
     let boot_handle = child.add_handle(boot_handle);
     assert_eq!(boot_handle.as_raw(), FIRST_HANDLE);
@@ -70,14 +110,14 @@ pub fn start_process(
             ctx.set_first_arg(start_info_addr);
         },
         syscall_handler,
-    );
+    )?;
     Ok(0)
 }
 
 pub fn new_thread(process: &Arc<Process>, proc_handle: u32, handle_ptr: usize) -> SyscallResult {
     let child = process
         .find_object_with_rights::<Process>(HandleId::from_raw(proc_handle), Rights::MANAGE)?;
-    let thread = child.new_thread();
+    let thread = child.new_thread()?;
     let handle = process.add_handle(Handle::new(thread, Rights::THREAD));
     process.root_vmar().write_val(handle_ptr, &handle)?;
     Ok(0)
@@ -116,3 +156,148 @@ pub fn kill_thread(process: &Arc<Process>, handle: u32) -> SyscallResult {
     thread.kill();
     Ok(0)
 }
+
+/// Binds an exception channel to a thread, process or job.
+pub fn create_exception_channel(
+    process: &Arc<Process>,
+    task_handle: u32,
+    channel_ptr: usize,
+) -> SyscallResult {
+    let task = process.get_handle_with_rights(HandleId::from_raw(task_handle), Rights::MANAGE)?;
+    let task = task.object;
+
+    let channel = if let Some(thread) = task.downcast_ref::<Thread>() {
+        thread.exceptionate().create_channel()?
+    } else if let Some(child) = task.downcast_ref::<Process>() {
+        child.exceptionate().create_channel()?
+    } else if let Some(job) = task.downcast_ref::<Job>() {
+        job.exceptionate().create_channel()?
+    } else {
+        return Err(Errno::WrongType.with_message("Not a thread, process or job."));
+    };
+
+    let handle = process.add_handle(Handle::new(channel, Rights::READ | Rights::WAIT));
+    process.root_vmar().write_val(channel_ptr, &handle)?;
+    Ok(0)
+}
+
+pub fn exception_set_state(process: &Arc<Process>, handle: u32, state: u32) -> SyscallResult {
+    let exception =
+        process.find_object_with_rights::<Exception>(HandleId::from_raw(handle), Rights::WRITE)?;
+    let state = match state {
+        EXCEPTION_STATE_TRY_NEXT => ExceptionState::TryNext,
+        EXCEPTION_STATE_RESUME => ExceptionState::Resume,
+        EXCEPTION_STATE_KILL => ExceptionState::Kill,
+        _ => return Err(Errno::InvArg.with_message("Unknown exception state.")),
+    };
+    exception.set_state(state);
+    Ok(0)
+}
+
+pub fn thread_suspend(process: &Arc<Process>, handle: u32, token_ptr: usize) -> SyscallResult {
+    let thread =
+        process.find_object_with_rights::<Thread>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let token = thread.suspend();
+    let handle = process.add_handle(Handle::new(token, Rights::BASIC | Rights::TRANSFER));
+    process.root_vmar().write_val(token_ptr, &handle)?;
+    Ok(0)
+}
+
+pub fn thread_read_state(
+    process: &Arc<Process>,
+    handle: u32,
+    kind: u32,
+    buf: usize,
+    buf_len: usize,
+) -> SyscallResult {
+    let thread =
+        process.find_object_with_rights::<Thread>(HandleId::from_raw(handle), Rights::READ)?;
+    match kind {
+        THREAD_REGS_GENERAL => {
+            if buf_len < size_of::<ThreadGeneralRegs>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let regs = thread.with_user_context(|user_ctx| ThreadGeneralRegs {
+                regs: user_ctx.get_regs(),
+                era: user_ctx.get_ip(),
+                prmd: user_ctx.get_mode(),
+            })?;
+            process.root_vmar().write_val(buf, &regs)?;
+            Ok(0)
+        }
+        THREAD_REGS_DEBUG => {
+            if buf_len < size_of::<ThreadDebugRegs>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let debug = thread.with_user_context(|user_ctx| user_ctx.debug_regs())?;
+            let regs = ThreadDebugRegs {
+                breakpoints: debug.breakpoints,
+                watchpoints: debug.watchpoints.map(|watchpoint| WatchpointRegs {
+                    addr: watchpoint.addr,
+                    len: watchpoint.len,
+                    flags: watchpoint.flags.bits(),
+                }),
+            };
+            process.root_vmar().write_val(buf, &regs)?;
+            Ok(0)
+        }
+        THREAD_REGS_SINGLE_STEP => {
+            if buf_len < size_of::<u32>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let single_step = thread.with_user_context(|user_ctx| user_ctx.single_step())?;
+            process.root_vmar().write_val(buf, &(single_step as u32))?;
+            Ok(0)
+        }
+        _ => Err(Errno::InvArg.with_message("Unknown register set.")),
+    }
+}
+
+pub fn thread_write_state(
+    process: &Arc<Process>,
+    handle: u32,
+    kind: u32,
+    buf: usize,
+    buf_len: usize,
+) -> SyscallResult {
+    let thread =
+        process.find_object_with_rights::<Thread>(HandleId::from_raw(handle), Rights::WRITE)?;
+    match kind {
+        THREAD_REGS_GENERAL => {
+            if buf_len < size_of::<ThreadGeneralRegs>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let regs = process.root_vmar().read_val::<ThreadGeneralRegs>(buf)?;
+            thread.with_user_context(|user_ctx| {
+                user_ctx.set_regs(&regs.regs);
+                user_ctx.set_ip(regs.era);
+            })?;
+            Ok(0)
+        }
+        THREAD_REGS_DEBUG => {
+            if buf_len < size_of::<ThreadDebugRegs>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let regs = process.root_vmar().read_val::<ThreadDebugRegs>(buf)?;
+            let debug = DebugRegs {
+                breakpoints: regs.breakpoints,
+                watchpoints: regs.watchpoints.map(|watchpoint| Watchpoint {
+                    addr: watchpoint.addr,
+                    len: watchpoint.len,
+                    flags: WatchFlags::from_bits_truncate(watchpoint.flags),
+                }),
+            };
+            thread.with_user_context(|user_ctx| user_ctx.set_debug_regs(debug))??;
+            Ok(0)
+        }
+        THREAD_REGS_SINGLE_STEP => {
+            if buf_len < size_of::<u32>() {
+                return Err(Errno::TooBig.with_message("Buffer is too small for the registers."));
+            }
+            let single_step = process.root_vmar().read_val::<u32>(buf)? != 0;
+            thread.with_user_context(|user_ctx| user_ctx.set_single_step(single_step))??;
+            Ok(0)
+        }
+        _ => Err(Errno::InvArg.with_message("Unknown register set.")),
+    }
+}
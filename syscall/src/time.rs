@@ -0,0 +1,51 @@
+use core::time::Duration;
+
+use alloc::sync::Arc;
+use kernel_hal::timer::monotonic;
+use object::{
+    object::{Handle, Rights},
+    task::{HandleId, Process, Thread},
+    time::Timer,
+};
+use protocol::DEADLINE_INFINITE;
+
+use crate::SyscallResult;
+
+/// Converts a raw nanosecond deadline, `None` meaning no deadline at all.
+pub(crate) fn deadline_from_raw(deadline: u64) -> Option<Duration> {
+    (deadline != DEADLINE_INFINITE).then(|| Duration::from_nanos(deadline))
+}
+
+pub fn clock_get_monotonic(_process: &Arc<Process>) -> SyscallResult {
+    Ok(monotonic().as_nanos() as usize)
+}
+
+pub fn nanosleep(_process: &Arc<Process>, deadline: u64) -> SyscallResult {
+    if let Some(deadline) = deadline_from_raw(deadline) {
+        Thread::current().unwrap().sleep_until(deadline)?;
+    }
+    Ok(0)
+}
+
+pub fn new_timer(process: &Arc<Process>, handle_ptr: usize) -> SyscallResult {
+    let handle = process.add_handle(Handle::new(Timer::new(), Rights::ALL));
+    process.root_vmar().write_val(handle_ptr, &handle)?;
+    Ok(0)
+}
+
+pub fn set_timer(process: &Arc<Process>, handle: u32, deadline: u64) -> SyscallResult {
+    let timer =
+        process.find_object_with_rights::<Timer>(HandleId::from_raw(handle), Rights::WRITE)?;
+    match deadline_from_raw(deadline) {
+        Some(deadline) => timer.set(deadline),
+        None => timer.cancel(),
+    }
+    Ok(0)
+}
+
+pub fn cancel_timer(process: &Arc<Process>, handle: u32) -> SyscallResult {
+    let timer =
+        process.find_object_with_rights::<Timer>(HandleId::from_raw(handle), Rights::WRITE)?;
+    timer.cancel();
+    Ok(0)
+}
@@ -1,9 +1,16 @@
 use alloc::sync::Arc;
+use errors::{Errno, Result};
 use kernel_hal::mem::{CachePolicy, MMUFlags, PageProperty, Privilege};
 use object::{
-    mem::{Vmar, Vmo},
+    dev::{Resource, ResourceKind},
+    mem::{MapOptions, Vmar, Vmo, VmoChildKind, VmoOp},
     object::{Handle, Rights},
-    task::{HandleId, Process},
+    task::{HandleId, JobPolicy, Process},
+};
+use protocol::{
+    CACHE_POLICY_CACHED, CACHE_POLICY_UNCACHED, CACHE_POLICY_WRITE_COMBINING, VM_ALIGN_MASK,
+    VM_ALIGN_SHIFT, VM_MAP_RANGE, VM_SPECIFIC, VM_SPECIFIC_OVERWRITE, VMO_CHILD_SLICE,
+    VMO_CHILD_SNAPSHOT, VMO_CONTINUOUS, VMO_OP_COMMIT, VMO_OP_DECOMMIT, VMO_OP_ZERO, VMO_RESIZABLE,
 };
 
 use crate::SyscallResult;
@@ -14,11 +21,15 @@ pub fn allocate_vmar(
     size: usize,
     child_handle_addr: usize,
 ) -> SyscallResult {
-    let vmar =
-        process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let handle_id = HandleId::from_raw(handle);
+    let vmar = process.find_object_with_rights::<Vmar>(handle_id, Rights::MANAGE)?;
+    let rights = process
+        .get_handle_with_rights(handle_id, Rights::MANAGE)?
+        .rights;
 
+    // A child can't be used to map with more than its parent allows.
     let child = vmar.allocate_child(size)?;
-    let handle = Handle::new(child.clone(), Rights::VMAR);
+    let handle = Handle::new(child.clone(), Rights::VMAR & rights);
     let handle = process.add_handle(handle);
 
     log::debug!("new vmar: {:#x}", handle.as_raw());
@@ -34,11 +45,14 @@ pub fn allocate_vmar_at(
     size: usize,
     child_handle_addr: usize,
 ) -> SyscallResult {
-    let vmar =
-        process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let handle_id = HandleId::from_raw(handle);
+    let vmar = process.find_object_with_rights::<Vmar>(handle_id, Rights::MANAGE)?;
+    let rights = process
+        .get_handle_with_rights(handle_id, Rights::MANAGE)?
+        .rights;
 
     let child = vmar.create_child(addr, size)?;
-    let handle = Handle::new(child, Rights::VMAR);
+    let handle = Handle::new(child, Rights::VMAR & rights);
     let handle = process.add_handle(handle);
 
     process.root_vmar().write_val(child_handle_addr, &handle)?;
@@ -57,20 +71,91 @@ pub fn map_vmar(
         process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
     let vmo =
         process.find_object_with_rights::<Vmo>(HandleId::from_raw(vmo_handle), Rights::MAP)?;
+    let flags = MMUFlags::from_bits_truncate(flags);
+    check_map_rights(process, handle, vmo_handle, flags)?;
 
     vmar.map(
         offset,
         &vmo,
-        PageProperty::new(
-            MMUFlags::from_bits_truncate(flags),
-            CachePolicy::CacheCoherent,
-            Privilege::User,
-        ),
+        PageProperty::new(flags, vmo.cache_policy(), Privilege::User),
         true,
     )?;
     Ok(0)
 }
 
+pub fn vmar_map(
+    process: &Arc<Process>,
+    handle: u32,
+    options: u32,
+    vmar_offset: usize,
+    vmo_handle: u32,
+    vmo_offset: usize,
+    len: usize,
+) -> SyscallResult {
+    let vmar =
+        process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let vmo =
+        process.find_object_with_rights::<Vmo>(HandleId::from_raw(vmo_handle), Rights::MAP)?;
+
+    let perms = MMUFlags::READ | MMUFlags::WRITE | MMUFlags::EXECUTE;
+    let known = perms.bits() | VM_SPECIFIC | VM_SPECIFIC_OVERWRITE | VM_MAP_RANGE | VM_ALIGN_MASK;
+    if options & !known != 0 {
+        return Err(Errno::InvArg.with_message("Unknown map options."));
+    }
+
+    let mut map_options = match options & (VM_SPECIFIC | VM_SPECIFIC_OVERWRITE) {
+        0 => MapOptions::new(),
+        VM_SPECIFIC => MapOptions::new().specific(vmar_offset),
+        VM_SPECIFIC_OVERWRITE => MapOptions::new().specific_overwrite(vmar_offset),
+        _ => return Err(Errno::InvArg.with_message("Conflicting map options.")),
+    };
+    let align_shift = (options & VM_ALIGN_MASK) >> VM_ALIGN_SHIFT;
+    if align_shift != 0 {
+        let align = 1usize
+            .checked_shl(align_shift)
+            .ok_or(Errno::InvArg.with_message("Invalid alignment."))?;
+        map_options = map_options.align(align);
+    }
+    if options & VM_MAP_RANGE != 0 {
+        map_options = map_options.map_range();
+    }
+
+    let flags = MMUFlags::from_bits_truncate(options) & perms;
+    check_map_rights(process, handle, vmo_handle, flags)?;
+
+    let prop = PageProperty::new(flags, vmo.cache_policy(), Privilege::User);
+    let addr = vmar.map_with(map_options, &vmo, vmo_offset, len, prop)?;
+    Ok(addr)
+}
+
+/// Rejects permissions that either the VMAR or the VMO handle doesn't grant.
+fn check_map_rights(
+    process: &Arc<Process>,
+    handle: u32,
+    vmo_handle: u32,
+    flags: MMUFlags,
+) -> Result<()> {
+    let rights = map_rights(flags);
+    process.get_handle_with_rights(HandleId::from_raw(handle), rights)?;
+    process.get_handle_with_rights(HandleId::from_raw(vmo_handle), rights)?;
+    Ok(())
+}
+
+/// Returns the handle rights needed to map memory with `flags`.
+fn map_rights(flags: MMUFlags) -> Rights {
+    let mut rights = Rights::empty();
+    for (flag, right) in [
+        (MMUFlags::READ, Rights::READ),
+        (MMUFlags::WRITE, Rights::WRITE),
+        (MMUFlags::EXECUTE, Rights::EXECUTE),
+    ] {
+        if flags.contains(flag) {
+            rights |= right;
+        }
+    }
+    rights
+}
+
 pub fn unmap_vmar(process: &Arc<Process>, handle: u32, addr: usize, size: usize) -> SyscallResult {
     let vmar =
         process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
@@ -86,10 +171,12 @@ pub fn protect_vmar(
     size: usize,
     flags: u32,
 ) -> SyscallResult {
-    let vmar =
-        process.find_object_with_rights::<Vmar>(HandleId::from_raw(handle), Rights::MANAGE)?;
+    let handle_id = HandleId::from_raw(handle);
+    let vmar = process.find_object_with_rights::<Vmar>(handle_id, Rights::MANAGE)?;
+    let flags = MMUFlags::from_bits_truncate(flags);
+    process.get_handle_with_rights(handle_id, map_rights(flags))?;
 
-    vmar.protect(addr, size, MMUFlags::from_bits_truncate(flags))?;
+    vmar.protect(addr, size, flags)?;
     Ok(0)
 }
 
@@ -108,13 +195,21 @@ pub fn get_vmar_size(process: &Arc<Process>, handle: u32) -> SyscallResult {
 pub fn allocate_vmo(
     process: &Arc<Process>,
     count: usize,
-    continuous: bool,
+    options: u32,
     handle_addr: usize,
+    resource: u32,
 ) -> SyscallResult {
-    let vmo = if continuous {
-        Vmo::allocate_continuous(count)?
-    } else {
-        Vmo::allocate_ram(count)?
+    let vmo = match options {
+        0 => Vmo::allocate_ram(count)?,
+        VMO_CONTINUOUS => {
+            // Contiguous memory is meant for DMA, which bypasses the MMU.
+            process
+                .find_object_with_rights::<Resource>(HandleId::from_raw(resource), Rights::empty())?
+                .validate_root()?;
+            Vmo::allocate_continuous(count)?
+        }
+        VMO_RESIZABLE => Vmo::allocate_resizable(count)?,
+        _ => return Err(Errno::InvArg.with_message("Invalid VMO options.")),
     };
     let handle = Handle::new(vmo, Rights::VMO);
     let handle = process.add_handle(handle);
@@ -126,10 +221,15 @@ pub fn allocate_vmo(
 
 pub fn acquire_vmo(
     process: &Arc<Process>,
+    resource: u32,
     handle_ptr: usize,
     addr: usize,
     size: usize,
 ) -> SyscallResult {
+    process.job().check_policy(JobPolicy::DENY_ACQUIRE_VMO)?;
+    process
+        .find_object_with_rights::<Resource>(HandleId::from_raw(resource), Rights::empty())?
+        .validate(ResourceKind::Memory, addr, size)?;
     let vmo = Vmo::acquire_iomem(addr, size)?;
     let handle = Handle::new(vmo, Rights::VMO);
     let handle = process.add_handle(handle);
@@ -139,6 +239,33 @@ pub fn acquire_vmo(
     Ok(0)
 }
 
+pub fn create_vmo_child(
+    process: &Arc<Process>,
+    handle: u32,
+    options: u32,
+    offset: usize,
+    size: usize,
+    child_handle_addr: usize,
+) -> SyscallResult {
+    let handle_id = HandleId::from_raw(handle);
+    let rights = Rights::READ | Rights::DUPLICATE;
+    let vmo = process.find_object_with_rights::<Vmo>(handle_id, rights)?;
+    let rights = process.get_handle_with_rights(handle_id, rights)?.rights;
+
+    // A slice keeps the rights of its parent, a snapshot is the caller's own copy.
+    let (kind, rights) = match options {
+        VMO_CHILD_SLICE => (VmoChildKind::Slice, rights),
+        VMO_CHILD_SNAPSHOT => (VmoChildKind::Snapshot, rights | Rights::WRITE),
+        _ => return Err(Errno::InvArg.with_message("Invalid VMO child options.")),
+    };
+
+    let child = vmo.create_child(kind, offset, size)?;
+    let handle = process.add_handle(Handle::new(child, rights));
+    process.root_vmar().write_val(child_handle_addr, &handle)?;
+
+    Ok(0)
+}
+
 pub fn read_vmo(
     process: &Arc<Process>,
     handle: u32,
@@ -171,10 +298,52 @@ pub fn write_vmo(
     Ok(0)
 }
 
-pub fn get_vmo_paddr(process: &Arc<Process>, handle: u32) -> SyscallResult {
+pub fn set_vmo_size(process: &Arc<Process>, handle: u32, size: usize) -> SyscallResult {
+    let vmo = process.find_object_with_rights::<Vmo>(HandleId::from_raw(handle), Rights::WRITE)?;
+
+    vmo.set_size(size)?;
+    Ok(0)
+}
+
+pub fn vmo_op_range(
+    process: &Arc<Process>,
+    handle: u32,
+    op: u32,
+    offset: usize,
+    len: usize,
+) -> SyscallResult {
+    let vmo = process.find_object_with_rights::<Vmo>(HandleId::from_raw(handle), Rights::WRITE)?;
+
+    let op = match op {
+        VMO_OP_COMMIT => VmoOp::Commit,
+        VMO_OP_DECOMMIT => VmoOp::Decommit,
+        VMO_OP_ZERO => VmoOp::Zero,
+        _ => return Err(Errno::InvArg.with_message("Unknown VMO operation.")),
+    };
+    vmo.op_range(op, offset, len)?;
+    Ok(0)
+}
+
+pub fn set_vmo_cache_policy(process: &Arc<Process>, handle: u32, policy: u32) -> SyscallResult {
+    let vmo = process.find_object_with_rights::<Vmo>(HandleId::from_raw(handle), Rights::MAP)?;
+
+    let policy = match policy {
+        CACHE_POLICY_CACHED => CachePolicy::CacheCoherent,
+        CACHE_POLICY_UNCACHED => CachePolicy::StronglyOrderedUnCached,
+        CACHE_POLICY_WRITE_COMBINING => CachePolicy::WeaklyOrderedUnCached,
+        _ => return Err(Errno::InvArg.with_message("Unknown cache policy.")),
+    };
+    vmo.set_cache_policy(policy)?;
+    Ok(0)
+}
+
+pub fn get_vmo_paddr(process: &Arc<Process>, resource: u32, handle: u32) -> SyscallResult {
     let vmo = process.find_object_with_rights::<Vmo>(HandleId::from_raw(handle), Rights::READ)?;
 
-    let paddr = unsafe { vmo.start() };
+    let paddr = vmo.physical_address(0)?;
+    process
+        .find_object_with_rights::<Resource>(HandleId::from_raw(resource), Rights::empty())?
+        .validate(ResourceKind::Memory, paddr, vmo.len())?;
 
     Ok(paddr)
 }
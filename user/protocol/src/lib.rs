@@ -9,11 +9,13 @@ pub const PROC_START_HANDLE_CNT: usize = 2;
 pub const PROC_HANDLE_IDX: usize = 0;
 pub const VMAR_HANDLE_IDX: usize = 1;
 
-pub const BOOT_HANDLE_CNT: usize = 3;
+pub const BOOT_HANDLE_CNT: usize = 5;
 
 pub const BOOT_TERM_HANDLE_IDX: usize = 0;
 pub const BOOT_FB_HANDLE_IDX: usize = 1;
 pub const BOOT_PCIE_HANDLE_IDX: usize = 2;
+pub const BOOT_JOB_HANDLE_IDX: usize = 3;
+pub const BOOT_RESOURCE_HANDLE_IDX: usize = 4;
 
 pub const BOOT_DATA_CNT: usize = 4;
 
@@ -22,6 +24,9 @@ pub const FB_WIDTH_IDX: usize = 1;
 pub const FB_HEIGHT_IDX: usize = 2;
 pub const PCIE_INFO_LEN_IDX: usize = 3;
 
+/// Waits with this deadline never time out.
+pub const DEADLINE_INFINITE: u64 = u64::MAX;
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod)]
 pub struct ProcessStartInfo {
@@ -53,3 +58,244 @@ pub struct WriteBuffer {
     pub addr: usize,
     pub len: usize,
 }
+
+pub const RESOURCE_KIND_MEMORY: u32 = 1;
+pub const RESOURCE_KIND_IRQ: u32 = 2;
+
+/// Options of `allocate_vmo`.
+pub const VMO_CONTINUOUS: u32 = 1 << 0;
+pub const VMO_RESIZABLE: u32 = 1 << 1;
+
+/// Operations of `vmo_op_range`.
+pub const VMO_OP_COMMIT: u32 = 1;
+/// Frees the pages covering the range, which then read as zero.
+pub const VMO_OP_DECOMMIT: u32 = 2;
+pub const VMO_OP_ZERO: u32 = 3;
+
+/// Cache policies of `vmo_set_cache_policy`.
+pub const CACHE_POLICY_CACHED: u32 = 0;
+/// Strongly ordered and uncached, for device registers.
+pub const CACHE_POLICY_UNCACHED: u32 = 1;
+/// Weakly ordered and uncached, so that writes can be merged, for framebuffers.
+pub const CACHE_POLICY_WRITE_COMBINING: u32 = 2;
+
+/// Child VMO sharing pages with its parent until either side writes them.
+pub const VMO_CHILD_SNAPSHOT: u32 = 1 << 0;
+/// Child VMO that is a window of its parent, sharing all writes.
+pub const VMO_CHILD_SLICE: u32 = 1 << 1;
+
+/// Options of `vmar_map`, next to the `MMUFlags` bits of the mapping.
+/// Maps at the given VMAR offset, which must be free.
+pub const VM_SPECIFIC: u32 = 1 << 16;
+/// Maps at the given VMAR offset, replacing the mappings there.
+pub const VM_SPECIFIC_OVERWRITE: u32 = 1 << 17;
+/// Commits and maps every page up front.
+pub const VM_MAP_RANGE: u32 = 1 << 18;
+/// Log2 of the alignment of a range the kernel chooses, 0 meaning a page.
+pub const VM_ALIGN_SHIFT: u32 = 24;
+pub const VM_ALIGN_MASK: u32 = 0x3f << VM_ALIGN_SHIFT;
+
+pub const BTI_PERM_READ: u32 = 1 << 0;
+pub const BTI_PERM_WRITE: u32 = 1 << 1;
+
+/// The range of a VMO to pin with `bti_pin`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct PinRequest {
+    pub vmo: u32,
+    /// `BTI_PERM_*` flags, which the VMO handle's rights must allow.
+    pub perms: u32,
+    pub offset: usize,
+    pub len: usize,
+}
+
+pub const INFO_HANDLE_BASIC: u32 = 1;
+pub const INFO_PROCESS_THREADS: u32 = 2;
+pub const INFO_VMAR_MAPS: u32 = 3;
+pub const INFO_VMO: u32 = 4;
+pub const INFO_THREAD: u32 = 5;
+pub const INFO_PROCESS: u32 = 6;
+
+pub const OBJECT_TYPE_NAME_LEN: usize = 32;
+
+/// The record of `INFO_HANDLE_BASIC`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct HandleBasicInfo {
+    pub koid: u64,
+    /// The koid of the peer, process or parent job, or 0 if there is none.
+    pub related_koid: u64,
+    pub rights: u32,
+    pub reserved: u32,
+    /// The type name of the object, padded with zeros.
+    pub type_name: [u8; OBJECT_TYPE_NAME_LEN],
+}
+
+/// One record of `INFO_VMAR_MAPS`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct MapInfo {
+    pub base: usize,
+    pub size: usize,
+    pub vmo_koid: u64,
+    /// The same flags `map_vmar` takes.
+    pub perm: u32,
+    pub reserved: u32,
+}
+
+/// The record of `INFO_VMO`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct VmoInfo {
+    pub koid: u64,
+    pub size: usize,
+    pub committed_pages: usize,
+}
+
+pub const THREAD_STATE_RUNNING: u32 = 0;
+pub const THREAD_STATE_READY: u32 = 1;
+pub const THREAD_STATE_BLOCKED: u32 = 2;
+pub const THREAD_STATE_DEAD: u32 = 3;
+
+/// The record of `INFO_THREAD`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ThreadInfo {
+    /// One of `THREAD_STATE_*`.
+    pub state: u32,
+    pub reserved: u32,
+}
+
+pub const PROCESS_STATE_CREATED: u32 = 0;
+pub const PROCESS_STATE_RUNNING: u32 = 1;
+pub const PROCESS_STATE_DYING: u32 = 2;
+pub const PROCESS_STATE_DEAD: u32 = 3;
+
+/// The record of `INFO_PROCESS`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ProcessInfo {
+    /// The exit status, meaningful once the process is dying.
+    pub return_code: i64,
+    /// One of `PROCESS_STATE_*`.
+    pub state: u32,
+    pub reserved: u32,
+}
+
+pub const PROP_NAME: u32 = 1;
+/// A `usize`, the number of bytes of a VMO holding data.
+pub const PROP_VMO_CONTENT_SIZE: u32 = 2;
+
+/// Longer names are truncated by `object_set_property`.
+pub const MAX_NAME_LEN: usize = 32;
+
+/// The register set read and written as [`ThreadGeneralRegs`].
+pub const THREAD_REGS_GENERAL: u32 = 0;
+
+/// The general register set of a stopped thread.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ThreadGeneralRegs {
+    /// `r0` to `r31`.
+    pub regs: [usize; 32],
+    pub era: usize,
+    /// Read only, ignored by `thread_write_state`.
+    pub prmd: usize,
+}
+
+/// The register set read and written as [`ThreadDebugRegs`].
+pub const THREAD_REGS_DEBUG: u32 = 1;
+/// A `u32`, non-zero while the thread traps after every instruction.
+pub const THREAD_REGS_SINGLE_STEP: u32 = 2;
+
+pub const HW_BREAKPOINT_COUNT: usize = 4;
+pub const WATCHPOINT_COUNT: usize = 4;
+
+pub const WATCH_READ: usize = 1 << 0;
+pub const WATCH_WRITE: usize = 1 << 1;
+
+/// A data watchpoint, unused while `flags` is 0.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct WatchpointRegs {
+    pub addr: usize,
+    /// 1, 2, 4 or 8, with `addr` aligned to it.
+    pub len: usize,
+    /// `WATCH_READ` and `WATCH_WRITE`.
+    pub flags: usize,
+}
+
+/// The hardware breakpoints and watchpoints of a thread.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ThreadDebugRegs {
+    /// Instruction addresses, unused while 0.
+    pub breakpoints: [usize; HW_BREAKPOINT_COUNT],
+    pub watchpoints: [WatchpointRegs; WATCHPOINT_COUNT],
+}
+
+pub const EXCEPTION_STATE_TRY_NEXT: u32 = 0;
+pub const EXCEPTION_STATE_RESUME: u32 = 1;
+pub const EXCEPTION_STATE_KILL: u32 = 2;
+
+pub const EXCEPTION_KIND_GENERAL: usize = 0;
+pub const EXCEPTION_KIND_PAGE_FAULT: usize = 1;
+/// A `break` instruction, `pc` points at it and has to be moved past it to resume.
+pub const EXCEPTION_KIND_SW_BREAKPOINT: usize = 2;
+pub const EXCEPTION_KIND_HW_BREAKPOINT: usize = 3;
+pub const EXCEPTION_KIND_SINGLE_STEP: usize = 4;
+/// `badv` holds the accessed address.
+pub const EXCEPTION_KIND_WATCHPOINT: usize = 5;
+
+/// The data of a message read from an exception channel,
+/// which also carries the exception handle.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct ExceptionReport {
+    /// One of `EXCEPTION_KIND_*`.
+    pub kind: usize,
+    /// The architecture's exception code, `ESTAT.Ecode` on LoongArch.
+    pub code: usize,
+    pub badv: usize,
+    pub pc: usize,
+    pub pid: u64,
+    pub tid: u64,
+    /// General registers, in the order of the architecture.
+    pub regs: [usize; 32],
+}
+
+pub const SOCKET_STREAM: u32 = 0;
+pub const SOCKET_DATAGRAM: u32 = 1;
+
+pub const SOCKET_SHUTDOWN_WRITE: u32 = 1 << 0;
+pub const SOCKET_SHUTDOWN_READ: u32 = 1 << 1;
+
+pub const PORT_PACKET_USER: u64 = 0;
+pub const PORT_PACKET_SIGNAL: u64 = 1;
+pub const PORT_PACKET_INTERRUPT: u64 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct PortPacket {
+    pub key: u64,
+    pub kind: u64,
+    pub payload: [u8; 32],
+}
+
+/// The payload of a `PORT_PACKET_SIGNAL` packet.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct SignalPacket {
+    pub trigger: u32,
+    pub observed: u32,
+    pub reserved: [u64; 3],
+}
+
+/// The payload of a `PORT_PACKET_INTERRUPT` packet.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct InterruptPacket {
+    /// Monotonic time in nanoseconds when the line fired.
+    pub timestamp: u64,
+    pub reserved: [u64; 3],
+}
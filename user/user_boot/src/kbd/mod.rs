@@ -1,14 +1,21 @@
 use core::ptr::NonNull;
 
 use pod::{FromBytes, Immutable, IntoBytes, derive};
-use spin::Mutex;
-use ustd::vm::{MMUFlags, Vmar, Vmo};
+use spin::{Lazy, Mutex};
+use ustd::{
+    dev::Bti,
+    vm::{MMUFlags, Vmar, Vmo},
+};
 use virtio_drivers::{
     Error, Hal, PAGE_SIZE, PhysAddr,
     device::input::VirtIOInput,
     transport::{DeviceStatus, DeviceType, InterruptStatus, Transport, mmio::MmioVersion},
 };
 
+use crate::ROOT_RESOURCE;
+
+static BTI: Lazy<Bti> = Lazy::new(|| Bti::new(ROOT_RESOURCE.get().unwrap()).unwrap());
+
 pub fn init(vmo: Vmo) {
     VirtIOInput::<HalImpl, TransportImpl>::new(TransportImpl::new(vmo))
         .unwrap()
@@ -22,11 +29,14 @@ unsafe impl Hal for HalImpl {
         pages: usize,
         _direction: virtio_drivers::BufferDirection,
     ) -> (PhysAddr, core::ptr::NonNull<u8>) {
-        let vmo = Vmo::allocate_continuous(pages).unwrap();
+        let vmo = Vmo::allocate_continuous(ROOT_RESOURCE.get().unwrap(), pages).unwrap();
         let vmar = Vmar::root().allocate(vmo.len()).unwrap();
         vmar.map(0, &vmo, MMUFlags::DATA).unwrap();
+        let (pmt, addrs) = BTI.pin(&vmo, 0, vmo.len(), true, true).unwrap();
+        // `dma_dealloc` never unpins, so the pages stay pinned for good.
+        core::mem::forget(pmt);
         (
-            vmo.start().unwrap() as PhysAddr,
+            addrs[0] as PhysAddr,
             NonNull::new(vmar.base() as *mut u8).unwrap(),
         )
     }
@@ -8,10 +8,12 @@ extern crate alloc;
 use alloc::vec::Vec;
 use pci_types::device_type::DeviceType;
 use protocol::{
-    BOOT_FB_HANDLE_IDX, BOOT_PCIE_HANDLE_IDX, FB_HEIGHT_IDX, FB_WIDTH_IDX, PCIE_INFO_LEN_IDX,
+    BOOT_FB_HANDLE_IDX, BOOT_PCIE_HANDLE_IDX, BOOT_RESOURCE_HANDLE_IDX, FB_HEIGHT_IDX,
+    FB_WIDTH_IDX, PCIE_INFO_LEN_IDX,
 };
 use spin::{Lazy, Once};
 use ustd::{
+    dev::Resource,
     ipc::{Channel, MessagePacket},
     os::raca::OwnedHandle,
     vm::Vmo,
@@ -25,6 +27,7 @@ mod shell;
 mod terminal;
 
 static PCIE_INFO_VMO: Once<Vmo> = Once::new();
+static ROOT_RESOURCE: Once<Resource> = Once::new();
 
 #[unsafe(no_mangle)]
 pub extern "Rust" fn main(channel: &Channel) -> i32 {
@@ -45,6 +48,13 @@ pub extern "Rust" fn main(channel: &Channel) -> i32 {
     };
     terminal::init(fb_vmo, fb_width, fb_height);
 
+    let root_resource = unsafe {
+        Resource::from_handle(OwnedHandle::from_raw(
+            handles[BOOT_RESOURCE_HANDLE_IDX].as_raw(),
+        ))
+    };
+    ROOT_RESOURCE.call_once(|| root_resource);
+
     termpln!("Hello World");
 
     let pcie_info_len = data[PCIE_INFO_LEN_IDX];
@@ -65,7 +75,7 @@ pub extern "Rust" fn main(channel: &Channel) -> i32 {
     termpln!("device bars: {:x?}", device.bars);
     let bar = device.bars[4].unwrap();
     let (address, size) = bar.unwrap_mem();
-    let vmo = Vmo::acquire(address, size).unwrap();
+    let vmo = Vmo::acquire(ROOT_RESOURCE.get().unwrap(), address, size).unwrap();
     kbd::init(vmo);
 
     core::mem::forget(handles);
@@ -13,7 +13,7 @@ use pci_types::*;
 use spin::{Lazy, Mutex};
 use ustd::vm::Vmo;
 
-use crate::{PCIE_INFO_VMO, termpln};
+use crate::{PCIE_INFO_VMO, ROOT_RESOURCE, termpln};
 
 pub static PCI_DEVICES: Lazy<Mutex<Vec<PciDevice>>> = Lazy::new(|| {
     let pci_region_vmo = PCIE_INFO_VMO.get().unwrap();
@@ -65,7 +65,9 @@ impl<'a> PciAccess<'a> {
             let mut inner = self.1.lock();
             let entry = inner.entry(address);
             entry
-                .insert_entry(Arc::new(Vmo::acquire(physical_address, 0x1000).unwrap()))
+                .insert_entry(Arc::new(
+                    Vmo::acquire(ROOT_RESOURCE.get().unwrap(), physical_address, 0x1000).unwrap(),
+                ))
                 .get()
                 .clone()
         }
@@ -0,0 +1,95 @@
+use alloc::vec::Vec;
+use errors::Result;
+use protocol::{BTI_PERM_READ, BTI_PERM_WRITE, PinRequest};
+
+use crate::{
+    dev::Resource,
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_bti_pin, sys_bti_release_quarantine, sys_new_bti, sys_pmt_unpin},
+    vm::{PAGE_SIZE, Vmo},
+};
+
+/// A device that can access memory on its own.
+pub struct Bti(pub(crate) OwnedHandle);
+
+impl Bti {
+    /// Creates a BTI, which requires the root resource.
+    pub fn new(resource: &Resource) -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_bti(resource.0.as_raw(), &mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+impl Bti {
+    /// Pins `offset..offset + len` of `vmo` for the device to read and/or write.
+    /// Returns the pinned memory token and the device address of each page.
+    pub fn pin(
+        &self,
+        vmo: &Vmo,
+        offset: usize,
+        len: usize,
+        read: bool,
+        write: bool,
+    ) -> Result<(Pmt, Vec<usize>)> {
+        let mut perms = 0;
+        if read {
+            perms |= BTI_PERM_READ;
+        }
+        if write {
+            perms |= BTI_PERM_WRITE;
+        }
+        let request = PinRequest {
+            vmo: unsafe { vmo.handle().as_raw() },
+            perms,
+            offset,
+            len,
+        };
+
+        let mut addrs = alloc::vec![0; len.div_ceil(PAGE_SIZE)];
+        let mut raw_handle = 0;
+        unsafe {
+            sys_bti_pin(
+                self.0.as_raw(),
+                &request,
+                addrs.as_mut_ptr(),
+                &mut raw_handle,
+            )?;
+            Ok((Pmt(OwnedHandle::from_raw(raw_handle)), addrs))
+        }
+    }
+
+    /// Unpins the memory of every token dropped without `unpin`,
+    /// once the device is known to be done with it.
+    pub fn release_quarantine(&self) -> Result<()> {
+        unsafe {
+            sys_bti_release_quarantine(self.0.as_raw())?;
+        }
+        Ok(())
+    }
+}
+
+/// A pinned memory token. Dropping it without `unpin` leaves the memory
+/// quarantined in the BTI, since the device may still be using it.
+pub struct Pmt(pub(crate) OwnedHandle);
+
+impl Pmt {
+    pub fn unpin(self) -> Result<()> {
+        unsafe {
+            sys_pmt_unpin(self.0.as_raw())?;
+        }
+        // The kernel has already closed the handle.
+        core::mem::forget(self);
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use errors::Result;
+
+use crate::{
+    dev::Resource,
+    ipc::Port,
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_interrupt_ack, sys_interrupt_bind, sys_interrupt_wait, sys_new_interrupt},
+    time::Instant,
+};
+
+/// A hardware interrupt line. The line is masked each time it fires,
+/// until the driver calls `ack`.
+pub struct Interrupt(pub(crate) OwnedHandle);
+
+impl Interrupt {
+    /// Binds `vector`, which `resource` must cover.
+    pub fn new(resource: &Resource, vector: usize) -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_interrupt(resource.0.as_raw(), vector, &mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+impl Interrupt {
+    /// Blocks until the line fires or `deadline` passes, and returns when it fired.
+    pub fn wait(&self, deadline: u64) -> Result<Instant> {
+        let mut timestamp = 0;
+        unsafe {
+            sys_interrupt_wait(self.0.as_raw(), deadline, &mut timestamp)?;
+        }
+        Ok(Instant::from_nanos(timestamp))
+    }
+
+    /// Unmasks the line once the device has been serviced.
+    pub fn ack(&self) -> Result<()> {
+        unsafe {
+            sys_interrupt_ack(self.0.as_raw())?;
+        }
+        Ok(())
+    }
+
+    /// Delivers further interrupts to `port` as packets with `key`, instead of to `wait`.
+    pub fn bind(&self, port: &Port, key: u64) -> Result<()> {
+        unsafe {
+            sys_interrupt_bind(self.0.as_raw(), port.0.as_raw(), key)?;
+        }
+        Ok(())
+    }
+}
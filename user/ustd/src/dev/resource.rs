@@ -0,0 +1,38 @@
+use errors::Result;
+use protocol::{RESOURCE_KIND_IRQ, RESOURCE_KIND_MEMORY};
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::sys_new_resource,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Memory,
+    Irq,
+}
+
+pub struct Resource(pub(crate) OwnedHandle);
+
+impl Resource {
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+
+    /// Creates a resource for a sub-range this resource covers.
+    pub fn new_child(&self, kind: ResourceKind, base: usize, size: usize) -> Result<Self> {
+        let kind = match kind {
+            ResourceKind::Memory => RESOURCE_KIND_MEMORY,
+            ResourceKind::Irq => RESOURCE_KIND_IRQ,
+        };
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_resource(self.0.as_raw(), kind, base, size, &mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+}
@@ -5,10 +5,10 @@ use alloc::{
     vec::Vec,
 };
 use errors::Result;
-use protocol::{ReadBuffer, WriteBuffer};
+use protocol::{DEADLINE_INFINITE, ReadBuffer, WriteBuffer};
 
 use crate::{
-    os::raca::OwnedHandle,
+    os::raca::{BorrowedHandle, OwnedHandle, Signal},
     syscall::{sys_new_channel, sys_read_channel, sys_write_channel},
 };
 
@@ -36,6 +36,10 @@ impl Channel {
     pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
         Self(handle)
     }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
 }
 
 impl Channel {
@@ -84,6 +88,14 @@ impl Channel {
         Ok(MessagePacket { data, handles })
     }
 
+    /// Blocks until a message arrives or the peer is closed, then reads.
+    pub fn read_blocking(&self) -> Result<MessagePacket> {
+        self.0
+            .borrow()
+            .wait_one(Signal::READABLE | Signal::PEER_CLOSED, DEADLINE_INFINITE)?;
+        self.read()
+    }
+
     pub fn write(&self, packet: MessagePacket) -> Result<()> {
         let MessagePacket { data, handles } = packet;
         let mut raw_handles = Vec::with_capacity(handles.len());
@@ -0,0 +1,50 @@
+use errors::Result;
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_new_event, sys_new_event_pair},
+};
+
+pub struct Event(pub(crate) OwnedHandle);
+
+impl Event {
+    pub fn new() -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_event(&mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+pub struct EventPair(pub(crate) OwnedHandle);
+
+impl EventPair {
+    pub fn new() -> Result<(Self, Self)> {
+        let mut raw_handle0 = 0;
+        let mut raw_handle1 = 0;
+        unsafe {
+            sys_new_event_pair(&mut raw_handle0, &mut raw_handle1)?;
+            Ok((
+                Self::from_handle(OwnedHandle::from_raw(raw_handle0)),
+                Self::from_handle(OwnedHandle::from_raw(raw_handle1)),
+            ))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
@@ -0,0 +1,71 @@
+use core::marker::PhantomData;
+
+use errors::Result;
+use pod::Pod;
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_new_fifo, sys_read_fifo, sys_write_fifo},
+};
+
+/// A queue of fixed-size `T` records shared with a peer.
+pub struct Fifo<T: Pod> {
+    handle: OwnedHandle,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Fifo<T> {
+    pub fn new(elem_count: usize) -> Result<(Self, Self)> {
+        let mut raw_handle0 = 0;
+        let mut raw_handle1 = 0;
+        unsafe {
+            sys_new_fifo(
+                elem_count,
+                size_of::<T>(),
+                &mut raw_handle0,
+                &mut raw_handle1,
+            )?;
+            Ok((
+                Self::from_handle(OwnedHandle::from_raw(raw_handle0)),
+                Self::from_handle(OwnedHandle::from_raw(raw_handle1)),
+            ))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self {
+            handle,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.handle.borrow()
+    }
+}
+
+impl<T: Pod> Fifo<T> {
+    /// Reads into `buffer`, returning the number of elements read.
+    pub fn read(&self, buffer: &mut [T]) -> Result<usize> {
+        unsafe {
+            sys_read_fifo(
+                self.handle.as_raw(),
+                size_of::<T>(),
+                buffer.as_mut_ptr() as *mut u8,
+                buffer.len(),
+            )
+        }
+    }
+
+    /// Writes as many of `elems` as fit, returning the number written.
+    pub fn write(&self, elems: &[T]) -> Result<usize> {
+        unsafe {
+            sys_write_fifo(
+                self.handle.as_raw(),
+                size_of::<T>(),
+                elems.as_ptr() as *const u8,
+                elems.len(),
+            )
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use errors::Result;
+use pod::Pod;
+use protocol::{
+    InterruptPacket, PORT_PACKET_INTERRUPT, PORT_PACKET_SIGNAL, PORT_PACKET_USER, SignalPacket,
+};
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle, Signal},
+    syscall::{
+        sys_new_port, sys_object_wait_async, sys_port_cancel, sys_port_queue, sys_port_wait,
+    },
+    time::Instant,
+};
+
+pub struct Port(pub(crate) OwnedHandle);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortPacket {
+    pub key: u64,
+    pub payload: PacketPayload,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketPayload {
+    Signal { trigger: Signal, observed: Signal },
+    User([u8; 32]),
+    Interrupt { timestamp: Instant },
+}
+
+impl Port {
+    pub fn new() -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_port(&mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+}
+
+impl Port {
+    /// Queues a packet with `key` once `handle` asserts any of `signals`.
+    /// The registration fires only once, unless cancelled first with [`Port::cancel`].
+    pub fn wait_async(&self, handle: BorrowedHandle, key: u64, signals: Signal) -> Result<()> {
+        unsafe {
+            sys_object_wait_async(handle.as_raw(), self.0.as_raw(), key, signals.bits())?;
+        }
+        Ok(())
+    }
+
+    /// Cancels the registrations on `handle` with `key` that haven't fired yet.
+    pub fn cancel(&self, handle: BorrowedHandle, key: u64) -> Result<()> {
+        unsafe {
+            sys_port_cancel(self.0.as_raw(), handle.as_raw(), key)?;
+        }
+        Ok(())
+    }
+
+    /// Dequeues a packet, waiting until `deadline` if the port is empty.
+    pub fn wait(&self, deadline: u64) -> Result<PortPacket> {
+        let mut raw_packet = protocol::PortPacket {
+            key: 0,
+            kind: 0,
+            payload: [0; 32],
+        };
+        unsafe {
+            sys_port_wait(self.0.as_raw(), deadline, &mut raw_packet)?;
+        }
+
+        let payload = match raw_packet.kind {
+            PORT_PACKET_SIGNAL => {
+                let signal_packet = SignalPacket::from_bytes(&raw_packet.payload);
+                PacketPayload::Signal {
+                    trigger: Signal::from_bits_truncate(signal_packet.trigger),
+                    observed: Signal::from_bits_truncate(signal_packet.observed),
+                }
+            }
+            PORT_PACKET_INTERRUPT => {
+                let interrupt_packet = InterruptPacket::from_bytes(&raw_packet.payload);
+                PacketPayload::Interrupt {
+                    timestamp: Instant::from_nanos(interrupt_packet.timestamp),
+                }
+            }
+            _ => PacketPayload::User(raw_packet.payload),
+        };
+        Ok(PortPacket {
+            key: raw_packet.key,
+            payload,
+        })
+    }
+
+    pub fn queue(&self, key: u64, payload: [u8; 32]) -> Result<()> {
+        unsafe {
+            sys_port_queue(
+                self.0.as_raw(),
+                &protocol::PortPacket {
+                    key,
+                    kind: PORT_PACKET_USER,
+                    payload,
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
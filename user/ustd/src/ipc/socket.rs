@@ -0,0 +1,69 @@
+use errors::Result;
+use protocol::{SOCKET_DATAGRAM, SOCKET_SHUTDOWN_READ, SOCKET_SHUTDOWN_WRITE, SOCKET_STREAM};
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_new_socket, sys_read_socket, sys_shutdown_socket, sys_write_socket},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketType {
+    Stream,
+    Datagram,
+}
+
+pub struct Socket(pub(crate) OwnedHandle);
+
+impl Socket {
+    pub fn new(socket_type: SocketType) -> Result<(Self, Self)> {
+        let socket_type = match socket_type {
+            SocketType::Stream => SOCKET_STREAM,
+            SocketType::Datagram => SOCKET_DATAGRAM,
+        };
+        let mut raw_handle0 = 0;
+        let mut raw_handle1 = 0;
+        unsafe {
+            sys_new_socket(socket_type, &mut raw_handle0, &mut raw_handle1)?;
+            Ok((
+                Self::from_handle(OwnedHandle::from_raw(raw_handle0)),
+                Self::from_handle(OwnedHandle::from_raw(raw_handle1)),
+            ))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+impl Socket {
+    /// Reads available bytes, or the next datagram truncated to `buffer`.
+    /// Returns the number of bytes read.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize> {
+        unsafe { sys_read_socket(self.0.as_raw(), buffer.as_mut_ptr(), buffer.len()) }
+    }
+
+    /// Writes as much of `data` as the peer has room for.
+    /// Returns the number of bytes written.
+    pub fn write(&self, data: &[u8]) -> Result<usize> {
+        unsafe { sys_write_socket(self.0.as_raw(), data.as_ptr(), data.len()) }
+    }
+
+    pub fn shutdown(&self, read: bool, write: bool) -> Result<()> {
+        let mut options = 0;
+        if read {
+            options |= SOCKET_SHUTDOWN_READ;
+        }
+        if write {
+            options |= SOCKET_SHUTDOWN_WRITE;
+        }
+        unsafe {
+            sys_shutdown_socket(self.0.as_raw(), options)?;
+        }
+        Ok(())
+    }
+}
@@ -8,12 +8,15 @@ pub use stdio::_print;
 
 extern crate alloc;
 
+pub mod dev;
 pub mod ipc;
 pub mod os;
 pub mod process;
 mod stdio;
+pub mod sync;
 pub mod syscall;
 pub mod thread;
+pub mod time;
 pub mod vm;
 
 pub fn debug(msg: &str) -> Result<()> {
@@ -1,4 +1,16 @@
-use crate::syscall::{sys_duplicate_handle, sys_remove_handle};
+use alloc::{string::String, vec::Vec};
+use errors::Result;
+use pod::Pod;
+use protocol::{HandleBasicInfo, INFO_HANDLE_BASIC, MAX_NAME_LEN, PROP_NAME};
+
+use crate::{
+    os::raca::{Rights, Signal},
+    syscall::{
+        sys_duplicate_handle, sys_object_get_info, sys_object_get_property,
+        sys_object_set_property, sys_object_signal, sys_object_signal_peer, sys_object_wait_one,
+        sys_remove_handle, sys_replace_handle,
+    },
+};
 
 pub type RawHandle = u32;
 
@@ -19,6 +31,18 @@ impl OwnedHandle {
     pub fn borrow(&self) -> BorrowedHandle {
         BorrowedHandle(self.0)
     }
+
+    /// Swaps this handle for one with `rights`, which must not add any.
+    /// The handle is closed even if that fails.
+    pub fn replace(self, rights: Rights) -> Result<OwnedHandle> {
+        let raw = self.0;
+        core::mem::forget(self);
+        let mut new_handle = 0;
+        unsafe {
+            sys_replace_handle(raw, rights.bits(), &mut new_handle)?;
+        }
+        Ok(OwnedHandle(new_handle))
+    }
 }
 
 impl Drop for OwnedHandle {
@@ -33,7 +57,8 @@ impl Clone for OwnedHandle {
     fn clone(&self) -> Self {
         unsafe {
             let mut new_handle = 0;
-            sys_duplicate_handle(self.as_raw(), &mut new_handle).unwrap();
+            sys_duplicate_handle(self.as_raw(), Rights::SAME_RIGHTS.bits(), &mut new_handle)
+                .unwrap();
             Self(new_handle)
         }
     }
@@ -54,10 +79,106 @@ impl BorrowedHandle {
 
 impl BorrowedHandle {
     pub fn duplicate(&self) -> OwnedHandle {
+        self.duplicate_with_rights(Rights::SAME_RIGHTS).unwrap()
+    }
+
+    /// Duplicates the handle with `rights`, which must not add any.
+    pub fn duplicate_with_rights(&self, rights: Rights) -> Result<OwnedHandle> {
+        let mut new_handle = 0;
         unsafe {
-            let mut new_handle = 0;
-            sys_duplicate_handle(self.as_raw(), &mut new_handle).unwrap();
-            OwnedHandle(new_handle)
+            sys_duplicate_handle(self.as_raw(), rights.bits(), &mut new_handle)?;
+        }
+        Ok(OwnedHandle(new_handle))
+    }
+
+    /// Waits until the object asserts any of `signals` or `deadline` passes.
+    /// Use `DEADLINE_INFINITE` to wait forever and 0 to poll.
+    pub fn wait_one(&self, signals: Signal, deadline: u64) -> Result<Signal> {
+        let mut observed = 0u32;
+        unsafe {
+            sys_object_wait_one(self.as_raw(), signals.bits(), deadline, &mut observed)?;
+        }
+        Ok(Signal::from_bits_truncate(observed))
+    }
+
+    /// Clears and then sets user signals on the object.
+    pub fn signal(&self, clear: Signal, set: Signal) -> Result<()> {
+        unsafe {
+            sys_object_signal(self.as_raw(), clear.bits(), set.bits())?;
+        }
+        Ok(())
+    }
+
+    /// Clears and then sets user signals on the object's peer.
+    pub fn signal_peer(&self, clear: Signal, set: Signal) -> Result<()> {
+        unsafe {
+            sys_object_signal_peer(self.as_raw(), clear.bits(), set.bits())?;
+        }
+        Ok(())
+    }
+
+    pub fn name(&self) -> Result<String> {
+        let mut name = alloc::vec![0; MAX_NAME_LEN];
+        let len = unsafe {
+            sys_object_get_property(self.as_raw(), PROP_NAME, name.as_mut_ptr(), name.len())?
+        };
+        name.truncate(len);
+        Ok(String::from_utf8_lossy(&name).into_owned())
+    }
+
+    /// Names the object for debugging. Names longer than `MAX_NAME_LEN` are truncated.
+    pub fn set_name(&self, name: &str) -> Result<()> {
+        unsafe {
+            sys_object_set_property(self.as_raw(), PROP_NAME, name.as_ptr(), name.len())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the koid, type and rights of the object behind this handle.
+    pub fn basic_info(&self) -> Result<HandleBasicInfo> {
+        self.get_info(INFO_HANDLE_BASIC)
+    }
+
+    /// Returns the kernel object ID of the object behind this handle.
+    pub fn koid(&self) -> Result<u64> {
+        Ok(self.basic_info()?.koid)
+    }
+
+    pub(crate) fn get_info<T: Pod>(&self, topic: u32) -> Result<T> {
+        let mut info = T::new_zeroed();
+        unsafe {
+            sys_object_get_info(
+                self.as_raw(),
+                topic,
+                info.as_mut_bytes().as_mut_ptr(),
+                size_of::<T>(),
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+            )?;
+        }
+        Ok(info)
+    }
+
+    /// Reads every record of `topic`, growing the buffer until they all fit.
+    pub(crate) fn get_info_vec<T: Pod>(&self, topic: u32) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+        loop {
+            let (mut actual, mut avail) = (0, 0);
+            unsafe {
+                sys_object_get_info(
+                    self.as_raw(),
+                    topic,
+                    records.as_mut_ptr() as *mut u8,
+                    records.capacity() * size_of::<T>(),
+                    &mut actual,
+                    &mut avail,
+                )?;
+                if actual == avail {
+                    records.set_len(actual);
+                    return Ok(records);
+                }
+            }
+            records.reserve(avail);
         }
     }
 }
@@ -1,9 +1,11 @@
 use core::alloc::GlobalAlloc;
 
-use spin::Mutex;
 use talc::{ClaimOnOom, Span, Talc, Talck};
 
-use crate::vm::{MMUFlags, Vmar, Vmo};
+use crate::{
+    sync::RawMutex,
+    vm::{MMUFlags, Vmar, Vmo},
+};
 
 #[global_allocator]
 static HEAP: Heap = Heap::new();
@@ -13,7 +15,7 @@ pub fn init(root_vmar: &Vmar) {
 }
 
 struct Heap {
-    inner: Talck<Mutex<()>, ClaimOnOom>,
+    inner: Talck<RawMutex, ClaimOnOom>,
 }
 
 const HEAP_SIZE: usize = 16 * 1024 * 1024 * 1024;
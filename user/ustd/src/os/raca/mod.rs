@@ -1,6 +1,14 @@
 pub use handle::*;
+pub use protocol::{
+    HandleBasicInfo, MapInfo, ProcessInfo, ThreadDebugRegs, ThreadGeneralRegs, ThreadInfo, VmoInfo,
+    WATCH_READ, WATCH_WRITE, WatchpointRegs,
+};
+pub use rights::*;
+pub use signal::*;
 
 mod entry;
 mod funcs;
 mod handle;
 mod heap;
+mod rights;
+mod signal;
@@ -0,0 +1,19 @@
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Rights: u32 {
+        const READ      = 1 << 0;
+        const WRITE     = 1 << 1;
+        const EXECUTE   = 1 << 2;
+        const MAP       = 1 << 3;
+        const DUPLICATE = 1 << 4;
+        const TRANSFER  = 1 << 5;
+        const WAIT      = 1 << 6;
+        const SIGNAL    = 1 << 7;
+        const MANAGE    = 1 << 8;
+
+        /// Keeps the rights of the source handle on duplicate and replace.
+        const SAME_RIGHTS = 1 << 31;
+    }
+}
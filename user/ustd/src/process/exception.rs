@@ -0,0 +1,72 @@
+use errors::{Errno, Result};
+use pod::Pod;
+use protocol::{EXCEPTION_STATE_KILL, EXCEPTION_STATE_RESUME};
+
+pub use protocol::{
+    EXCEPTION_KIND_GENERAL, EXCEPTION_KIND_HW_BREAKPOINT, EXCEPTION_KIND_PAGE_FAULT,
+    EXCEPTION_KIND_SINGLE_STEP, EXCEPTION_KIND_SW_BREAKPOINT, EXCEPTION_KIND_WATCHPOINT,
+    ExceptionReport,
+};
+
+use crate::{
+    ipc::Channel,
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_create_exception_channel, sys_exception_set_state},
+};
+
+/// Binds an exception channel to the thread, process or job behind `task`.
+pub(crate) fn create_exception_channel(task: BorrowedHandle) -> Result<Channel> {
+    let mut raw_handle = 0;
+    unsafe {
+        sys_create_exception_channel(task.as_raw(), &mut raw_handle)?;
+        Ok(Channel::from_handle(OwnedHandle::from_raw(raw_handle)))
+    }
+}
+
+/// An exception read from an exception channel, while the faulting thread is suspended.
+/// Dropping it without resolving passes the exception on to the next handler.
+pub struct Exception {
+    handle: OwnedHandle,
+    report: ExceptionReport,
+}
+
+impl Exception {
+    /// Waits for the next exception on `channel`.
+    pub fn read(channel: &Channel) -> Result<Self> {
+        let mut msg = channel.read_blocking()?;
+        if msg.data.len() != size_of::<ExceptionReport>() || msg.handles.len() != 1 {
+            return Err(Errno::InvArg.with_message("Not an exception message."));
+        }
+        Ok(Self {
+            handle: msg.handles.pop().unwrap(),
+            report: ExceptionReport::from_bytes(&msg.data),
+        })
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.handle.borrow()
+    }
+
+    pub fn report(&self) -> &ExceptionReport {
+        &self.report
+    }
+}
+
+impl Exception {
+    /// Resumes the faulting thread.
+    pub fn resume(self) -> Result<()> {
+        self.resolve(EXCEPTION_STATE_RESUME)
+    }
+
+    /// Terminates the faulting process.
+    pub fn kill(self) -> Result<()> {
+        self.resolve(EXCEPTION_STATE_KILL)
+    }
+
+    fn resolve(self, state: u32) -> Result<()> {
+        unsafe {
+            sys_exception_set_state(self.handle.as_raw(), state)?;
+        }
+        Ok(())
+    }
+}
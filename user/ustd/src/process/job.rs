@@ -0,0 +1,63 @@
+use bitflags::bitflags;
+use errors::Result;
+
+use crate::{
+    ipc::Channel,
+    os::raca::{BorrowedHandle, OwnedHandle},
+    process::create_exception_channel,
+    syscall::{sys_kill_job, sys_new_job, sys_set_job_policy},
+};
+
+bitflags! {
+    /// Actions denied to the processes of a job and of all its descendants.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct JobPolicy: u32 {
+        const DENY_NEW_PROCESS = 1 << 0;
+        const DENY_NEW_JOB     = 1 << 1;
+        const DENY_ACQUIRE_VMO = 1 << 2;
+    }
+}
+
+pub struct Job(pub(crate) OwnedHandle);
+
+impl Job {
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+impl Job {
+    /// Creates a child job, which inherits this job's policy.
+    pub fn new_child(&self) -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_job(self.0.as_raw(), &mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    /// Adds `policy` to the job, which must not have children or processes yet.
+    pub fn set_policy(&self, policy: JobPolicy) -> Result<()> {
+        unsafe {
+            sys_set_job_policy(self.0.as_raw(), policy.bits())?;
+        }
+        Ok(())
+    }
+
+    /// Binds a channel receiving the exceptions that no thread or process handled.
+    pub fn create_exception_channel(&self) -> Result<Channel> {
+        create_exception_channel(self.handle())
+    }
+
+    /// Kills every process in the job and its descendants.
+    pub fn kill(&self) -> Result<()> {
+        unsafe {
+            sys_kill_job(self.0.as_raw())?;
+        }
+        Ok(())
+    }
+}
@@ -31,6 +31,8 @@ pub fn load_elf(vmar: &Vmar, elf_data: &[u8]) -> Result<usize> {
         let aligned_memsz = (memsz + page_offset).div_ceil(PAGE_SIZE) * PAGE_SIZE;
 
         let vmo = Vmo::allocate(aligned_memsz / PAGE_SIZE)?;
+        vmo.handle()
+            .set_name(&alloc::format!("segment@{:#x}", aligned_vaddr))?;
 
         let flags = {
             let mut flags = MMUFlags::empty();
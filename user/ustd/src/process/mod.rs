@@ -1,11 +1,14 @@
 use alloc::vec::Vec;
 use errors::Result;
-use protocol::{PROC_HANDLE_IDX, PROC_START_HANDLE_CNT, ProcessStartInfo, VMAR_HANDLE_IDX};
+use protocol::{
+    DEADLINE_INFINITE, INFO_PROCESS, INFO_PROCESS_THREADS, PROC_HANDLE_IDX, PROC_START_HANDLE_CNT,
+    ProcessStartInfo, VMAR_HANDLE_IDX,
+};
 use spin::Once;
 
 use crate::{
     ipc::{Channel, MessagePacket},
-    os::raca::{BorrowedHandle, OwnedHandle},
+    os::raca::{BorrowedHandle, OwnedHandle, ProcessInfo, Signal},
     process::{
         loader::load_elf,
         stack::{new_user_stack, push_stack},
@@ -15,6 +18,11 @@ use crate::{
     vm::Vmar,
 };
 
+pub use exception::*;
+pub use job::*;
+
+mod exception;
+mod job;
 mod loader;
 mod stack;
 
@@ -34,13 +42,19 @@ impl Process {
         Self { handle, root_vmar }
     }
 
-    pub fn new() -> Result<Self> {
+    pub fn new(job: &Job) -> Result<Self> {
         let mut raw_handle = 0;
         let mut raw_vmar_handle = 0;
         let mut base = 0;
         let mut size = 0;
         unsafe {
-            sys_new_process(&mut raw_handle, &mut raw_vmar_handle, &mut base, &mut size).unwrap();
+            sys_new_process(
+                job.handle().as_raw(),
+                &mut raw_handle,
+                &mut raw_vmar_handle,
+                &mut base,
+                &mut size,
+            )?;
         }
         let handle = unsafe { OwnedHandle::from_raw(raw_handle) };
         let root_vmar = unsafe {
@@ -59,9 +73,31 @@ impl Process {
         &self.root_vmar
     }
 
-    pub(crate) fn handle(&self) -> BorrowedHandle {
+    pub fn handle(&self) -> BorrowedHandle {
         self.handle.borrow()
     }
+
+    /// Binds a channel receiving the exceptions of the process's threads.
+    pub fn create_exception_channel(&self) -> Result<Channel> {
+        create_exception_channel(self.handle())
+    }
+
+    /// Returns the koids of the process's threads.
+    pub fn threads(&self) -> Result<Vec<u64>> {
+        self.handle().get_info_vec(INFO_PROCESS_THREADS)
+    }
+
+    /// Returns the lifecycle state and return code of the process.
+    pub fn info(&self) -> Result<ProcessInfo> {
+        self.handle().get_info(INFO_PROCESS)
+    }
+
+    /// Waits for the process to terminate and returns its return code.
+    pub fn wait(&self) -> Result<i64> {
+        self.handle()
+            .wait_one(Signal::TERMINATED, DEADLINE_INFINITE)?;
+        Ok(self.info()?.return_code)
+    }
 }
 
 impl Process {
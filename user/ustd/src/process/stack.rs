@@ -8,6 +8,7 @@ static USER_STACK_SIZE: usize = 16 * 1024 * 1024;
 pub fn new_user_stack(vmar: &Vmar) -> Result<(Vmo, Vmar)> {
     let stack = vmar.allocate(USER_STACK_SIZE)?;
     let vmo = Vmo::allocate(stack.page_count())?;
+    vmo.handle().set_name("user-stack")?;
     stack.map(0, &vmo, MMUFlags::DATA)?;
 
     Ok((vmo, stack))
@@ -0,0 +1,53 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{
+    sync::{MutexGuard, futex_wait, futex_wake},
+    time::Instant,
+};
+
+pub struct Condvar {
+    seq: AtomicU32,
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+        }
+    }
+
+    /// Unlocks `guard` and blocks until notified, then locks it again.
+    /// Spurious wake ups are possible.
+    pub fn wait<T>(&self, guard: &mut MutexGuard<'_, T>) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        MutexGuard::unlocked(guard, || {
+            let _ = futex_wait(&self.seq, seq, None);
+        });
+    }
+
+    /// Like `wait`, but gives up at `deadline`.
+    /// Returns `true` if the deadline has passed.
+    pub fn wait_until<T>(&self, guard: &mut MutexGuard<'_, T>, deadline: Instant) -> bool {
+        let seq = self.seq.load(Ordering::Relaxed);
+        MutexGuard::unlocked(guard, || {
+            let _ = futex_wait(&self.seq, seq, Some(deadline));
+        });
+        Instant::now() >= deadline
+    }
+
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        futex_wake(&self.seq, 1);
+    }
+
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        futex_wake(&self.seq, usize::MAX);
+    }
+}
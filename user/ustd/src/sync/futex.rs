@@ -0,0 +1,44 @@
+use core::sync::atomic::AtomicU32;
+
+use errors::Result;
+
+use crate::{
+    syscall::{sys_futex_requeue, sys_futex_wait, sys_futex_wake},
+    time::Instant,
+};
+
+/// Blocks while `futex` holds `expected`, until woken or `deadline` passes.
+/// Returns `BadState` if the value has already changed.
+pub fn futex_wait(futex: &AtomicU32, expected: u32, deadline: Option<Instant>) -> Result<()> {
+    let deadline = deadline.map_or(protocol::DEADLINE_INFINITE, |d| d.as_deadline());
+    unsafe {
+        sys_futex_wait(futex.as_ptr(), expected, deadline)?;
+    }
+    Ok(())
+}
+
+/// Wakes up to `count` threads waiting on `futex`, returning how many were woken.
+pub fn futex_wake(futex: &AtomicU32, count: usize) -> usize {
+    unsafe { sys_futex_wake(futex.as_ptr(), count).unwrap() }
+}
+
+/// Wakes up to `wake_count` waiters of `futex` and moves up to `requeue_count`
+/// of the rest to `requeue`, if `futex` still holds `expected`.
+pub fn futex_requeue(
+    futex: &AtomicU32,
+    wake_count: usize,
+    expected: u32,
+    requeue: &AtomicU32,
+    requeue_count: usize,
+) -> Result<()> {
+    unsafe {
+        sys_futex_requeue(
+            futex.as_ptr(),
+            wake_count,
+            expected,
+            requeue.as_ptr(),
+            requeue_count,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub use condvar::*;
+pub use futex::*;
+pub use mutex::*;
+pub use rwlock::*;
+
+mod condvar;
+mod futex;
+mod mutex;
+mod rwlock;
@@ -0,0 +1,46 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use lock_api::GuardSend;
+
+use crate::sync::{futex_wait, futex_wake};
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+const CONTENDED: u32 = 2;
+
+/// A mutex, which parks contended threads in the kernel instead of spinning.
+pub struct RawMutex {
+    state: AtomicU32,
+}
+
+unsafe impl lock_api::RawMutex for RawMutex {
+    const INIT: Self = Self {
+        state: AtomicU32::new(UNLOCKED),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        if self.try_lock() {
+            return;
+        }
+        while self.state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+            let _ = futex_wait(&self.state, CONTENDED, None);
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            futex_wake(&self.state, 1);
+        }
+    }
+}
+
+pub type Mutex<T> = lock_api::Mutex<RawMutex, T>;
+pub type MutexGuard<'a, T> = lock_api::MutexGuard<'a, RawMutex, T>;
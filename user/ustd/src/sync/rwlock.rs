@@ -0,0 +1,85 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use lock_api::GuardSend;
+
+use crate::sync::{futex_wait, futex_wake};
+
+const WRITE_LOCKED: u32 = u32::MAX;
+
+/// A readers-writer lock, which parks contended threads in the kernel.
+/// `state` is the number of readers, or `WRITE_LOCKED`.
+pub struct RawRwLock {
+    state: AtomicU32,
+    waiters: AtomicU32,
+}
+
+impl RawRwLock {
+    fn wait(&self, state: u32) {
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let _ = futex_wait(&self.state, state, None);
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn wake_all(&self) {
+        if self.waiters.load(Ordering::SeqCst) != 0 {
+            futex_wake(&self.state, usize::MAX);
+        }
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    const INIT: Self = Self {
+        state: AtomicU32::new(0),
+        waiters: AtomicU32::new(0),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        while !self.try_lock_shared() {
+            let state = self.state.load(Ordering::SeqCst);
+            if state == WRITE_LOCKED || state == WRITE_LOCKED - 1 {
+                self.wait(state);
+            }
+        }
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        let state = self.state.load(Ordering::SeqCst);
+        state < WRITE_LOCKED - 1
+            && self
+                .state
+                .compare_exchange(state, state + 1, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+    }
+
+    unsafe fn unlock_shared(&self) {
+        if self.state.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.wake_all();
+        }
+    }
+
+    fn lock_exclusive(&self) {
+        while !self.try_lock_exclusive() {
+            let state = self.state.load(Ordering::SeqCst);
+            if state != 0 {
+                self.wait(state);
+            }
+        }
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        self.state
+            .compare_exchange(0, WRITE_LOCKED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.state.store(0, Ordering::SeqCst);
+        self.wake_all();
+    }
+}
+
+pub type RwLock<T> = lock_api::RwLock<RawRwLock, T>;
+pub type RwLockReadGuard<'a, T> = lock_api::RwLockReadGuard<'a, RawRwLock, T>;
+pub type RwLockWriteGuard<'a, T> = lock_api::RwLockWriteGuard<'a, RawRwLock, T>;
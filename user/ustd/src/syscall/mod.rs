@@ -1,5 +1,5 @@
 use errors::{Errno, Error, Result};
-use protocol::{ReadBuffer, WriteBuffer};
+use protocol::{PinRequest, PortPacket, ReadBuffer, WriteBuffer};
 
 mod r#impl;
 
@@ -23,7 +23,63 @@ gen_syscall! {
     fn sys_debug (0usize) (ptr: *const u8, len: usize);
 
     fn sys_remove_handle (1usize) (handle: u32);
-    fn sys_duplicate_handle (19usize) (handle: u32, new_handle: *mut u32);
+    fn sys_duplicate_handle (19usize) (handle: u32, rights: u32, new_handle: *mut u32);
+    fn sys_replace_handle (61usize) (handle: u32, rights: u32, new_handle: *mut u32);
+    fn sys_object_get_info (62usize) (
+        handle: u32,
+        topic: u32,
+        buf: *mut u8,
+        buf_len: usize,
+        actual: *mut usize,
+        avail: *mut usize,
+    );
+    fn sys_object_get_property (63usize) (handle: u32, prop: u32, buf: *mut u8, buf_len: usize);
+    fn sys_object_set_property (64usize) (
+        handle: u32,
+        prop: u32,
+        buf: *const u8,
+        buf_len: usize,
+    );
+
+    fn sys_object_wait_one (26usize) (
+        handle: u32,
+        signals: u32,
+        deadline: u64,
+        observed: *mut u32,
+    );
+    fn sys_object_wait_async (28usize) (
+        handle: u32,
+        port: u32,
+        key: u64,
+        signals: u32,
+    );
+
+    fn sys_object_signal (33usize) (handle: u32, clear: u32, set: u32);
+    fn sys_object_signal_peer (34usize) (handle: u32, clear: u32, set: u32);
+
+    fn sys_new_event (31usize) (handle: *mut u32);
+    fn sys_new_event_pair (32usize) (handle0_ptr: *mut u32, handle1_ptr: *mut u32);
+
+    fn sys_new_port (27usize) (handle: *mut u32);
+    fn sys_port_wait (29usize) (port: u32, deadline: u64, packet: *mut PortPacket);
+    fn sys_port_queue (30usize) (port: u32, packet: *const PortPacket);
+    fn sys_port_cancel (75usize) (port: u32, handle: u32, key: u64);
+
+    fn sys_clock_get_monotonic (35usize) ();
+    fn sys_nanosleep (36usize) (deadline: u64);
+    fn sys_new_timer (37usize) (handle: *mut u32);
+    fn sys_set_timer (38usize) (handle: u32, deadline: u64);
+    fn sys_cancel_timer (39usize) (handle: u32);
+
+    fn sys_futex_wait (40usize) (addr: *const u32, expected: u32, deadline: u64);
+    fn sys_futex_wake (41usize) (addr: *const u32, count: usize);
+    fn sys_futex_requeue (42usize) (
+        addr: *const u32,
+        wake_count: usize,
+        expected: u32,
+        requeue_addr: *const u32,
+        requeue_count: usize,
+    );
 
     fn sys_new_channel (2usize) (handle0_ptr: *mut u32, handle1_ptr: *mut u32);
     fn sys_read_channel (3usize) (
@@ -37,6 +93,29 @@ gen_syscall! {
         handle_buffer: *const WriteBuffer,
     );
 
+    fn sys_new_socket (43usize) (
+        socket_type: u32,
+        handle0_ptr: *mut u32,
+        handle1_ptr: *mut u32,
+    );
+    fn sys_read_socket (44usize) (handle: u32, buffer: *mut u8, len: usize);
+    fn sys_write_socket (45usize) (handle: u32, buffer: *const u8, len: usize);
+    fn sys_shutdown_socket (46usize) (handle: u32, options: u32);
+
+    fn sys_new_fifo (47usize) (
+        elem_count: usize,
+        elem_size: usize,
+        handle0_ptr: *mut u32,
+        handle1_ptr: *mut u32,
+    );
+    fn sys_read_fifo (48usize) (handle: u32, elem_size: usize, buffer: *mut u8, count: usize);
+    fn sys_write_fifo (49usize) (
+        handle: u32,
+        elem_size: usize,
+        buffer: *const u8,
+        count: usize,
+    );
+
     fn sys_allocate_vmar (5usize) (
         handle: u32,
         size: usize,
@@ -55,6 +134,14 @@ gen_syscall! {
         vmo_handle: u32,
         flags: u32,
     );
+    fn sys_vmar_map (74usize) (
+        handle: u32,
+        options: u32,
+        vmar_offset: usize,
+        vmo_handle: u32,
+        vmo_offset: usize,
+        len: usize,
+    );
     fn sys_unmap_vmar (8usize) (handle: u32, addr: usize, size: usize);
     fn sys_protect_vmar (9usize) (
         handle: u32,
@@ -66,14 +153,35 @@ gen_syscall! {
     fn sys_get_vmar_base (22usize) (handle: u32);
     fn sys_get_vmar_size (23usize) (handle: u32);
 
-    fn sys_allocate_vmo (10usize) (count: usize, continuous: u8, handle: *mut u32);
-    fn sys_acquire_vmo (24usize) (handle: *mut u32, addr: usize, size: usize);
+    fn sys_allocate_vmo (10usize) (
+        count: usize,
+        options: u32,
+        handle: *mut u32,
+        resource: u32,
+    );
+    fn sys_acquire_vmo (24usize) (
+        resource: u32,
+        handle: *mut u32,
+        addr: usize,
+        size: usize,
+    );
     fn sys_read_vmo (20usize) (handle: u32, offset: usize, buffer: *mut u8, size: usize);
     fn sys_write_vmo (21usize) (handle: u32, offset: usize, buffer: *const u8, size: usize);
-    fn sys_get_vmo_paddr (25usize) (handle: u32);
+    fn sys_get_vmo_paddr (25usize) (resource: u32, handle: u32);
+    fn sys_set_vmo_size (71usize) (handle: u32, size: usize);
+    fn sys_vmo_op_range (72usize) (handle: u32, op: u32, offset: usize, len: usize);
+    fn sys_set_vmo_cache_policy (73usize) (handle: u32, policy: u32);
+    fn sys_create_vmo_child (70usize) (
+        handle: u32,
+        options: u32,
+        offset: usize,
+        size: usize,
+        child: *mut u32,
+    );
 
     fn sys_exit (11usize) (exit_code: i32);
     fn sys_new_process (12usize) (
+        job: u32,
         handle: *mut u32,
         vmar_handle: *mut u32,
         base: *mut usize,
@@ -89,6 +197,32 @@ gen_syscall! {
     );
     fn sys_kill_process (17usize) (process: u32);
 
+    fn sys_new_job (50usize) (parent: u32, handle: *mut u32);
+    fn sys_set_job_policy (51usize) (handle: u32, policy: u32);
+    fn sys_kill_job (52usize) (handle: u32);
+
+    fn sys_new_resource (53usize) (
+        parent: u32,
+        kind: u32,
+        base: usize,
+        size: usize,
+        handle: *mut u32,
+    );
+    fn sys_new_interrupt (54usize) (resource: u32, vector: usize, handle: *mut u32);
+    fn sys_interrupt_wait (55usize) (handle: u32, deadline: u64, timestamp: *mut u64);
+    fn sys_interrupt_ack (56usize) (handle: u32);
+    fn sys_interrupt_bind (57usize) (handle: u32, port: u32, key: u64);
+
+    fn sys_new_bti (58usize) (resource: u32, handle: *mut u32);
+    fn sys_bti_pin (59usize) (
+        handle: u32,
+        request: *const PinRequest,
+        addrs: *mut usize,
+        pmt: *mut u32,
+    );
+    fn sys_pmt_unpin (60usize) (handle: u32);
+    fn sys_bti_release_quarantine (76usize) (handle: u32);
+
     fn sys_new_thread (14usize) (process: u32, handle: *mut u32);
     fn sys_start_thread (15usize) (
         handle: u32,
@@ -97,5 +231,20 @@ gen_syscall! {
         first_arg: usize,
     );
     fn sys_exit_thread (16usize) ();
+    fn sys_create_exception_channel (65usize) (task: u32, channel: *mut u32);
+    fn sys_exception_set_state (66usize) (handle: u32, state: u32);
+    fn sys_thread_suspend (67usize) (handle: u32, token: *mut u32);
+    fn sys_thread_read_state (68usize) (
+        handle: u32,
+        kind: u32,
+        buf: *mut u8,
+        buf_len: usize,
+    );
+    fn sys_thread_write_state (69usize) (
+        handle: u32,
+        kind: u32,
+        buf: *const u8,
+        buf_len: usize,
+    );
     fn sys_kill_thread (18usize) (thread: u32);
 }
@@ -1,4 +1,16 @@
-use crate::os::raca::{BorrowedHandle, OwnedHandle};
+use core::time::Duration;
+
+use errors::Result;
+use pod::Pod;
+use protocol::{INFO_THREAD, THREAD_REGS_DEBUG, THREAD_REGS_GENERAL, THREAD_REGS_SINGLE_STEP};
+
+use crate::{
+    ipc::Channel,
+    os::raca::{BorrowedHandle, OwnedHandle, ThreadDebugRegs, ThreadGeneralRegs, ThreadInfo},
+    process::create_exception_channel,
+    syscall::{sys_nanosleep, sys_thread_read_state, sys_thread_suspend, sys_thread_write_state},
+    time::Instant,
+};
 
 pub struct Thread {
     handle: OwnedHandle,
@@ -11,8 +23,93 @@ impl Thread {
 }
 
 impl Thread {
-    #[allow(unused)]
-    pub(crate) fn handle(&self) -> BorrowedHandle {
+    pub fn handle(&self) -> BorrowedHandle {
         self.handle.borrow()
     }
+
+    /// Binds a channel receiving the exceptions of this thread.
+    pub fn create_exception_channel(&self) -> Result<Channel> {
+        create_exception_channel(self.handle())
+    }
+
+    /// Returns the scheduling state of the thread.
+    pub fn info(&self) -> Result<ThreadInfo> {
+        self.handle().get_info(INFO_THREAD)
+    }
+
+    /// Suspends the thread until the returned token is dropped.
+    pub fn suspend(&self) -> Result<SuspendToken> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_thread_suspend(self.handle.as_raw(), &mut raw_handle)?;
+            Ok(SuspendToken {
+                _handle: OwnedHandle::from_raw(raw_handle),
+            })
+        }
+    }
+
+    /// Reads the registers of a suspended thread, or one stopped in an exception.
+    pub fn read_state(&self) -> Result<ThreadGeneralRegs> {
+        self.read_regs(THREAD_REGS_GENERAL)
+    }
+
+    /// Overwrites the registers of a stopped thread. `prmd` is left unchanged.
+    pub fn write_state(&self, regs: &ThreadGeneralRegs) -> Result<()> {
+        self.write_regs(THREAD_REGS_GENERAL, regs)
+    }
+
+    /// Reads the hardware breakpoints and watchpoints of a stopped thread.
+    pub fn read_debug_regs(&self) -> Result<ThreadDebugRegs> {
+        self.read_regs(THREAD_REGS_DEBUG)
+    }
+
+    /// Sets the hardware breakpoints and watchpoints of a stopped thread.
+    pub fn write_debug_regs(&self, regs: &ThreadDebugRegs) -> Result<()> {
+        self.write_regs(THREAD_REGS_DEBUG, regs)
+    }
+
+    /// Makes a stopped thread raise a single-step exception after every instruction.
+    pub fn set_single_step(&self, enabled: bool) -> Result<()> {
+        self.write_regs(THREAD_REGS_SINGLE_STEP, &(enabled as u32))
+    }
+
+    fn read_regs<T: Pod>(&self, kind: u32) -> Result<T> {
+        let mut regs = T::new_zeroed();
+        unsafe {
+            sys_thread_read_state(
+                self.handle.as_raw(),
+                kind,
+                regs.as_mut_bytes().as_mut_ptr(),
+                size_of::<T>(),
+            )?;
+        }
+        Ok(regs)
+    }
+
+    fn write_regs<T: Pod>(&self, kind: u32, regs: &T) -> Result<()> {
+        unsafe {
+            sys_thread_write_state(
+                self.handle.as_raw(),
+                kind,
+                regs.as_bytes().as_ptr(),
+                size_of::<T>(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a thread suspended while alive.
+pub struct SuspendToken {
+    _handle: OwnedHandle,
+}
+
+pub fn sleep(duration: Duration) {
+    sleep_until(Instant::now() + duration);
+}
+
+pub fn sleep_until(deadline: Instant) {
+    unsafe {
+        sys_nanosleep(deadline.as_deadline()).unwrap();
+    }
 }
@@ -0,0 +1,50 @@
+use core::{
+    ops::{Add, Sub},
+    time::Duration,
+};
+
+use crate::syscall::sys_clock_get_monotonic;
+
+/// A point on the kernel's monotonic clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    pub fn now() -> Self {
+        let nanos = unsafe { sys_clock_get_monotonic().unwrap() };
+        Self(Duration::from_nanos(nanos as u64))
+    }
+
+    pub(crate) fn from_nanos(nanos: u64) -> Self {
+        Self(Duration::from_nanos(nanos))
+    }
+
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// Converts to a raw deadline accepted by the wait syscalls.
+    pub fn as_deadline(&self) -> u64 {
+        self.0.as_nanos() as u64
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Self(self.0 + rhs)
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Self::Output {
+        self.duration_since(rhs)
+    }
+}
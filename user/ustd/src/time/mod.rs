@@ -0,0 +1,5 @@
+pub use instant::*;
+pub use timer::*;
+
+mod instant;
+mod timer;
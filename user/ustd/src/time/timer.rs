@@ -0,0 +1,44 @@
+use errors::Result;
+
+use crate::{
+    os::raca::{BorrowedHandle, OwnedHandle},
+    syscall::{sys_cancel_timer, sys_new_timer, sys_set_timer},
+    time::Instant,
+};
+
+/// A one-shot timer, which asserts `Signal::SIGNALED` at its deadline.
+pub struct Timer(pub(crate) OwnedHandle);
+
+impl Timer {
+    pub fn new() -> Result<Self> {
+        let mut raw_handle = 0;
+        unsafe {
+            sys_new_timer(&mut raw_handle)?;
+            Ok(Self::from_handle(OwnedHandle::from_raw(raw_handle)))
+        }
+    }
+
+    pub unsafe fn from_handle(handle: OwnedHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn handle(&self) -> BorrowedHandle {
+        self.0.borrow()
+    }
+}
+
+impl Timer {
+    pub fn set(&self, deadline: Instant) -> Result<()> {
+        unsafe {
+            sys_set_timer(self.0.as_raw(), deadline.as_deadline())?;
+        }
+        Ok(())
+    }
+
+    pub fn cancel(&self) -> Result<()> {
+        unsafe {
+            sys_cancel_timer(self.0.as_raw())?;
+        }
+        Ok(())
+    }
+}
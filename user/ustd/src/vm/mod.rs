@@ -21,3 +21,13 @@ bitflags! {
         const RWX = Self::READ.bits() | Self::WRITE.bits() | Self::EXECUTE.bits();
     }
 }
+
+/// How the pages of a VMO are cached once mapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    Cached,
+    /// Strongly ordered and uncached, for device registers.
+    Uncached,
+    /// Uncached, but writes may be merged, for framebuffers.
+    WriteCombining,
+}
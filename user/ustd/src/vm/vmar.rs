@@ -1,15 +1,62 @@
+use alloc::vec::Vec;
 use errors::Result;
+use protocol::{
+    INFO_VMAR_MAPS, VM_ALIGN_MASK, VM_ALIGN_SHIFT, VM_MAP_RANGE, VM_SPECIFIC, VM_SPECIFIC_OVERWRITE,
+};
 
 use crate::{
-    os::raca::{BorrowedHandle, OwnedHandle},
+    os::raca::{BorrowedHandle, MapInfo, OwnedHandle},
     process::Process,
     syscall::{
         sys_allocate_vmar, sys_allocate_vmar_at, sys_get_vmar_base, sys_get_vmar_size,
-        sys_map_vmar, sys_protect_vmar, sys_unmap_vmar,
+        sys_map_vmar, sys_protect_vmar, sys_unmap_vmar, sys_vmar_map,
     },
     vm::{MMUFlags, PAGE_SIZE, Vmo},
 };
 
+/// Where and how [`Vmar::map_with`] places a mapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapOptions {
+    options: u32,
+    offset: usize,
+}
+
+impl MapOptions {
+    /// Lets the kernel choose a page-aligned free range.
+    pub const fn new() -> Self {
+        Self {
+            options: 0,
+            offset: 0,
+        }
+    }
+
+    /// Maps at `offset` from the VMAR base, which must be free.
+    pub fn specific(mut self, offset: usize) -> Self {
+        self.options = self.options & !VM_SPECIFIC_OVERWRITE | VM_SPECIFIC;
+        self.offset = offset;
+        self
+    }
+
+    /// Maps at `offset` from the VMAR base, replacing the mappings there.
+    pub fn specific_overwrite(mut self, offset: usize) -> Self {
+        self.options = self.options & !VM_SPECIFIC | VM_SPECIFIC_OVERWRITE;
+        self.offset = offset;
+        self
+    }
+
+    /// Aligns a range chosen by the kernel to `align`, a power of two no smaller than a page.
+    pub fn align(mut self, align: usize) -> Self {
+        self.options = self.options & !VM_ALIGN_MASK | align.trailing_zeros() << VM_ALIGN_SHIFT;
+        self
+    }
+
+    /// Commits and maps every page up front instead of on first access.
+    pub fn map_range(mut self) -> Self {
+        self.options |= VM_MAP_RANGE;
+        self
+    }
+}
+
 pub struct Vmar {
     handle: OwnedHandle,
     base: usize,
@@ -38,9 +85,14 @@ impl Vmar {
         Self { handle, base, size }
     }
 
-    pub(crate) fn handle(&self) -> BorrowedHandle {
+    pub fn handle(&self) -> BorrowedHandle {
         self.handle.borrow()
     }
+
+    /// Lists the mappings of this VMAR and its children, sorted by address.
+    pub fn mappings(&self) -> Result<Vec<MapInfo>> {
+        self.handle().get_info_vec(INFO_VMAR_MAPS)
+    }
 }
 
 impl Vmar {
@@ -102,6 +154,28 @@ impl Vmar {
         Ok(())
     }
 
+    /// Maps `len` bytes of `vmo` from the page-aligned `vmo_offset` on,
+    /// and returns the address of the mapping.
+    pub fn map_with(
+        &self,
+        options: MapOptions,
+        vmo: &Vmo,
+        vmo_offset: usize,
+        len: usize,
+        flags: MMUFlags,
+    ) -> Result<usize> {
+        unsafe {
+            sys_vmar_map(
+                self.handle.as_raw(),
+                options.options | flags.bits(),
+                options.offset,
+                vmo.handle().as_raw(),
+                vmo_offset,
+                len,
+            )
+        }
+    }
+
     pub fn unmap(&self, addr: usize, size: usize) -> Result<()> {
         unsafe {
             sys_unmap_vmar(self.handle.as_raw(), addr, size)?;
@@ -1,10 +1,20 @@
 use errors::Result;
 use pod::Pod;
+use protocol::{
+    CACHE_POLICY_CACHED, CACHE_POLICY_UNCACHED, CACHE_POLICY_WRITE_COMBINING, INFO_VMO,
+    PROP_VMO_CONTENT_SIZE, VMO_CHILD_SLICE, VMO_CHILD_SNAPSHOT, VMO_CONTINUOUS, VMO_OP_COMMIT,
+    VMO_OP_DECOMMIT, VMO_OP_ZERO, VMO_RESIZABLE,
+};
 
 use crate::{
-    os::raca::{BorrowedHandle, OwnedHandle},
-    syscall::{sys_acquire_vmo, sys_allocate_vmo, sys_get_vmo_paddr, sys_read_vmo, sys_write_vmo},
-    vm::PAGE_SIZE,
+    dev::Resource,
+    os::raca::{BorrowedHandle, OwnedHandle, VmoInfo},
+    syscall::{
+        sys_acquire_vmo, sys_allocate_vmo, sys_create_vmo_child, sys_get_vmo_paddr,
+        sys_object_get_property, sys_object_set_property, sys_read_vmo, sys_set_vmo_cache_policy,
+        sys_set_vmo_size, sys_vmo_op_range, sys_write_vmo,
+    },
+    vm::{CachePolicy, PAGE_SIZE},
 };
 
 pub struct Vmo {
@@ -25,7 +35,19 @@ impl Vmo {
     pub fn allocate(count: usize) -> Result<Self> {
         let mut raw_handle = 0u32;
         unsafe {
-            sys_allocate_vmo(count, 0, &mut raw_handle)?;
+            sys_allocate_vmo(count, 0, &mut raw_handle, 0)?;
+            Ok(Self::from_handle_len(
+                OwnedHandle::from_raw(raw_handle),
+                count * PAGE_SIZE,
+            ))
+        }
+    }
+
+    /// Allocates pages that can later be grown or shrunk with [`Vmo::set_size`].
+    pub fn allocate_resizable(count: usize) -> Result<Self> {
+        let mut raw_handle = 0u32;
+        unsafe {
+            sys_allocate_vmo(count, VMO_RESIZABLE, &mut raw_handle, 0)?;
             Ok(Self::from_handle_len(
                 OwnedHandle::from_raw(raw_handle),
                 count * PAGE_SIZE,
@@ -33,10 +55,11 @@ impl Vmo {
         }
     }
 
-    pub fn allocate_continuous(count: usize) -> Result<Self> {
+    /// Allocates physically contiguous pages, which requires the root resource.
+    pub fn allocate_continuous(resource: &Resource, count: usize) -> Result<Self> {
         let mut raw_handle = 0u32;
         unsafe {
-            sys_allocate_vmo(count, 1, &mut raw_handle)?;
+            sys_allocate_vmo(count, VMO_CONTINUOUS, &mut raw_handle, resource.0.as_raw())?;
             Ok(Self {
                 handle: OwnedHandle::from_raw(raw_handle),
                 len: count * PAGE_SIZE,
@@ -45,10 +68,36 @@ impl Vmo {
         }
     }
 
-    pub fn acquire(addr: usize, size: usize) -> Result<Self> {
+    /// Maps the physical range `addr..addr + size`, which `resource` must cover.
+    pub fn acquire(resource: &Resource, addr: usize, size: usize) -> Result<Self> {
+        let mut raw_handle = 0u32;
+        unsafe {
+            sys_acquire_vmo(resource.0.as_raw(), &mut raw_handle, addr, size)?;
+            Ok(Self::from_handle_len(
+                OwnedHandle::from_raw(raw_handle),
+                size,
+            ))
+        }
+    }
+}
+
+impl Vmo {
+    /// Creates a VMO sharing the pages of `offset..offset + size`,
+    /// so that writes on either side are seen by both.
+    pub fn slice(&self, offset: usize, size: usize) -> Result<Self> {
+        self.create_child(VMO_CHILD_SLICE, offset, size)
+    }
+
+    /// Creates a copy of `offset..offset + size` whose pages are only
+    /// duplicated once either side writes them.
+    pub fn snapshot(&self, offset: usize, size: usize) -> Result<Self> {
+        self.create_child(VMO_CHILD_SNAPSHOT, offset, size)
+    }
+
+    fn create_child(&self, options: u32, offset: usize, size: usize) -> Result<Self> {
         let mut raw_handle = 0u32;
         unsafe {
-            sys_acquire_vmo(&mut raw_handle, addr, size)?;
+            sys_create_vmo_child(self.handle.as_raw(), options, offset, size, &mut raw_handle)?;
             Ok(Self::from_handle_len(
                 OwnedHandle::from_raw(raw_handle),
                 size,
@@ -58,9 +107,11 @@ impl Vmo {
 }
 
 impl Vmo {
-    pub fn start(&self) -> Option<usize> {
+    pub fn start(&self, resource: &Resource) -> Option<usize> {
         self.continuous
-            .then(|| unsafe { Some(sys_get_vmo_paddr(self.handle.as_raw()).ok()?) })
+            .then(|| unsafe {
+                Some(sys_get_vmo_paddr(resource.0.as_raw(), self.handle.as_raw()).ok()?)
+            })
             .flatten()
     }
 
@@ -72,9 +123,86 @@ impl Vmo {
         self.len.div_ceil(PAGE_SIZE) * PAGE_SIZE
     }
 
-    pub(super) fn handle(&self) -> BorrowedHandle {
+    pub fn handle(&self) -> BorrowedHandle {
         self.handle.borrow()
     }
+
+    /// Returns the koid, size and committed pages of the VMO.
+    pub fn info(&self) -> Result<VmoInfo> {
+        self.handle().get_info(INFO_VMO)
+    }
+}
+
+impl Vmo {
+    /// Resizes a VMO from [`Vmo::allocate_resizable`], rounding up to whole pages.
+    /// The content size becomes `size`.
+    pub fn set_size(&mut self, size: usize) -> Result<()> {
+        unsafe {
+            sys_set_vmo_size(self.handle.as_raw(), size)?;
+        }
+        self.len = size.div_ceil(PAGE_SIZE) * PAGE_SIZE;
+        Ok(())
+    }
+
+    /// Returns how many bytes of the VMO hold data.
+    pub fn content_size(&self) -> Result<usize> {
+        let mut size = 0usize;
+        unsafe {
+            sys_object_get_property(
+                self.handle.as_raw(),
+                PROP_VMO_CONTENT_SIZE,
+                (&raw mut size).cast(),
+                size_of::<usize>(),
+            )?;
+        }
+        Ok(size)
+    }
+
+    pub fn set_content_size(&self, size: usize) -> Result<()> {
+        unsafe {
+            sys_object_set_property(
+                self.handle.as_raw(),
+                PROP_VMO_CONTENT_SIZE,
+                (&raw const size).cast(),
+                size_of::<usize>(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// RAM can only change policy before it is committed or mapped.
+    pub fn set_cache_policy(&self, policy: CachePolicy) -> Result<()> {
+        let policy = match policy {
+            CachePolicy::Cached => CACHE_POLICY_CACHED,
+            CachePolicy::Uncached => CACHE_POLICY_UNCACHED,
+            CachePolicy::WriteCombining => CACHE_POLICY_WRITE_COMBINING,
+        };
+        unsafe {
+            sys_set_vmo_cache_policy(self.handle.as_raw(), policy)?;
+        }
+        Ok(())
+    }
+
+    /// Commits the pages covering `offset..offset + len` ahead of use.
+    pub fn commit(&self, offset: usize, len: usize) -> Result<()> {
+        self.op_range(VMO_OP_COMMIT, offset, len)
+    }
+
+    /// Frees the pages covering `offset..offset + len`, which then read as zero.
+    pub fn decommit(&self, offset: usize, len: usize) -> Result<()> {
+        self.op_range(VMO_OP_DECOMMIT, offset, len)
+    }
+
+    pub fn zero(&self, offset: usize, len: usize) -> Result<()> {
+        self.op_range(VMO_OP_ZERO, offset, len)
+    }
+
+    fn op_range(&self, op: u32, offset: usize, len: usize) -> Result<()> {
+        unsafe {
+            sys_vmo_op_range(self.handle.as_raw(), op, offset, len)?;
+        }
+        Ok(())
+    }
 }
 
 impl Vmo {